@@ -18,6 +18,7 @@ use rememex_lib::config::{get_embedding_model, get_table_name, load_config, Conf
 use rememex_lib::indexer;
 use rememex_lib::indexer::annotations;
 use rememex_lib::indexer::embedding_provider::{EmbeddingProvider, LocalProvider, RemoteProvider};
+use rememex_lib::indexer::jobs::{self, JobAction};
 use rememex_lib::state::ModelState;
 
 #[global_allocator]
@@ -28,6 +29,7 @@ struct AppState {
     provider: Arc<Mutex<Box<dyn EmbeddingProvider>>>,
     reranker: Arc<Mutex<Option<fastembed::TextRerank>>>,
     config: Config,
+    jobs_dir: PathBuf,
 }
 
 #[derive(Clone)]
@@ -46,7 +48,7 @@ struct SearchParams {
     file_extensions: Option<Vec<String>>,
     #[schemars(description = "Filter by path prefix, e.g. \"src/indexer\"")]
     path_prefix: Option<String>,
-    #[schemars(description = "Max snippet size in bytes (default 1500, max 10000)")]
+    #[schemars(description = "Max snippet size in bytes (default 1500, max 10000). Long sections are cut around the matched lines")]
     context_bytes: Option<usize>,
     #[schemars(description = "Minimum relevance score (0-100). Results below this are filtered out. Default: no filtering.")]
     min_score: Option<f32>,
@@ -55,12 +57,14 @@ struct SearchParams {
 
 #[derive(Deserialize, schemars::JsonSchema)]
 struct ReadFileParams {
-    #[schemars(description = "Absolute path to the file to read. Must be within an indexed container.")]
+    #[schemars(description = "Absolute path to the file to read. Must be within an indexed container. Archive entries use virtual paths like /docs/bundle.zip!/readme.md.")]
     path: String,
     #[schemars(description = "Start line (1-indexed, inclusive). Omit to read from beginning.")]
     start_line: Option<u32>,
     #[schemars(description = "End line (1-indexed, inclusive). Omit to read to end.")]
     end_line: Option<u32>,
+    #[schemars(description = "Page number (1-indexed) for PDF files. Line range applies within the page.")]
+    page: Option<u32>,
 }
 
 #[derive(Deserialize, schemars::JsonSchema)]
@@ -118,6 +122,14 @@ struct DeleteAnnotationParams {
     container: Option<String>,
 }
 
+#[derive(Deserialize, schemars::JsonSchema)]
+struct JobsParams {
+    #[schemars(description = "One of 'list' (default), 'cancel', 'pause', 'resume'.")]
+    action: Option<String>,
+    #[schemars(description = "Job ID (e.g. 'job_...') to control. Required for cancel, pause and resume. Get IDs from action 'list'.")]
+    job_id: Option<String>,
+}
+
 fn is_path_within_container(file_path: &Path, config: &Config, container_name: &str) -> bool {
     let canonical = match std::fs::canonicalize(file_path) {
         Ok(p) => p,
@@ -235,18 +247,18 @@ impl RememexServer {
             if used_hybrid {
                 for (rank, (path, note, _dist)) in ann_results.into_iter().enumerate() {
                     let rrf_score = 1.0 / (60.0 + rank as f32 + 1.0);
-                    merged.push((path, note, rrf_score));
+                    merged.push((path, note, rrf_score, None));
                 }
                 merged.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
             } else {
                 for (path, note, dist) in ann_results {
-                    merged.push((path, note, dist));
+                    merged.push((path, note, dist, None));
                 }
                 merged.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
             }
         }
 
-        let rerank_input: Vec<(String, String, f32)> =
+        let rerank_input: Vec<indexer::SearchHit> =
             merged.into_iter().take(top_k * 2).collect();
 
         let (final_results, used_reranker) = {
@@ -280,13 +292,7 @@ impl RememexServer {
         scored.retain(|item| item.score >= min_score.unwrap_or(0.0));
 
         for item in &mut scored {
-            if item.snippet.len() > context_bytes {
-                let mut end = context_bytes;
-                while end > 0 && !item.snippet.is_char_boundary(end) {
-                    end -= 1;
-                }
-                item.snippet = item.snippet[..end].to_string();
-            }
+            indexer::pipeline::clip_snippet(item, context_bytes);
         }
 
         let json = serde_json::to_string_pretty(&scored)
@@ -296,14 +302,17 @@ impl RememexServer {
     }
 
     #[tool(
-        description = "Read file content by path. Supports optional line range. The file must be within an indexed container."
+        description = "Read file content by path. Supports optional line range, a page number for PDFs, and archive entries via virtual paths (bundle.zip!/inner/file.md). The file must be within an indexed container."
     )]
     async fn rememex_read_file(
         &self,
-        Parameters(ReadFileParams { path, start_line, end_line }): Parameters<ReadFileParams>,
+        Parameters(ReadFileParams { path, start_line, end_line, page }): Parameters<ReadFileParams>,
     ) -> Result<CallToolResult, McpError> {
-        debug!("rememex_read_file: path={}, lines={:?}-{:?}", path, start_line, end_line);
-        let file_path = PathBuf::from(&path);
+        debug!("rememex_read_file: path={}, lines={:?}-{:?}, page={:?}", path, start_line, end_line, page);
+        let (file_path, entry) = match indexer::archive::split_virtual(&path) {
+            Some((archive, inner)) => (PathBuf::from(archive), Some(inner)),
+            None => (PathBuf::from(&path), None),
+        };
 
         let mut authorized = false;
         for name in self.state.config.containers.keys() {
@@ -324,8 +333,51 @@ impl RememexServer {
             )]));
         }
 
-        let content = std::fs::read_to_string(&file_path)
-            .map_err(|e| McpError::internal_error(format!("failed to read file: {}", e), None))?;
+        let bytes = match entry {
+            Some(inner) => indexer::archive::read_entry(&file_path, inner).map_err(|e| {
+                McpError::internal_error(format!("failed to read archive entry: {}", e), None)
+            })?,
+            None => std::fs::read(&file_path)
+                .map_err(|e| McpError::internal_error(format!("failed to read file: {}", e), None))?,
+        };
+        let file_name = Path::new(entry.unwrap_or(&path))
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("");
+        let extract = |bytes: &[u8]| {
+            indexer::file_io::extract_content(file_name, bytes, &self.state.config.indexing)
+        };
+
+        let is_pdf = Path::new(file_name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("pdf"));
+        let content = if is_pdf {
+            let text = extract(&bytes)
+                .ok_or_else(|| McpError::internal_error("failed to extract PDF text", None))?;
+            let pages: Vec<&str> = text.split(indexer::file_io::PAGE_BREAK).collect();
+            match page {
+                Some(n) => match pages.get((n as usize).saturating_sub(1)) {
+                    Some(text) if n > 0 => text.to_string(),
+                    _ => {
+                        return Ok(CallToolResult::success(vec![Content::text(format!(
+                            "page {} out of range: {} has {} pages",
+                            n,
+                            path,
+                            pages.len()
+                        ))]));
+                    }
+                },
+                None => pages.join("\n"),
+            }
+        } else {
+            match String::from_utf8(bytes) {
+                Ok(text) => text,
+                Err(e) => extract(e.as_bytes()).ok_or_else(|| {
+                    McpError::internal_error("failed to read file: not valid UTF-8 text", None)
+                })?,
+            }
+        };
 
         let output = match (start_line, end_line) {
             (Some(start), Some(end)) => {
@@ -608,7 +660,7 @@ impl RememexServer {
                         entry["preview"] = serde_json::json!(preview);
                         entry["total_lines"] = serde_json::json!(total_lines);
                     }
-                } else {
+                } else if indexer::archive::split_virtual(path).is_none() {
                     entry["status"] = serde_json::json!("deleted");
                 }
             }
@@ -823,76 +875,17 @@ impl RememexServer {
                 .ok_or_else(|| McpError::internal_error("Empty embedding result", None))?
         };
 
-        let dim = vector.len();
-        let ann_table_name = format!("{}_annotations", table_name);
-        let ann_table = if let Ok(t) = self.state.db.open_table(&ann_table_name).execute().await {
-            t
-        } else {
-            use arrow_schema::{DataType, Field, Schema};
-            use arrow_array::RecordBatchIterator;
-            let schema = std::sync::Arc::new(Schema::new(vec![
-                Field::new("id", DataType::Utf8, false),
-                Field::new("path", DataType::Utf8, false),
-                Field::new("note", DataType::Utf8, false),
-                Field::new("source", DataType::Utf8, false),
-                Field::new("vector", DataType::FixedSizeList(
-                    std::sync::Arc::new(Field::new("item", DataType::Float32, true)),
-                    dim as i32,
-                ), false),
-                Field::new("created_at", DataType::Int64, false),
-            ]));
-            self.state.db.create_table(&ann_table_name, RecordBatchIterator::new(vec![], schema))
-                .execute().await
-                .map_err(|e| McpError::internal_error(e.to_string(), None))?
-        };
-
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
-        let id = format!("ann_{}", now.as_nanos());
-        let created_at = now.as_secs() as i64;
-
-        use arrow_array::*;
-        use arrow_schema::{DataType, Field, Schema};
-        let schema = std::sync::Arc::new(Schema::new(vec![
-            Field::new("id", DataType::Utf8, false),
-            Field::new("path", DataType::Utf8, false),
-            Field::new("note", DataType::Utf8, false),
-            Field::new("source", DataType::Utf8, false),
-            Field::new("vector", DataType::FixedSizeList(
-                std::sync::Arc::new(Field::new("item", DataType::Float32, true)),
-                dim as i32,
-            ), false),
-            Field::new("created_at", DataType::Int64, false),
-        ]));
-        let vector_array = FixedSizeListArray::try_new(
-            std::sync::Arc::new(Field::new("item", DataType::Float32, true)),
-            dim as i32,
-            std::sync::Arc::new(Float32Array::from(vector)),
-            None,
-        ).map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-        let batch = RecordBatch::try_new(
-            schema.clone(),
-            vec![
-                std::sync::Arc::new(StringArray::from(vec![id.as_str()])),
-                std::sync::Arc::new(StringArray::from(vec![path.as_str()])),
-                std::sync::Arc::new(StringArray::from(vec![note.as_str()])),
-                std::sync::Arc::new(StringArray::from(vec!["agent"])),
-                std::sync::Arc::new(vector_array),
-                std::sync::Arc::new(Int64Array::from(vec![created_at])),
-            ],
-        ).map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-        ann_table.add(RecordBatchIterator::new(vec![Ok(batch)], schema))
-            .execute().await
+        let annotation = annotations::Annotation::new(&path, &note, "agent");
+        annotations::insert_annotation(&self.state.db, &table_name, &annotation, vector)
+            .await
             .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
         let result = serde_json::json!({
-            "id": id,
-            "path": path,
-            "note": note,
-            "source": "agent",
-            "created_at": created_at
+            "id": annotation.id,
+            "path": annotation.path,
+            "note": annotation.note,
+            "source": annotation.source,
+            "created_at": annotation.created_at
         });
         Ok(CallToolResult::success(vec![Content::text(result.to_string())]))
     }
@@ -964,6 +957,44 @@ impl RememexServer {
         });
         Ok(CallToolResult::success(vec![Content::text(result.to_string())]))
     }
+
+    #[tool(
+        description = "List indexing jobs (status, progress checkpoint, files indexed, skipped files with reasons) or control one: cancel, pause, or resume. Resuming an interrupted job continues from its last checkpoint. Control requests are picked up by the running Rememex app."
+    )]
+    async fn rememex_jobs(
+        &self,
+        Parameters(JobsParams { action, job_id }): Parameters<JobsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let action = action.unwrap_or_else(|| "list".to_string());
+        if action.trim().eq_ignore_ascii_case("list") {
+            let mut all = jobs::read_job_files(&self.state.jobs_dir);
+            all.sort_by_key(|j| std::cmp::Reverse(j.started_at));
+            let json = serde_json::to_string_pretty(&all)
+                .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            return Ok(CallToolResult::success(vec![Content::text(json)]));
+        }
+
+        let Some(job_action) = JobAction::parse(&action) else {
+            return Ok(CallToolResult::success(vec![Content::text(
+                format!("unknown action '{}'. use list, cancel, pause or resume.", action),
+            )]));
+        };
+        let Some(job_id) = job_id else {
+            return Ok(CallToolResult::success(vec![Content::text(
+                format!("job_id is required for action '{}'.", job_action.as_str()),
+            )]));
+        };
+
+        if let Err(e) = jobs::request_control(&self.state.jobs_dir, &job_id, job_action) {
+            return Ok(CallToolResult::success(vec![Content::text(e)]));
+        }
+
+        let result = serde_json::json!({
+            "requested": job_action.as_str(),
+            "id": job_id,
+        });
+        Ok(CallToolResult::success(vec![Content::text(result.to_string())]))
+    }
 }
 
 #[tool_handler]
@@ -987,6 +1018,7 @@ impl ServerHandler for RememexServer {
                  Use rememex_annotate to add searchable notes to files (they appear in future searches). \
                  Use rememex_annotations to list existing annotations. \
                  Use rememex_delete_annotation to remove outdated agent-created annotations by ID (user annotations are protected). \
+                 Use rememex_jobs to list indexing jobs and cancel, pause or resume them. \
                  Use rememex_list_containers to see available search scopes."
                     .into(),
             ),
@@ -1048,6 +1080,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         provider: Arc::new(Mutex::new(provider)),
         reranker: Arc::new(Mutex::new(reranker)),
         config,
+        jobs_dir: app_data.join("jobs"),
     });
 
     let server = RememexServer::new(state);
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use log::{info, error, debug};
+use log::{info, error, debug, warn};
 
 use serde::{Deserialize, Serialize};
 use tauri::{Emitter, Manager};
@@ -9,9 +9,14 @@ use tokio::sync::Mutex;
 use crate::config::{get_table_name, ConfigState, EmbeddingProviderConfig};
 use crate::indexer;
 use crate::indexer::annotations;
+use crate::indexer::chunking::ChunkUnit;
 use crate::indexer::embedding_provider::RemoteProviderConfig;
+use crate::indexer::jobs::{Job, JobAction, JobInfo, JobState, JobStatus};
+use crate::indexer::maintenance::{MaintenanceState, MaintenanceStatus};
+use crate::indexer::schema::{self, RebuildRequired};
+use crate::indexer::snapshot::{self, ProviderIdentity, SnapshotManifest};
 use crate::state::{
-    ContainerListItem, DbState, IndexingProgress, ProviderState, RerankerState, SearchResult,
+    ContainerListItem, DbState, ProviderState, RerankerState, SearchResult,
 };
 use crate::watcher;
 
@@ -46,6 +51,7 @@ pub async fn create_container(
     remote_api_key: Option<String>,
     remote_model: Option<String>,
     remote_dimensions: Option<usize>,
+    remote_max_tokens: Option<usize>,
     config_state: tauri::State<'_, ConfigState>,
 ) -> Result<(), String> {
     info!("create_container: name=\"{}\" provider_type={}", name, provider_type);
@@ -61,6 +67,7 @@ pub async fn create_container(
             api_key: remote_api_key,
             model: remote_model.unwrap_or_default(),
             dimensions: remote_dimensions.unwrap_or(1024),
+            max_tokens: remote_max_tokens.filter(|&v| v > 0),
         })
     } else {
         EmbeddingProviderConfig::Local {
@@ -72,6 +79,7 @@ pub async fn create_container(
         description,
         indexed_paths: Vec::new(),
         embedding_provider: Some(provider),
+        roots: Default::default(),
     });
     drop(config);
     config_state.save().await?;
@@ -103,7 +111,7 @@ pub async fn delete_container(
         guard.db.clone()
     };
     let table_name = get_table_name(&name);
-    let _ = db.drop_table(&table_name, &[]).await;
+    let _ = schema::drop_versioned_table(&db, &table_name).await;
 
     Ok(())
 }
@@ -274,18 +282,18 @@ pub async fn search(
         if used_hybrid {
             for (rank, (path, note, _dist)) in ann_results.into_iter().enumerate() {
                 let rrf_score = 1.0 / (60.0 + rank as f32 + 1.0);
-                merged.push((path, note, rrf_score));
+                merged.push((path, note, rrf_score, None));
             }
             merged.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
         } else {
             for (path, note, dist) in ann_results {
-                merged.push((path, note, dist));
+                merged.push((path, note, dist, None));
             }
             merged.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
         }
     }
 
-    let rerank_input: Vec<(String, String, f32)> = merged.into_iter().take(15).collect();
+    let rerank_input: Vec<indexer::SearchHit> = merged.into_iter().take(15).collect();
 
     let reranker_enabled = {
         let config = config_state.config.lock().await;
@@ -324,10 +332,132 @@ pub async fn search(
             path: r.path,
             snippet: r.snippet,
             score: r.score,
+            meta: r.meta,
         })
         .collect())
 }
 
+async fn run_indexing_job(app: &tauri::AppHandle, job: &Arc<Job>) -> Result<usize, String> {
+    let info = job.info();
+    let table_name = get_table_name(&info.container);
+
+    let db = {
+        let db_state = app.state::<Arc<Mutex<DbState>>>();
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+    let ps = app.state::<Arc<Mutex<ProviderState>>>().inner().clone();
+    let config_state = app.state::<ConfigState>();
+    let (indexing_config, root, nested, added) = {
+        let mut config = config_state.config.lock().await;
+        let container = config.containers.get_mut(&info.container);
+        let existing = container.as_ref().and_then(|c| c.root(&info.root_dir));
+        let added = existing.is_none() && container.is_some();
+        let (root, nested) = match container {
+            Some(c) => {
+                let root = existing.unwrap_or_else(|| c.ensure_root(&info.root_dir));
+                let nested = c.nested_roots(&root);
+                (Some(root), nested)
+            }
+            None => (None, Vec::new()),
+        };
+        (config.indexing.clone(), root, nested, added)
+    };
+    if added {
+        if let Err(e) = config_state.save().await {
+            error!("Failed to save root mapping for {}: {}", info.root_dir, e);
+        }
+    }
+
+    let _ = app.emit("indexing-job", &info);
+    let result = match root {
+        Some(root) => {
+            let app_handle = app.clone();
+            indexer::index_directory(&root, &nested, &table_name, &db, &ps, &indexing_config, job, move |progress| {
+                let _ = app_handle.emit("indexing-progress", progress);
+            })
+            .await
+            .map_err(|e| {
+                if let Some(rebuild) = e.downcast_ref::<RebuildRequired>() {
+                    let _ = app.emit("index-rebuild-required", rebuild.clone());
+                }
+                e.to_string()
+            })
+        }
+        None => Err(format!("Container '{}' does not exist", info.container)),
+    };
+
+    job.finish(&result);
+    let _ = app.emit("indexing-job", job.info());
+    result
+}
+
+fn indexed_message(count: usize, job: &Job) -> String {
+    match job.info().skipped.len() {
+        0 => format!("{} files indexed", count),
+        skipped => format!("{} files indexed, {} skipped (too large)", count, skipped),
+    }
+}
+
+pub async fn apply_job_action(
+    app: &tauri::AppHandle,
+    job_id: &str,
+    action: JobAction,
+) -> Result<JobInfo, String> {
+    info!("apply_job_action: job={} action={}", job_id, action.as_str());
+    let jobs = app.state::<JobState>().inner().clone();
+
+    if let Some(job) = jobs.get(job_id).await {
+        if !job.info().status.is_finished() {
+            match action {
+                JobAction::Cancel => job.cancel(),
+                JobAction::Pause => job.pause(),
+                JobAction::Resume => job.resume(),
+            }
+            let info = job.info();
+            let _ = app.emit("indexing-job", &info);
+            return Ok(info);
+        }
+    }
+
+    if action != JobAction::Resume {
+        return Err(format!("Job {} is not running", job_id));
+    }
+
+    let job = jobs.restart(job_id).await?;
+    let info = job.info();
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match run_indexing_job(&app_handle, &job).await {
+            Ok(count) => {
+                let _ = app_handle.emit("indexing-complete", indexed_message(count, &job));
+            }
+            Err(e) => error!("Resumed job {} failed: {}", job.id(), e),
+        }
+    });
+    Ok(info)
+}
+
+pub async fn resume_interrupted_jobs(app: &tauri::AppHandle) {
+    let jobs = app.state::<JobState>().inner().clone();
+    for job_id in jobs.take_interrupted() {
+        info!("Resuming interrupted indexing job {}", job_id);
+        let job = match jobs.restart(&job_id).await {
+            Ok(job) => job,
+            Err(e) => {
+                warn!("Cannot resume job {}: {}", job_id, e);
+                continue;
+            }
+        };
+        match run_indexing_job(app, &job).await {
+            Ok(count) => {
+                let _ = app.emit("indexing-complete", indexed_message(count, &job));
+            }
+            Err(e) => error!("Resumed job {} failed: {}", job_id, e),
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn index_folder(
     app: tauri::AppHandle,
@@ -336,45 +466,31 @@ pub async fn index_folder(
     provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
     config_state: tauri::State<'_, ConfigState>,
     watcher_state: tauri::State<'_, watcher::WatcherState>,
+    job_state: tauri::State<'_, JobState>,
 ) -> Result<String, String> {
     info!("index_folder: dir=\"{}\"", dir);
-    let table_name = {
+    let container = {
         let config = config_state.config.lock().await;
-        get_table_name(&config.active_container)
+        config.active_container.clone()
     };
 
     {
         let mut config = config_state.config.lock().await;
-        let active = config.active_container.clone();
-        if let Some(info) = config.containers.get_mut(&active) {
-            if !info.indexed_paths.contains(&dir) {
-                info.indexed_paths.push(dir.clone());
-            }
+        if let Some(info) = config.containers.get_mut(&container) {
+            info.ensure_root(&dir);
         }
         drop(config);
         config_state.save().await?;
     }
 
-    let db = {
-        let guard = db_state.lock().await;
-        guard.db.clone()
-    };
-
-    let ps = provider_state.inner().clone();
-    let app_handle = app.clone();
+    let job = job_state.start(&container, &dir).await;
+    let count = run_indexing_job(&app, &job).await?;
 
-    let indexing_config = {
-        let config = config_state.config.lock().await;
-        config.indexing.clone()
-    };
-
-    let count = indexer::index_directory(&dir, &table_name, &db, &ps, &indexing_config, move |current, total, path| {
-        let _ = app_handle.emit("indexing-progress", IndexingProgress { current, total, path });
-    })
-    .await
-    .map_err(|e| e.to_string())?;
-
-    let _ = app.emit("indexing-complete", format!("{} files indexed", count));
+    if job.is_cancelled() {
+        let _ = app.emit("indexing-complete", format!("Cancelled after {} files", count));
+    } else {
+        let _ = app.emit("indexing-complete", indexed_message(count, &job));
+    }
 
     let db2 = {
         let guard = db_state.lock().await;
@@ -415,48 +531,313 @@ pub async fn reset_index(
 #[tauri::command]
 pub async fn reindex_all(
     app: tauri::AppHandle,
-    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
-    provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
     config_state: tauri::State<'_, ConfigState>,
+    job_state: tauri::State<'_, JobState>,
 ) -> Result<String, String> {
     info!("reindex_all");
-    let (table_name, paths) = {
+    let (container, paths) = {
         let config = config_state.config.lock().await;
         let info = config.containers.get(&config.active_container)
             .ok_or("Active container not found")?;
-        (get_table_name(&config.active_container), info.indexed_paths.clone())
+        (config.active_container.clone(), info.indexed_paths.clone())
     };
 
     if paths.is_empty() {
         return Err("No folders to reindex".to_string());
     }
 
+    let mut total = 0;
+    for dir in &paths {
+        let job = job_state.start(&container, dir).await;
+        total += run_indexing_job(&app, &job).await?;
+        if job.is_cancelled() {
+            let _ = app.emit("indexing-complete", format!("Cancelled after {} files", total));
+            return Ok(format!("Reindexing cancelled after {} files", total));
+        }
+    }
+
+    let _ = app.emit("indexing-complete", format!("{} files reindexed from {} folders", total, paths.len()));
+
+    Ok(format!("Reindexed {} files from {} folders", total, paths.len()))
+}
+
+#[tauri::command]
+pub async fn confirm_index_rebuild(
+    app: tauri::AppHandle,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+    job_state: tauri::State<'_, JobState>,
+    table: String,
+) -> Result<String, String> {
+    let table_name = {
+        let config = config_state.config.lock().await;
+        let active_table = get_table_name(&config.active_container);
+        if table != active_table {
+            return Err(format!(
+                "The rebuild was requested for index '{}', but the active container is now '{}'. Switch back to that container to rebuild it.",
+                table, config.active_container
+            ));
+        }
+        active_table
+    };
+    info!("confirm_index_rebuild: table={}", table_name);
+
     let db = {
         let guard = db_state.lock().await;
         guard.db.clone()
     };
+    schema::drop_versioned_table(&db, &table_name)
+        .await
+        .map_err(|e| e.to_string())?;
+    annotations::reembed_annotations(&db, &table_name, provider_state.inner())
+        .await
+        .map_err(|e| e.to_string())?;
 
-    let ps = provider_state.inner().clone();
+    reindex_all(app, config_state, job_state).await
+}
+
+#[tauri::command]
+pub async fn list_indexing_jobs(
+    job_state: tauri::State<'_, JobState>,
+) -> Result<Vec<JobInfo>, String> {
+    Ok(job_state.list().await)
+}
+
+#[tauri::command]
+pub async fn cancel_indexing_job(
+    app: tauri::AppHandle,
+    job_id: String,
+) -> Result<JobInfo, String> {
+    apply_job_action(&app, &job_id, JobAction::Cancel).await
+}
+
+#[tauri::command]
+pub async fn pause_indexing_job(
+    app: tauri::AppHandle,
+    job_id: String,
+) -> Result<JobInfo, String> {
+    apply_job_action(&app, &job_id, JobAction::Pause).await
+}
+
+#[tauri::command]
+pub async fn resume_indexing_job(
+    app: tauri::AppHandle,
+    job_id: String,
+) -> Result<JobInfo, String> {
+    apply_job_action(&app, &job_id, JobAction::Resume).await
+}
 
-    let indexing_config = {
+pub async fn run_scheduled_maintenance(app: &tauri::AppHandle) {
+    let maintainer = app.state::<MaintenanceState>().inner().clone();
+    let db = {
+        let db_state = app.state::<Arc<Mutex<DbState>>>();
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+    let containers: Vec<String> = {
+        let config_state = app.state::<ConfigState>();
         let config = config_state.config.lock().await;
-        config.indexing.clone()
+        config.containers.keys().cloned().collect()
     };
+    let busy: Vec<String> = app
+        .state::<JobState>()
+        .list()
+        .await
+        .into_iter()
+        .filter(|j| matches!(j.status, JobStatus::Running | JobStatus::Paused))
+        .map(|j| j.container)
+        .collect();
+
+    for container in containers.iter().filter(|c| !busy.contains(c)) {
+        let table_name = get_table_name(container);
+        if let Err(e) = maintainer.run(&db, &table_name, false).await {
+            error!("Maintenance of '{}' failed: {}", table_name, e);
+        }
+    }
+}
 
-    let mut total = 0;
-    for dir in &paths {
-        let app_handle = app.clone();
-        let count = indexer::index_directory(dir, &table_name, &db, &ps, &indexing_config, move |current, total, path| {
-            let _ = app_handle.emit("indexing-progress", IndexingProgress { current, total, path });
-        })
+#[tauri::command]
+pub async fn get_maintenance_status(
+    maintenance_state: tauri::State<'_, MaintenanceState>,
+) -> Result<Vec<MaintenanceStatus>, String> {
+    Ok(maintenance_state.status())
+}
+
+#[tauri::command]
+pub async fn run_index_maintenance(
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+    maintenance_state: tauri::State<'_, MaintenanceState>,
+) -> Result<Option<MaintenanceStatus>, String> {
+    let table_name = {
+        let config = config_state.config.lock().await;
+        get_table_name(&config.active_container)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+    info!("run_index_maintenance: {}", table_name);
+    maintenance_state
+        .run(&db, &table_name, true)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn export_container(
+    name: String,
+    dest: String,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<SnapshotManifest, String> {
+    info!("export_container: name=\"{}\" dest=\"{}\"", name, dest);
+    let (container_info, provider_config) = {
+        let config = config_state.config.lock().await;
+        let info = config.containers.get(&name)
+            .ok_or("Container does not exist")?
+            .clone();
+        let provider = info.embedding_provider.clone()
+            .unwrap_or_else(|| config.embedding_provider.clone());
+        (info, provider)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+    snapshot::export_snapshot(&db, &name, &container_info, &provider_config, std::path::Path::new(&dest))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn import_container(
+    app: tauri::AppHandle,
+    archive: String,
+    name: Option<String>,
+    roots: Option<Vec<String>>,
+    overwrite: Option<bool>,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+    watcher_state: tauri::State<'_, watcher::WatcherState>,
+) -> Result<SnapshotManifest, String> {
+    let archive_path = std::path::PathBuf::from(&archive);
+    let manifest = snapshot::read_manifest(&archive_path).map_err(|e| e.to_string())?;
+    let name = name.unwrap_or_else(|| manifest.container.clone());
+    let roots = roots.unwrap_or_else(|| manifest.roots.clone());
+    info!("import_container: archive=\"{}\" name=\"{}\"", archive, name);
+
+    let (expected, is_active) = {
+        let config = config_state.config.lock().await;
+        let provider = config.containers.get(&name)
+            .map(|c| c.embedding_provider.clone())
+            .unwrap_or_else(|| manifest.info.embedding_provider.clone())
+            .unwrap_or_else(|| config.embedding_provider.clone());
+        (ProviderIdentity::from_config(&provider), config.active_container == name)
+    };
+
+    let db = {
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+    let manifest = snapshot::import_snapshot(&db, &archive_path, &name, &roots, &expected, overwrite.unwrap_or(false))
         .await
         .map_err(|e| e.to_string())?;
-        total += count;
+
+    {
+        let mut config = config_state.config.lock().await;
+        let info = config.containers.entry(name.clone()).or_insert_with(|| manifest.info.clone());
+        info.roots = roots.iter()
+            .zip(&manifest.roots)
+            .filter_map(|(root, archived)| manifest.info.root(archived).map(|r| (r.id, root.clone())))
+            .collect();
+        info.indexed_paths = roots.clone();
+        info.sync_roots();
     }
+    config_state.save().await?;
 
-    let _ = app.emit("indexing-complete", format!("{} files reindexed from {} folders", total, paths.len()));
+    if is_active {
+        watcher::restart(
+            watcher_state.inner(),
+            config_state.inner(),
+            db,
+            provider_state.inner().clone(),
+            app,
+        ).await;
+    }
 
-    Ok(format!("Reindexed {} files from {} folders", total, paths.len()))
+    Ok(manifest)
+}
+
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn relocate_root(
+    app: tauri::AppHandle,
+    container: String,
+    from: String,
+    to: String,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+    watcher_state: tauri::State<'_, watcher::WatcherState>,
+) -> Result<u64, String> {
+    info!("relocate_root: container=\"{}\" from=\"{}\" to=\"{}\"", container, from, to);
+    if !std::path::Path::new(&to).is_dir() {
+        return Err(format!("{} is not a folder", to));
+    }
+
+    let (updated, old_root, nested, root, is_active) = {
+        let config = config_state.config.lock().await;
+        let mut info = config.containers.get(&container)
+            .ok_or("Container does not exist")?
+            .clone();
+        info.sync_roots();
+        let old_root = info.root(&from)
+            .ok_or_else(|| format!("{} is not an indexed folder", from))?;
+        let nested = info.nested_roots(&old_root);
+        let root = info.relocate_root(&from, &to)?;
+        (info, old_root, nested, root, config.active_container == container)
+    };
+
+    let db = {
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+    let table_name = get_table_name(&container);
+    let mut rows = 0;
+    if let Ok(table) = db.open_table(&table_name).execute().await {
+        schema::migrate(&db, &table, &table_name, schema::TableKind::Chunks)
+            .await
+            .map_err(|e| e.to_string())?;
+        indexer::db::assign_root(&table, &old_root, &nested).await.map_err(|e| e.to_string())?;
+        rows = indexer::db::relocate_root(&table, &root).await.map_err(|e| e.to_string())?;
+    }
+    annotations::relocate_annotations(&db, &table_name, &from, &to)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    {
+        let mut config = config_state.config.lock().await;
+        if let Some(info) = config.containers.get_mut(&container) {
+            *info = updated;
+        }
+    }
+    config_state.save().await?;
+
+    if is_active {
+        watcher::restart(
+            watcher_state.inner(),
+            config_state.inner(),
+            db,
+            provider_state.inner().clone(),
+            app,
+        ).await;
+    }
+
+    Ok(rows)
 }
 
 #[derive(Serialize)]
@@ -468,6 +849,7 @@ pub struct AppConfig {
     pub embedding_model: String,
     pub chunk_size: Option<usize>,
     pub chunk_overlap: Option<usize>,
+    pub chunk_unit: ChunkUnit,
     pub extra_extensions: Vec<String>,
     pub excluded_extensions: Vec<String>,
     pub provider_type: String,
@@ -475,6 +857,7 @@ pub struct AppConfig {
     pub remote_api_key: String,
     pub remote_model: String,
     pub remote_dimensions: usize,
+    pub remote_max_tokens: Option<usize>,
     pub first_run: bool,
     pub use_reranker: bool,
     pub hyde_enabled: bool,
@@ -491,7 +874,7 @@ pub async fn get_config(
     config_state: tauri::State<'_, ConfigState>,
 ) -> Result<AppConfig, String> {
     let config = config_state.config.lock().await;
-    let (provider_type, remote_endpoint, remote_api_key, remote_model, remote_dimensions) =
+    let (provider_type, remote_endpoint, remote_api_key, remote_model, remote_dimensions, remote_max_tokens) =
         match &config.embedding_provider {
             EmbeddingProviderConfig::Local { .. } => (
                 "local".to_string(),
@@ -499,6 +882,7 @@ pub async fn get_config(
                 String::new(),
                 String::new(),
                 0,
+                None,
             ),
             EmbeddingProviderConfig::Remote(rc) => (
                 "remote".to_string(),
@@ -506,6 +890,7 @@ pub async fn get_config(
                 rc.api_key.clone().unwrap_or_default(),
                 rc.model.clone(),
                 rc.dimensions,
+                rc.max_tokens,
             ),
         };
     Ok(AppConfig {
@@ -516,6 +901,7 @@ pub async fn get_config(
         embedding_model: config.embedding_model.clone(),
         chunk_size: config.indexing.chunk_size,
         chunk_overlap: config.indexing.chunk_overlap,
+        chunk_unit: config.indexing.chunk_unit,
         extra_extensions: config.indexing.extra_extensions.clone(),
         excluded_extensions: config.indexing.excluded_extensions.clone(),
         provider_type,
@@ -523,6 +909,7 @@ pub async fn get_config(
         remote_api_key,
         remote_model,
         remote_dimensions,
+        remote_max_tokens,
         first_run: config.first_run,
         use_reranker: config.use_reranker,
         hyde_enabled: config.hyde.as_ref().is_some_and(|h| h.enabled),
@@ -544,6 +931,7 @@ pub struct ConfigUpdate {
     pub embedding_model: Option<String>,
     pub chunk_size: Option<Option<usize>>,
     pub chunk_overlap: Option<Option<usize>>,
+    pub chunk_unit: Option<ChunkUnit>,
     pub extra_extensions: Option<Vec<String>>,
     pub excluded_extensions: Option<Vec<String>>,
     pub provider_type: Option<String>,
@@ -551,6 +939,7 @@ pub struct ConfigUpdate {
     pub remote_api_key: Option<String>,
     pub remote_model: Option<String>,
     pub remote_dimensions: Option<usize>,
+    pub remote_max_tokens: Option<usize>,
     pub first_run: Option<bool>,
     pub use_reranker: Option<bool>,
     pub hyde_enabled: Option<bool>,
@@ -617,6 +1006,10 @@ pub async fn update_config(
             config.indexing.chunk_overlap = v;
         }
 
+        if let Some(v) = updates.chunk_unit {
+            config.indexing.chunk_unit = v;
+        }
+
         if let Some(ref v) = updates.extra_extensions {
             config.indexing.extra_extensions = v.clone();
         }
@@ -637,11 +1030,13 @@ pub async fn update_config(
                     let api_key = updates.remote_api_key.clone().filter(|k| !k.is_empty());
                     let model = updates.remote_model.clone().unwrap_or_default();
                     let dimensions = updates.remote_dimensions.unwrap_or(1024);
+                    let max_tokens = updates.remote_max_tokens.filter(|&v| v > 0);
                     config.embedding_provider = EmbeddingProviderConfig::Remote(RemoteProviderConfig {
                         endpoint,
                         api_key,
                         model,
                         dimensions,
+                        max_tokens,
                     });
                     provider_changed = true;
                 }
@@ -664,6 +1059,10 @@ pub async fn update_config(
                 rc.dimensions = v;
                 provider_changed = true;
             }
+            if let Some(v) = updates.remote_max_tokens {
+                rc.max_tokens = Some(v).filter(|&v| v > 0);
+                provider_changed = true;
+            }
         }
 
         if let Some(v) = updates.first_run {
@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use log::{info, warn};
@@ -7,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
 use tokio::sync::Mutex;
 
+use crate::indexer::chunking::ChunkUnit;
 use crate::indexer::embedding_provider::RemoteProviderConfig;
 use crate::indexer::hyde::HydeConfig;
 
@@ -54,8 +56,20 @@ pub struct IndexingConfig {
     pub excluded_extensions: Vec<String>,
     pub chunk_size: Option<usize>,
     pub chunk_overlap: Option<usize>,
+    #[serde(default)]
+    pub chunk_unit: ChunkUnit,
     #[serde(default = "default_true")]
     pub use_git_history: bool,
+    #[serde(default = "default_true")]
+    pub notebook_outputs: bool,
+    #[serde(default)]
+    pub index_archives: bool,
+    #[serde(default = "default_max_file_size_mb")]
+    pub max_file_size_mb: u64,
+    #[serde(default)]
+    pub tail_patterns: Vec<String>,
+    #[serde(default)]
+    pub parent_chunks: bool,
 }
 
 impl Default for IndexingConfig {
@@ -65,7 +79,13 @@ impl Default for IndexingConfig {
             excluded_extensions: Vec::new(),
             chunk_size: None,
             chunk_overlap: None,
+            chunk_unit: ChunkUnit::Bytes,
             use_git_history: true,
+            notebook_outputs: true,
+            index_archives: false,
+            max_file_size_mb: default_max_file_size_mb(),
+            tail_patterns: Vec::new(),
+            parent_chunks: false,
         }
     }
 }
@@ -76,6 +96,99 @@ pub struct ContainerInfo {
     pub indexed_paths: Vec<String>,
     #[serde(default)]
     pub embedding_provider: Option<EmbeddingProviderConfig>,
+    #[serde(default)]
+    pub roots: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexRoot {
+    pub id: String,
+    pub path: String,
+}
+
+impl IndexRoot {
+    pub fn relative(&self, path: &Path) -> Option<String> {
+        let rel = path.strip_prefix(&self.path).ok()?;
+        let parts: Vec<String> = rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        Some(parts.join("/"))
+    }
+
+    pub fn absolute(&self, rel_path: &str) -> String {
+        let mut full = PathBuf::from(&self.path);
+        full.extend(rel_path.split('/').filter(|part| !part.is_empty()));
+        full.to_string_lossy().to_string()
+    }
+}
+
+impl ContainerInfo {
+    pub fn root(&self, dir: &str) -> Option<IndexRoot> {
+        self.roots
+            .iter()
+            .find(|(_, path)| path.as_str() == dir)
+            .map(|(id, path)| IndexRoot { id: id.clone(), path: path.clone() })
+    }
+
+    pub fn root_for(&self, path: &Path) -> Option<IndexRoot> {
+        self.roots
+            .iter()
+            .filter(|(_, root)| path.starts_with(root.as_str()))
+            .max_by_key(|(_, root)| root.len())
+            .map(|(id, root)| IndexRoot { id: id.clone(), path: root.clone() })
+    }
+
+    pub fn nested_roots(&self, root: &IndexRoot) -> Vec<IndexRoot> {
+        self.roots
+            .iter()
+            .filter(|(id, path)| **id != root.id && Path::new(path.as_str()).starts_with(&root.path))
+            .map(|(id, path)| IndexRoot { id: id.clone(), path: path.clone() })
+            .collect()
+    }
+
+    pub fn ensure_root(&mut self, dir: &str) -> IndexRoot {
+        if !self.indexed_paths.iter().any(|p| p == dir) {
+            self.indexed_paths.push(dir.to_string());
+        }
+        if let Some(root) = self.root(dir) {
+            return root;
+        }
+        let id = (self.roots.len() + 1..)
+            .map(|n| format!("r{}", n))
+            .find(|id| !self.roots.contains_key(id))
+            .unwrap_or_default();
+        self.roots.insert(id.clone(), dir.to_string());
+        IndexRoot { id, path: dir.to_string() }
+    }
+
+    pub fn sync_roots(&mut self) -> bool {
+        let missing: Vec<String> = self
+            .indexed_paths
+            .iter()
+            .filter(|p| self.root(p).is_none())
+            .cloned()
+            .collect();
+        for dir in &missing {
+            self.ensure_root(dir);
+        }
+        !missing.is_empty()
+    }
+
+    pub fn relocate_root(&mut self, from: &str, to: &str) -> Result<IndexRoot, String> {
+        if self.root(to).is_some() {
+            return Err(format!("{} is already an indexed folder", to));
+        }
+        self.sync_roots();
+        let root = self
+            .root(from)
+            .ok_or_else(|| format!("{} is not an indexed folder", from))?;
+        self.roots.insert(root.id.clone(), to.to_string());
+        for path in self.indexed_paths.iter_mut().filter(|p| p.as_str() == from) {
+            *path = to.to_string();
+        }
+        Ok(IndexRoot { id: root.id, path: to.to_string() })
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -122,6 +235,10 @@ fn default_true() -> bool {
     true
 }
 
+fn default_max_file_size_mb() -> u64 {
+    100
+}
+
 fn default_mmr_lambda() -> f32 {
     0.7
 }
@@ -133,6 +250,7 @@ impl Default for Config {
             description: String::new(),
             indexed_paths: Vec::new(),
             embedding_provider: None,
+            roots: BTreeMap::new(),
         });
         Self {
             schema: default_schema(),
@@ -307,8 +425,11 @@ pub fn load_config(config_path: &std::path::Path) -> Config {
     }
     let content = std::fs::read_to_string(config_path).unwrap_or_default();
     match serde_json::from_str::<Config>(&content) {
-        Ok(c) => {
+        Ok(mut c) => {
             info!("Config loaded from {:?}", config_path);
+            for info in c.containers.values_mut() {
+                info.sync_roots();
+            }
             c
         }
         Err(_) => {
@@ -327,6 +448,7 @@ pub fn load_config(config_path: &std::path::Path) -> Config {
                             description: String::new(),
                             indexed_paths: Vec::new(),
                             embedding_provider: None,
+                            roots: BTreeMap::new(),
                         });
                     }
                 }
@@ -335,6 +457,7 @@ pub fn load_config(config_path: &std::path::Path) -> Config {
                         description: String::new(),
                         indexed_paths: Vec::new(),
                         embedding_provider: None,
+                        roots: BTreeMap::new(),
                     });
                 }
                 let default_active = containers.keys().next().cloned().unwrap_or_else(|| "Default".to_string());
@@ -426,5 +549,69 @@ mod tests {
         assert_eq!(restored.model, "llama3.2");
         assert!(restored.api_key.is_none());
     }
-}
 
+    #[test]
+    fn test_remote_provider_serde() {
+        use crate::indexer::embedding_provider::RemoteProviderConfig;
+        let provider = EmbeddingProviderConfig::Remote(RemoteProviderConfig {
+            endpoint: "https://api.openai.com/v1/embeddings".into(),
+            api_key: None,
+            model: "text-embedding-3-small".into(),
+            dimensions: 1536,
+            max_tokens: Some(8191),
+        });
+        let json = serde_json::to_string(&provider).unwrap();
+        let restored: EmbeddingProviderConfig = serde_json::from_str(&json).unwrap();
+        assert!(matches!(restored, EmbeddingProviderConfig::Remote(rc) if rc.max_tokens == Some(8191)));
+
+        let legacy = json.replace(",\"max_tokens\":8191", "");
+        let restored: EmbeddingProviderConfig = serde_json::from_str(&legacy).unwrap();
+        assert!(matches!(restored, EmbeddingProviderConfig::Remote(rc) if rc.max_tokens.is_none()));
+    }
+
+    #[test]
+    fn test_roots_survive_relocation() {
+        let mut info = ContainerInfo {
+            description: String::new(),
+            indexed_paths: vec!["/home/a/code".to_string(), "/home/a/code/vendor".to_string()],
+            embedding_provider: None,
+            roots: BTreeMap::new(),
+        };
+        assert!(info.sync_roots());
+        assert!(!info.sync_roots());
+
+        let file = Path::new("/home/a/code/vendor/lib/x.rs");
+        let root = info.root_for(file).unwrap();
+        assert_eq!(root.path, "/home/a/code/vendor");
+        assert_eq!(root.relative(file).unwrap(), "lib/x.rs");
+        assert_eq!(root.absolute("lib/x.rs"), file.to_string_lossy());
+
+        let moved = info.relocate_root("/home/a/code", "/mnt/code").unwrap();
+        assert_eq!(moved.id, info.root("/mnt/code").unwrap().id);
+        assert!(info.indexed_paths.contains(&"/mnt/code".to_string()));
+        assert!(info.relocate_root("/home/a/code", "/mnt/x").is_err());
+        assert!(info.relocate_root("/mnt/code", "/home/a/code/vendor").is_err());
+
+        let added = info.ensure_root("/home/a/code");
+        assert_ne!(added.id, moved.id);
+    }
+
+    #[test]
+    fn test_nested_roots_own_their_files() {
+        let mut info = ContainerInfo {
+            description: String::new(),
+            indexed_paths: Vec::new(),
+            embedding_provider: None,
+            roots: BTreeMap::new(),
+        };
+        let outer = info.ensure_root("/a/code");
+        let vendor = info.ensure_root("/a/code/vendor");
+        let sibling = info.ensure_root("/a/code-old");
+
+        assert_eq!(info.nested_roots(&outer), vec![vendor.clone()]);
+        assert!(info.nested_roots(&vendor).is_empty());
+        assert!(info.nested_roots(&sibling).is_empty());
+        assert_eq!(info.root_for(Path::new("/a/code/vendor/x.rs")), Some(vendor));
+        assert_eq!(info.root_for(Path::new("/a/code/src/x.rs")), Some(outer));
+    }
+}
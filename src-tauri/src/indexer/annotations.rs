@@ -16,6 +16,8 @@ use tokio::sync::Mutex;
 
 use crate::state::ProviderState;
 
+use super::schema::{self, TableKind};
+
 #[derive(Serialize, Clone, Debug)]
 pub struct Annotation {
     pub id: String,
@@ -25,7 +27,19 @@ pub struct Annotation {
     pub created_at: i64,
 }
 
-fn annotations_table_name(container_table: &str) -> String {
+impl Annotation {
+    pub fn new(path: &str, note: &str, source: &str) -> Self {
+        Self {
+            id: generate_id(),
+            path: path.to_string(),
+            note: note.to_string(),
+            source: source.to_string(),
+            created_at: now_unix(),
+        }
+    }
+}
+
+pub(crate) fn annotations_table_name(container_table: &str) -> String {
     format!("{}_annotations", container_table)
 }
 
@@ -62,7 +76,7 @@ fn now_unix() -> i64 {
         .as_secs() as i64
 }
 
-async fn get_or_create_annotations_table(
+pub async fn get_or_create_annotations_table(
     db: &Connection,
     container_table: &str,
     dim: usize,
@@ -71,11 +85,9 @@ async fn get_or_create_annotations_table(
 
     if let Ok(table) = db.open_table(&table_name).execute().await {
         let schema = table.schema().await?;
-        if schema.column_with_name("source").is_some() {
-            return Ok(table);
-        }
-        log::warn!("Annotations table '{}' missing 'source' column, recreating", table_name);
-        db.drop_table(&table_name, &[]).await?;
+        schema::check_dimension(&schema, &table_name, dim)?;
+        schema::migrate(db, &table, &table_name, TableKind::Annotations).await?;
+        return Ok(table);
     }
 
     let schema = Arc::new(make_annotations_schema(dim));
@@ -83,69 +95,120 @@ async fn get_or_create_annotations_table(
         .create_table(&table_name, RecordBatchIterator::new(vec![], schema))
         .execute()
         .await?;
+    schema::set_version(db, &table_name, TableKind::Annotations.current_version()).await?;
 
     info!("Annotations table '{}' created (dim={})", table_name, dim);
     Ok(table)
 }
 
-pub async fn add_annotation(
-    db: &Connection,
-    container_table: &str,
-    provider_state: &Arc<Mutex<ProviderState>>,
-    path: &str,
-    note: &str,
-    source: &str,
-) -> Result<Annotation> {
-    let vector = {
-        let guard = provider_state.lock().await;
-        let provider = guard
-            .provider
-            .as_ref()
-            .ok_or_else(|| anyhow!("Embedding provider not initialized"))?;
-        let vectors: Vec<Vec<f32>> = provider.embed_passages(vec![note.to_string()]).await?;
-        vectors.into_iter().next().ok_or_else(|| anyhow!("Empty embedding result"))?
-    };
-
-    let dim = vector.len();
-    let table = get_or_create_annotations_table(db, container_table, dim).await?;
-
-    let id = generate_id();
-    let created_at = now_unix();
+async fn embed_note(provider_state: &Arc<Mutex<ProviderState>>, note: &str) -> Result<Vec<f32>> {
+    let guard = provider_state.lock().await;
+    let provider = guard
+        .provider
+        .as_ref()
+        .ok_or_else(|| anyhow!("Embedding provider not initialized"))?;
+    let vectors: Vec<Vec<f32>> = provider.embed_passages(vec![note.to_string()]).await?;
+    vectors.into_iter().next().ok_or_else(|| anyhow!("Empty embedding result"))
+}
 
+fn annotations_batch(annotations: &[Annotation], vectors: Vec<Vec<f32>>) -> Result<RecordBatch> {
+    let dim = vectors.first().map_or(0, Vec::len);
     let schema = Arc::new(make_annotations_schema(dim));
     let vector_array = FixedSizeListArray::try_new(
         Arc::new(Field::new("item", DataType::Float32, true)),
         dim as i32,
-        Arc::new(Float32Array::from(vector)),
+        Arc::new(Float32Array::from(vectors.concat())),
         None,
     )?;
 
-    let batch = RecordBatch::try_new(
-        schema.clone(),
+    RecordBatch::try_new(
+        schema,
         vec![
-            Arc::new(StringArray::from(vec![id.as_str()])),
-            Arc::new(StringArray::from(vec![path])),
-            Arc::new(StringArray::from(vec![note])),
-            Arc::new(StringArray::from(vec![source])),
+            Arc::new(StringArray::from_iter_values(annotations.iter().map(|a| a.id.as_str()))),
+            Arc::new(StringArray::from_iter_values(annotations.iter().map(|a| a.path.as_str()))),
+            Arc::new(StringArray::from_iter_values(annotations.iter().map(|a| a.note.as_str()))),
+            Arc::new(StringArray::from_iter_values(annotations.iter().map(|a| a.source.as_str()))),
             Arc::new(vector_array),
-            Arc::new(Int64Array::from(vec![created_at])),
+            Arc::new(Int64Array::from_iter_values(annotations.iter().map(|a| a.created_at))),
         ],
-    )?;
+    )
+    .map_err(|e| anyhow!(e))
+}
 
+pub async fn insert_annotation(
+    db: &Connection,
+    container_table: &str,
+    annotation: &Annotation,
+    vector: Vec<f32>,
+) -> Result<()> {
+    let table = get_or_create_annotations_table(db, container_table, vector.len()).await?;
+    let batch = annotations_batch(std::slice::from_ref(annotation), vec![vector])?;
+    let schema = batch.schema();
     table
         .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
         .execute()
         .await?;
+    Ok(())
+}
+
+pub async fn add_annotation(
+    db: &Connection,
+    container_table: &str,
+    provider_state: &Arc<Mutex<ProviderState>>,
+    path: &str,
+    note: &str,
+    source: &str,
+) -> Result<Annotation> {
+    let vector = embed_note(provider_state, note).await?;
+    let annotation = Annotation::new(path, note, source);
+    insert_annotation(db, container_table, &annotation, vector).await?;
+
+    debug!("Annotation added: id={}, path={}", annotation.id, path);
+    Ok(annotation)
+}
 
-    debug!("Annotation added: id={}, path={}", id, path);
+pub async fn reembed_annotations(
+    db: &Connection,
+    container_table: &str,
+    provider_state: &Arc<Mutex<ProviderState>>,
+) -> Result<usize> {
+    let table_name = annotations_table_name(container_table);
+    let staging_name = format!("{}__reembed", table_name);
+    let source = match db.open_table(&table_name).execute().await {
+        Ok(table) => {
+            schema::migrate(db, &table, &table_name, TableKind::Annotations).await?;
+            &table_name
+        }
+        Err(_) => &staging_name,
+    };
+    let existing = read_annotations(db, source, None).await?;
+    if existing.is_empty() {
+        schema::drop_versioned_table(db, &table_name).await?;
+        schema::drop_versioned_table(db, &staging_name).await?;
+        return Ok(0);
+    }
+    let mut vectors = Vec::with_capacity(existing.len());
+    for annotation in &existing {
+        vectors.push(embed_note(provider_state, &annotation.note).await?);
+    }
+    let batch = annotations_batch(&existing, vectors)?;
+    let schema = batch.schema();
+
+    if source == &table_name {
+        schema::drop_versioned_table(db, &staging_name).await?;
+        db.create_table(&staging_name, RecordBatchIterator::new(vec![Ok(batch.clone())], schema.clone()))
+            .execute()
+            .await?;
+    }
+    schema::drop_versioned_table(db, &table_name).await?;
+    db.create_table(&table_name, RecordBatchIterator::new(vec![Ok(batch)], schema))
+        .execute()
+        .await?;
+    schema::set_version(db, &table_name, TableKind::Annotations.current_version()).await?;
+    schema::drop_versioned_table(db, &staging_name).await?;
 
-    Ok(Annotation {
-        id,
-        path: path.to_string(),
-        note: note.to_string(),
-        source: source.to_string(),
-        created_at,
-    })
+    info!("Re-embedded {} annotations for '{}'", existing.len(), container_table);
+    Ok(existing.len())
 }
 
 pub async fn get_annotations(
@@ -153,8 +216,11 @@ pub async fn get_annotations(
     container_table: &str,
     path: Option<&str>,
 ) -> Result<Vec<Annotation>> {
-    let table_name = annotations_table_name(container_table);
-    let table = match db.open_table(&table_name).execute().await {
+    read_annotations(db, &annotations_table_name(container_table), path).await
+}
+
+async fn read_annotations(db: &Connection, table_name: &str, path: Option<&str>) -> Result<Vec<Annotation>> {
+    let table = match db.open_table(table_name).execute().await {
         Ok(t) => t,
         Err(_) => return Ok(vec![]),
     };
@@ -217,6 +283,37 @@ pub async fn delete_annotation(
     Ok(())
 }
 
+pub async fn relocate_annotations(
+    db: &Connection,
+    container_table: &str,
+    from: &str,
+    to: &str,
+) -> Result<u64> {
+    let table_name = annotations_table_name(container_table);
+    let table = match db.open_table(&table_name).execute().await {
+        Ok(t) => t,
+        Err(_) => return Ok(0),
+    };
+
+    let old_prefix = super::db::root_prefix(from);
+    let result = table
+        .update()
+        .only_if(format!("starts_with(path, '{}')", old_prefix.replace('\'', "''")))
+        .column(
+            "path",
+            format!(
+                "concat('{}', substr(path, {}))",
+                super::db::root_prefix(to).replace('\'', "''"),
+                old_prefix.chars().count() + 1
+            ),
+        )
+        .execute()
+        .await?;
+
+    debug!("Relocated {} annotations from {} to {}", result.rows_updated, from, to);
+    Ok(result.rows_updated)
+}
+
 pub async fn search_annotations(
     db: &Connection,
     container_table: &str,
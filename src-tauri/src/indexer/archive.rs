@@ -0,0 +1,242 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use flate2::read::MultiGzDecoder;
+use zip::ZipArchive;
+
+pub const VIRTUAL_SEPARATOR: &str = "!/";
+
+const MAX_ENTRY_SIZE: u64 = 10 * 1024 * 1024;
+const MAX_ENTRIES: usize = 10_000;
+const TAR_BLOCK: u64 = 512;
+
+pub fn is_archive_path(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    name.ends_with(".zip") || name.ends_with(".tar") || name.ends_with(".tar.gz") || name.ends_with(".tgz")
+}
+
+pub fn virtual_path(archive: &str, inner: &str) -> String {
+    format!("{}{}{}", archive, VIRTUAL_SEPARATOR, inner)
+}
+
+pub fn split_virtual(path: &str) -> Option<(&str, &str)> {
+    path.split_once(VIRTUAL_SEPARATOR)
+        .filter(|(archive, _)| is_archive_path(Path::new(archive)))
+}
+
+fn skipped_entry(name: &str) -> bool {
+    name.split('/')
+        .any(|part| part.starts_with('.') || part == "__MACOSX")
+        || is_archive_path(Path::new(name))
+}
+
+pub fn for_each_entry(path: &Path, mut visit: impl FnMut(&str, &[u8])) -> Result<()> {
+    let mut seen = 0usize;
+    let mut filtered = |name: &str, data: &[u8]| {
+        if seen < MAX_ENTRIES && !skipped_entry(name) {
+            seen += 1;
+            visit(name, data);
+        }
+    };
+    let name = path.to_string_lossy().to_lowercase();
+    let file = BufReader::new(File::open(path)?);
+    if name.ends_with(".zip") {
+        zip_entries(file, &mut filtered)
+    } else if name.ends_with(".tar") {
+        tar_entries(file, &mut filtered)
+    } else {
+        tar_entries(MultiGzDecoder::new(file), &mut filtered)
+    }
+}
+
+pub fn read_entry(path: &Path, inner: &str) -> Result<Vec<u8>> {
+    let mut found = None;
+    for_each_entry(path, |name, data| {
+        if found.is_none() && name == inner {
+            found = Some(data.to_vec());
+        }
+    })?;
+    found.ok_or_else(|| anyhow!("{} not found in {}", inner, path.display()))
+}
+
+fn zip_entries(file: BufReader<File>, visit: &mut impl FnMut(&str, &[u8])) -> Result<()> {
+    let mut archive = ZipArchive::new(file)?;
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i)?;
+        if entry.is_dir() || entry.size() > MAX_ENTRY_SIZE {
+            continue;
+        }
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+        let name = name
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let mut data = Vec::with_capacity(entry.size() as usize);
+        entry.take(MAX_ENTRY_SIZE).read_to_end(&mut data)?;
+        visit(&name, &data);
+    }
+    Ok(())
+}
+
+fn header_str(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+fn header_size(field: &[u8]) -> Result<u64> {
+    if field[0] & 0x80 != 0 {
+        return Ok(field[1..].iter().fold(0u64, |n, &b| (n << 8) | b as u64));
+    }
+    let digits = header_str(field);
+    let digits = digits.trim_matches(|c: char| c == ' ' || c == '\0');
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(digits, 8).map_err(|_| anyhow!("Invalid tar entry size"))
+}
+
+fn pax_path(records: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(records)
+        .lines()
+        .filter_map(|line| line.split_once(' ').map(|(_, record)| record))
+        .find_map(|record| record.strip_prefix("path=").map(str::to_string))
+}
+
+fn read_block<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 if filled == 0 => return Ok(false),
+            0 => return Err(anyhow!("Truncated tar archive")),
+            n => filled += n,
+        }
+    }
+    Ok(true)
+}
+
+fn tar_entries<R: Read>(mut reader: R, visit: &mut impl FnMut(&str, &[u8])) -> Result<()> {
+    let mut header = [0u8; TAR_BLOCK as usize];
+    let mut long_name: Option<String> = None;
+    while read_block(&mut reader, &mut header)? {
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+        let size = header_size(&header[124..136])?;
+        let padding = size.div_ceil(TAR_BLOCK) * TAR_BLOCK - size;
+        let kind = header[156];
+
+        let wanted = matches!(kind, b'0' | 0 | b'7' | b'L' | b'x') && size <= MAX_ENTRY_SIZE;
+        if !wanted {
+            io::copy(&mut (&mut reader).take(size + padding), &mut io::sink())?;
+            continue;
+        }
+        let mut data = vec![0u8; size as usize];
+        reader.read_exact(&mut data)?;
+        io::copy(&mut (&mut reader).take(padding), &mut io::sink())?;
+
+        match kind {
+            b'L' => long_name = Some(header_str(&data)),
+            b'x' => long_name = pax_path(&data).or(long_name),
+            _ => {
+                let name = long_name.take().unwrap_or_else(|| {
+                    let name = header_str(&header[0..100]);
+                    let prefix = header_str(&header[345..500]);
+                    if &header[257..262] == b"ustar" && !prefix.is_empty() {
+                        format!("{}/{}", prefix, name)
+                    } else {
+                        name
+                    }
+                });
+                let name = name.trim_start_matches("./").trim_start_matches('/');
+                if !name.split('/').any(|part| part == "..") {
+                    visit(name, &data);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn tar_header(name: &str, size: usize, kind: u8) -> Vec<u8> {
+        let mut header = vec![0u8; 512];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[124..135].copy_from_slice(format!("{:011o}", size).as_bytes());
+        header[156] = kind;
+        header[257..262].copy_from_slice(b"ustar");
+        header
+    }
+
+    fn tar_file(name: &str, data: &[u8], kind: u8) -> Vec<u8> {
+        let mut out = tar_header(name, data.len(), kind);
+        out.extend_from_slice(data);
+        out.resize(out.len() + (512 - data.len() % 512) % 512, 0);
+        out
+    }
+
+    #[test]
+    fn test_tar_gz_entries() {
+        let long = format!("docs/{}/guide.md", "nested".repeat(20));
+        let mut tar = Vec::new();
+        tar.extend(tar_file("docs/readme.md", b"# Readme", b'0'));
+        tar.extend(tar_file("docs/", b"", b'5'));
+        tar.extend(tar_file("././@LongLink", format!("{}\0", long).as_bytes(), b'L'));
+        tar.extend(tar_file("truncated", b"long guide", b'0'));
+        tar.extend(tar_file(".hidden/secret.txt", b"skip me", b'0'));
+        tar.extend(vec![0u8; 1024]);
+
+        let path = std::env::temp_dir().join("rememex_archive_test.tar.gz");
+        let mut gz = flate2::write::GzEncoder::new(File::create(&path).unwrap(), flate2::Compression::fast());
+        gz.write_all(&tar).unwrap();
+        gz.finish().unwrap();
+
+        let mut entries = Vec::new();
+        for_each_entry(&path, |name, data| entries.push((name.to_string(), data.to_vec()))).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0], ("docs/readme.md".to_string(), b"# Readme".to_vec()));
+        assert_eq!(entries[1].0, long);
+        assert_eq!(read_entry(&path, &long).unwrap(), b"long guide");
+        assert!(read_entry(&path, "missing.md").is_err());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_zip_entries_and_virtual_paths() {
+        let path = std::env::temp_dir().join("rememex_archive_test.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.add_directory("src/", options).unwrap();
+        zip.start_file("src/main.rs", options).unwrap();
+        zip.write_all(b"fn main() {}").unwrap();
+        zip.start_file("__MACOSX/src/._main.rs", options).unwrap();
+        zip.write_all(b"junk").unwrap();
+        zip.start_file("inner.zip", options).unwrap();
+        zip.write_all(b"PK").unwrap();
+        zip.finish().unwrap();
+
+        let mut names = Vec::new();
+        for_each_entry(&path, |name, _| names.push(name.to_string())).unwrap();
+        assert_eq!(names, vec!["src/main.rs"]);
+
+        let virtual_path = virtual_path(&path.to_string_lossy(), "src/main.rs");
+        let (archive, inner) = split_virtual(&virtual_path).unwrap();
+        assert_eq!(read_entry(Path::new(archive), inner).unwrap(), b"fn main() {}");
+        assert!(is_archive_path(Path::new("bundle.TAR.GZ")));
+        assert!(!is_archive_path(Path::new("notes.gz")));
+        assert!(split_virtual("/home/me/wow!/notes.md").is_none());
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -1,4 +1,13 @@
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::file_io::PAGE_BREAK;
+use super::email::{is_email_extension, message_starts};
+use super::headings::{breadcrumb_at, headings, is_heading_extension};
+use super::notebook::cell_starts;
+use super::structured::{is_structured_extension, key_path_units};
+use super::syntax::{is_syntax_extension, symbol_units, SymbolUnit};
+use super::tabular::{is_tabular_extension, row_chunks, source_offset, RowSpan};
 
 pub struct ChunkConfig {
     pub max_bytes: usize,
@@ -13,10 +22,12 @@ pub fn get_chunk_config(ext: &str) -> ChunkConfig {
         | "fs" | "fsi" | "fsx" | "vb" | "vbs" | "rb" | "erb" | "swift" | "m" | "mm" | "dart"
         | "php" | "pl" | "pm" | "lua" | "r" | "jl" | "ex" | "exs" | "erl" | "hrl" | "hs"
         | "lhs" | "ml" | "mli" | "elm" | "zig" | "nim" | "v" | "d" | "sol" | "move" | "pas"
-        | "lisp" | "el" | "rkt" | "asm" | "s" | "wat" | "vue" | "svelte" | "astro" => ChunkConfig {
-            max_bytes: 1200,
-            overlap_bytes: 200,
-        },
+        | "lisp" | "el" | "rkt" | "asm" | "s" | "wat" | "vue" | "svelte" | "astro" | "ipynb" => {
+            ChunkConfig {
+                max_bytes: 1200,
+                overlap_bytes: 200,
+            }
+        }
         "md" | "markdown" | "txt" | "rst" | "adoc" | "tex" => ChunkConfig {
             max_bytes: 800,
             overlap_bytes: 150,
@@ -40,7 +51,8 @@ pub fn get_chunk_config(ext: &str) -> ChunkConfig {
 }
 
 use std::collections::HashMap;
-use std::sync::LazyLock;
+use std::ops::Range;
+use std::sync::{Arc, LazyLock};
 
 static SEMANTIC_PATTERNS: LazyLock<HashMap<&'static str, Regex>> = LazyLock::new(|| {
     let entries: Vec<(&str, &str)> = vec![
@@ -174,6 +186,12 @@ static SEMANTIC_PATTERNS: LazyLock<HashMap<&'static str, Regex>> = LazyLock::new
         ("pas", r"\n(?:procedure |function |type |var |begin )"),
         ("vb", r"\n\s*(?:Sub |Function |Class |Property |Module )"),
         ("vbs", r"\n\s*(?:Sub |Function |Class |Property |Module )"),
+        ("html", r"\n(?:#{1,6} |---\n)"),
+        ("htm", r"\n(?:#{1,6} |---\n)"),
+        ("xhtml", r"\n(?:#{1,6} |---\n)"),
+        ("xml", r"\n#{1,6} "),
+        ("rss", r"\n#{1,6} "),
+        ("atom", r"\n#{1,6} "),
         ("md", r"\n#{1,6} "),
         ("markdown", r"\n#{1,6} "),
         ("rst", r"\n\n"),
@@ -181,6 +199,14 @@ static SEMANTIC_PATTERNS: LazyLock<HashMap<&'static str, Regex>> = LazyLock::new
         ("txt", r"\n\n"),
         ("tex", r"\n\n"),
         ("bib", r"\n\n"),
+        ("docx", r"\n\n"),
+        ("odt", r"\n\n"),
+        ("xlsx", r"\n## Sheet: "),
+        ("xlsm", r"\n## Sheet: "),
+        ("xls", r"\n## Sheet: "),
+        ("ods", r"\n## Sheet: "),
+        ("pptx", r"\n## Slide "),
+        ("odp", r"\n## Slide "),
         ("toml", r"\n\["),
         ("ini", r"\n\["),
         ("cfg", r"\n\["),
@@ -222,40 +248,439 @@ fn get_semantic_pattern(ext: &str) -> Option<&'static Regex> {
     SEMANTIC_PATTERNS.get(ext)
 }
 
-pub fn semantic_chunk_with_overrides(
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    pub text: String,
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub page: Option<usize>,
+    pub cell: Option<usize>,
+    pub symbol: Option<String>,
+    pub symbol_kind: Option<String>,
+    pub breadcrumb: Option<String>,
+    pub parent: Option<Box<Chunk>>,
+    pub prefix_len: usize,
+    pub rows: Vec<RowSpan>,
+}
+
+pub fn semantic_chunk_spans(
     text: &str,
     ext: &str,
     chunk_size: Option<usize>,
     chunk_overlap: Option<usize>,
-) -> Vec<String> {
+) -> Vec<Chunk> {
+    let size = chunk_size.map(|s| s.max(MIN_CHUNK_BYTES));
+    chunk_spans(text, ext, chunk_config(ext, size, chunk_overlap))
+}
+
+fn chunk_config(ext: &str, max_bytes: Option<usize>, overlap_bytes: Option<usize>) -> ChunkConfig {
     let mut config = get_chunk_config(ext);
-    if let Some(size) = chunk_size {
-        config.max_bytes = size.max(100);
+    if let Some(size) = max_bytes {
+        config.max_bytes = size;
     }
-    if let Some(overlap) = chunk_overlap {
+    if let Some(overlap) = overlap_bytes {
         config.overlap_bytes = overlap;
     }
+    config
+}
+
+fn chunk_spans(text: &str, ext: &str, config: ChunkConfig) -> Vec<Chunk> {
+    if ext == "pdf" {
+        let breaks: Vec<usize> = text.match_indices(PAGE_BREAK).map(|(i, _)| i).collect();
+        let mut chunks = ranges_to_chunks(text, page_ranges(text, &config));
+        for chunk in &mut chunks {
+            chunk.page = Some(breaks.partition_point(|&b| b < chunk.start_byte) + 1);
+        }
+        return chunks;
+    }
+    if ext == "ipynb" {
+        let starts = cell_starts(text);
+        let mut chunks = ranges_to_chunks(text, unit_ranges(text, &starts, &config));
+        for chunk in &mut chunks {
+            chunk.cell = Some(starts.partition_point(|&s| s <= chunk.start_byte).max(1));
+        }
+        return chunks;
+    }
+    if is_email_extension(ext) {
+        return ranges_to_chunks(text, unit_ranges(text, &message_starts(text), &config));
+    }
+    if is_tabular_extension(ext) {
+        let rows = row_chunks(text, ext, config.max_bytes);
+        let mut chunks = ranges_to_chunks(text, rows.iter().map(|row| row.range.clone()).collect());
+        for (chunk, row) in chunks.iter_mut().zip(rows) {
+            chunk.text = row.text;
+            chunk.prefix_len = row.header_len;
+            chunk.rows = row.rows;
+        }
+        return chunks;
+    }
+    if is_structured_extension(ext) {
+        if let Some(units) = key_path_units(text, ext, config.max_bytes) {
+            return key_path_chunks(text, units, &config);
+        }
+    }
 
-    let pattern = match get_semantic_pattern(ext) {
-        Some(p) => p,
-        None => return chunk_with_overlap(text, config.max_bytes, config.overlap_bytes),
+    if is_syntax_extension(ext) {
+        if let Some(units) = symbol_units(text, ext, config.max_bytes) {
+            return symbol_chunks(text, units, &config);
+        }
+    }
+
+    let ranges = match get_semantic_pattern(ext) {
+        Some(pattern) => semantic_ranges(text, &config, pattern),
+        None => overlap_ranges(text, 0..text.len(), config.max_bytes, config.overlap_bytes),
     };
 
-    chunk_with_semantic_config(text, &config, pattern)
+    let mut chunks = ranges_to_chunks(text, ranges);
+    if is_heading_extension(ext) {
+        let headings = headings(text, ext);
+        for chunk in &mut chunks {
+            let indent = chunk.text.len() - chunk.text.trim_start().len();
+            chunk.breadcrumb = breadcrumb_at(&headings, chunk.start_byte + indent);
+        }
+    }
+    chunks
 }
 
-pub fn semantic_chunk(text: &str, ext: &str) -> Vec<String> {
-    let config = get_chunk_config(ext);
+const BYTES_PER_TOKEN: usize = 4;
+const MIN_CHUNK_BYTES: usize = 100;
+const MIN_CHUNK_TOKENS: usize = MIN_CHUNK_BYTES / BYTES_PER_TOKEN;
+const RESERVED_TOKENS: usize = 32;
+const SAMPLE_BYTES: usize = 16 * 1024;
+const CHILD_DIVISOR: usize = 4;
+const MIN_CHILD_BYTES: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkUnit {
+    #[default]
+    Bytes,
+    Tokens,
+}
 
-    let pattern = match get_semantic_pattern(ext) {
-        Some(p) => p,
-        None => return chunk_with_overlap(text, config.max_bytes, config.overlap_bytes),
+pub type TokenCounter = Arc<dyn Fn(&str) -> usize + Send + Sync>;
+
+#[derive(Clone)]
+pub struct TokenBudget {
+    pub count: TokenCounter,
+    pub max_tokens: usize,
+}
+
+impl TokenBudget {
+    pub fn estimated(max_tokens: usize) -> Self {
+        Self {
+            count: Arc::new(estimate_tokens),
+            max_tokens,
+        }
+    }
+
+    fn limit(&self) -> usize {
+        self.max_tokens.saturating_sub(RESERVED_TOKENS).max(16)
+    }
+
+    fn bytes_per_token(&self, text: &str) -> f64 {
+        let mut end = text.len().min(SAMPLE_BYTES);
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        let tokens = (self.count)(&text[..end]);
+        if tokens == 0 {
+            return BYTES_PER_TOKEN as f64;
+        }
+        (end as f64 / tokens as f64).max(1.0)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ChunkSizing {
+    pub chunk_size: Option<usize>,
+    pub chunk_overlap: Option<usize>,
+    pub unit: ChunkUnit,
+    pub budget: Option<TokenBudget>,
+    pub parent_chunks: bool,
+}
+
+pub fn estimate_tokens(text: &str) -> usize {
+    let ascii = text.bytes().filter(u8::is_ascii).count();
+    let other = text.chars().filter(|c| !c.is_ascii()).count();
+    ascii.div_ceil(BYTES_PER_TOKEN) + other
+}
+
+pub fn sized_chunk_spans(text: &str, ext: &str, sizing: &ChunkSizing) -> Vec<Chunk> {
+    let (size, overlap) = match &sizing.budget {
+        None => match sizing.unit {
+            ChunkUnit::Bytes => (sizing.chunk_size.map(|s| s.max(MIN_CHUNK_BYTES)), sizing.chunk_overlap),
+            ChunkUnit::Tokens => (
+                sizing.chunk_size.map(|s| s.max(MIN_CHUNK_TOKENS) * BYTES_PER_TOKEN),
+                sizing.chunk_overlap.map(|o| o * BYTES_PER_TOKEN),
+            ),
+        },
+        Some(budget) => {
+            let defaults = get_chunk_config(ext);
+            let limit = budget.limit();
+            let ratio = budget.bytes_per_token(text);
+            let to_bytes = |tokens: usize| (tokens as f64 * ratio) as usize;
+            let size = match (sizing.unit, sizing.chunk_size) {
+                (ChunkUnit::Bytes, Some(bytes)) => bytes.max(MIN_CHUNK_BYTES),
+                (ChunkUnit::Tokens, Some(tokens)) => to_bytes(tokens.max(MIN_CHUNK_TOKENS).min(limit)),
+                (_, None) => to_bytes((defaults.max_bytes / BYTES_PER_TOKEN).min(limit)),
+            };
+            let overlap = match (sizing.unit, sizing.chunk_overlap) {
+                (ChunkUnit::Bytes, Some(bytes)) => bytes,
+                (ChunkUnit::Tokens, Some(tokens)) => to_bytes(tokens),
+                (_, None) => to_bytes(defaults.overlap_bytes / BYTES_PER_TOKEN),
+            };
+            (Some(size), Some(overlap))
+        }
     };
 
-    chunk_with_semantic_config(text, &config, pattern)
+    let mut chunks = chunk_spans(text, ext, chunk_config(ext, size, overlap));
+    if sizing.parent_chunks {
+        let parent_bytes = size.unwrap_or_else(|| get_chunk_config(ext).max_bytes);
+        chunks = child_chunks(text, chunks, (parent_bytes / CHILD_DIVISOR).max(MIN_CHILD_BYTES));
+    }
+    match &sizing.budget {
+        Some(budget) => chunks
+            .into_iter()
+            .flat_map(|chunk| fit_to_budget(text, chunk, budget, budget.limit()))
+            .collect(),
+        None => chunks,
+    }
+}
+
+fn split_chunk(text: &str, chunk: &Chunk, max_bytes: usize, overlap_bytes: usize) -> Vec<Chunk> {
+    let (prefix, body) = chunk.text.split_at(chunk.prefix_len);
+    let source = chunk.start_byte..chunk.end_byte;
+    let max_bytes = max_bytes.saturating_sub(prefix.len()).max(max_bytes / 2);
+    let pieces: Vec<(&str, Range<usize>)> = if text.get(source.clone()) == Some(body) {
+        overlap_ranges(text, source.clone(), max_bytes, overlap_bytes)
+            .into_iter()
+            .map(|range| (&text[range.clone()], range))
+            .collect()
+    } else {
+        let offset = |pos: usize| {
+            let mut pos = source_offset(&chunk.rows, pos, source.end).clamp(source.start, source.end);
+            while !text.is_char_boundary(pos) {
+                pos -= 1;
+            }
+            pos
+        };
+        overlap_ranges(body, 0..body.len(), max_bytes, overlap_bytes)
+            .into_iter()
+            .map(|range| (&body[range.clone()], offset(range.start)..offset(range.end)))
+            .collect()
+    };
+    let newlines: Vec<usize> = text[source.clone()].match_indices('\n').map(|(i, _)| source.start + i).collect();
+    let line_at = |pos: usize| chunk.start_line + newlines.partition_point(|&n| n < pos);
+    pieces
+        .into_iter()
+        .map(|(piece, range)| Chunk {
+            text: format!("{}{}", prefix, piece),
+            start_line: line_at(range.start),
+            end_line: line_at(range.end.saturating_sub(1).max(range.start)),
+            start_byte: range.start,
+            end_byte: range.end,
+            page: chunk.page,
+            cell: chunk.cell,
+            symbol: chunk.symbol.clone(),
+            symbol_kind: chunk.symbol_kind.clone(),
+            breadcrumb: chunk.breadcrumb.clone(),
+            parent: chunk.parent.clone(),
+            prefix_len: chunk.prefix_len,
+            rows: if chunk.rows.is_empty() {
+                Vec::new()
+            } else {
+                vec![RowSpan { rendered: 0..piece.len(), source: range.clone() }]
+            },
+        })
+        .collect()
+}
+
+fn child_chunks(text: &str, parents: Vec<Chunk>, max_bytes: usize) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    for parent in parents {
+        if parent.text.len() <= max_bytes {
+            chunks.push(parent);
+            continue;
+        }
+        let children: Vec<Chunk> = split_chunk(text, &parent, max_bytes, max_bytes / 8)
+            .into_iter()
+            .filter(|child| !child.text[child.prefix_len..].trim().is_empty())
+            .collect();
+        if children.len() <= 1 {
+            chunks.push(parent);
+            continue;
+        }
+        let parent = Box::new(parent);
+        chunks.extend(children.into_iter().map(|child| Chunk {
+            parent: Some(parent.clone()),
+            ..child
+        }));
+    }
+    chunks
+}
+
+fn fit_to_budget(text: &str, chunk: Chunk, budget: &TokenBudget, limit: usize) -> Vec<Chunk> {
+    let tokens = (budget.count)(&chunk.text);
+    if tokens <= limit {
+        return vec![chunk];
+    }
+    let max_bytes = (chunk.text.len() * limit * 9 / 10 / tokens).max(1);
+    match split_chunk(text, &chunk, max_bytes, 0) {
+        pieces if pieces.is_empty() => vec![chunk],
+        pieces => pieces,
+    }
+}
+
+pub fn semantic_chunk_with_overrides(
+    text: &str,
+    ext: &str,
+    chunk_size: Option<usize>,
+    chunk_overlap: Option<usize>,
+) -> Vec<String> {
+    semantic_chunk_spans(text, ext, chunk_size, chunk_overlap)
+        .into_iter()
+        .map(|c| c.text)
+        .collect()
+}
+
+pub fn semantic_chunk(text: &str, ext: &str) -> Vec<String> {
+    semantic_chunk_with_overrides(text, ext, None, None)
+}
+
+fn ranges_to_chunks(text: &str, ranges: Vec<Range<usize>>) -> Vec<Chunk> {
+    let newlines: Vec<usize> = text.match_indices('\n').map(|(i, _)| i).collect();
+    let line_at = |pos: usize| newlines.partition_point(|&n| n < pos) + 1;
+    ranges
+        .into_iter()
+        .map(|r| Chunk {
+            text: text[r.clone()].to_string(),
+            start_line: line_at(r.start),
+            end_line: line_at(r.end.saturating_sub(1).max(r.start)),
+            start_byte: r.start,
+            end_byte: r.end,
+            page: None,
+            cell: None,
+            symbol: None,
+            symbol_kind: None,
+            breadcrumb: None,
+            parent: None,
+            prefix_len: 0,
+            rows: Vec::new(),
+        })
+        .collect()
+}
+
+fn key_path_chunks(text: &str, units: Vec<(String, Range<usize>)>, config: &ChunkConfig) -> Vec<Chunk> {
+    units
+        .into_iter()
+        .filter(|(_, range)| !text[range.clone()].trim().is_empty())
+        .flat_map(|(path, range)| {
+            let ranges = overlap_ranges(text, range, config.max_bytes, config.overlap_bytes);
+            ranges_to_chunks(text, ranges).into_iter().map(move |mut chunk| {
+                if !path.is_empty() {
+                    let prefix = format!("Key: {}\n", path);
+                    chunk.prefix_len = prefix.len();
+                    chunk.text = prefix + &chunk.text;
+                }
+                chunk
+            })
+        })
+        .collect()
+}
+
+fn symbol_chunks(text: &str, units: Vec<SymbolUnit>, config: &ChunkConfig) -> Vec<Chunk> {
+    units
+        .into_iter()
+        .filter(|unit| !text[unit.range.clone()].trim().is_empty())
+        .flat_map(|unit| {
+            let ranges = overlap_ranges(text, unit.range, config.max_bytes, config.overlap_bytes);
+            ranges_to_chunks(text, ranges).into_iter().map(move |mut chunk| {
+                if let Some(symbol) = &unit.symbol {
+                    chunk.symbol = Some(symbol.name.clone());
+                    chunk.symbol_kind = Some(symbol.kind.clone());
+                }
+                chunk
+            })
+        })
+        .collect()
+}
+
+fn unit_ranges(text: &str, starts: &[usize], config: &ChunkConfig) -> Vec<Range<usize>> {
+    let mut bounds: Vec<usize> = starts.to_vec();
+    if bounds.first() != Some(&0) {
+        bounds.insert(0, 0);
+    }
+    bounds.push(text.len());
+    bounds
+        .windows(2)
+        .filter(|w| !text[w[0]..w[1]].trim().is_empty())
+        .flat_map(|w| overlap_ranges(text, w[0]..w[1], config.max_bytes, config.overlap_bytes))
+        .collect()
+}
+
+fn page_ranges(text: &str, config: &ChunkConfig) -> Vec<Range<usize>> {
+    let mut pages: Vec<Range<usize>> = Vec::new();
+    let mut start = 0;
+    for (i, _) in text.match_indices(PAGE_BREAK) {
+        pages.push(start..i + 1);
+        start = i + 1;
+    }
+    if start < text.len() {
+        pages.push(start..text.len());
+    }
+
+    let tiny = config.max_bytes / 4;
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut current = 0..0;
+
+    for page in pages {
+        let merge = current.len() + page.len() <= config.max_bytes
+            && (current.len() < tiny || page.len() < tiny);
+        if current.is_empty() {
+            current = page;
+        } else if merge {
+            current.end = page.end;
+        } else {
+            ranges.extend(overlap_ranges(
+                text,
+                current.clone(),
+                config.max_bytes,
+                config.overlap_bytes,
+            ));
+            current = page;
+        }
+    }
+    if !current.is_empty() {
+        ranges.extend(overlap_ranges(
+            text,
+            current,
+            config.max_bytes,
+            config.overlap_bytes,
+        ));
+    }
+
+    ranges
+        .into_iter()
+        .filter(|r| !text[r.clone()].trim().is_empty())
+        .collect()
 }
 
-fn chunk_with_semantic_config(text: &str, config: &ChunkConfig, pattern: &Regex) -> Vec<String> {
+fn carried_line_start(text: &str, range: &Range<usize>) -> usize {
+    let chunk = &text[range.clone()];
+    let body = chunk.strip_suffix('\n').unwrap_or(chunk);
+    let body = body.strip_suffix('\r').unwrap_or(body);
+    let line_start = body.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    if body[line_start..].is_empty() {
+        range.end
+    } else {
+        range.start + line_start
+    }
+}
+
+fn semantic_ranges(text: &str, config: &ChunkConfig, pattern: &Regex) -> Vec<Range<usize>> {
     let mut split_points: Vec<usize> = vec![0];
     for m in pattern.find_iter(text) {
         let pos = m.start();
@@ -269,67 +694,78 @@ fn chunk_with_semantic_config(text: &str, config: &ChunkConfig, pattern: &Regex)
     split_points.push(text.len());
     split_points.dedup();
 
-    let mut chunks: Vec<String> = Vec::new();
-    let mut current = String::new();
-    let mut prev_last_line = String::new();
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut current = 0..0;
 
     for window in split_points.windows(2) {
-        let segment = &text[window[0]..window[1]];
+        let segment_len = window[1] - window[0];
 
-        if !current.is_empty() && current.len() + segment.len() > config.max_bytes {
+        if !current.is_empty() && current.len() + segment_len > config.max_bytes {
             if current.len() > config.max_bytes {
-                let sub_chunks =
-                    chunk_with_overlap(&current, config.max_bytes, config.overlap_bytes);
-                if let Some(last) = sub_chunks.last() {
-                    prev_last_line = last.lines().last().unwrap_or("").to_string();
-                }
-                chunks.extend(sub_chunks);
+                ranges.extend(overlap_ranges(
+                    text,
+                    current.clone(),
+                    config.max_bytes,
+                    config.overlap_bytes,
+                ));
             } else {
-                prev_last_line = current.lines().last().unwrap_or("").to_string();
-                chunks.push(current.clone());
-            }
-            current.clear();
-            if !prev_last_line.is_empty() {
-                current.push_str(&prev_last_line);
-                current.push('\n');
+                ranges.push(current.clone());
             }
+            let carried = ranges
+                .last()
+                .map(|last| carried_line_start(text, last))
+                .unwrap_or(current.end);
+            current = carried..current.end;
         }
 
-        current.push_str(segment);
+        current.end = window[1];
     }
 
-    if !current.trim().is_empty() {
+    if !text[current.clone()].trim().is_empty() {
         if current.len() > config.max_bytes {
-            chunks.extend(chunk_with_overlap(
-                &current,
+            ranges.extend(overlap_ranges(
+                text,
+                current,
                 config.max_bytes,
                 config.overlap_bytes,
             ));
         } else {
-            chunks.push(current);
+            ranges.push(current);
         }
     }
 
-    if chunks.is_empty() {
-        chunks.push(text.to_string());
+    if ranges.is_empty() {
+        ranges.push(0..text.len());
     }
 
-    chunks
+    ranges
 }
 
 pub fn chunk_with_overlap(text: &str, max_bytes: usize, overlap_bytes: usize) -> Vec<String> {
-    let mut chunks = Vec::new();
-    let mut start = 0;
+    overlap_ranges(text, 0..text.len(), max_bytes, overlap_bytes)
+        .into_iter()
+        .map(|r| text[r].to_string())
+        .collect()
+}
 
-    while start < text.len() {
-        let mut end = (start + max_bytes).min(text.len());
+fn overlap_ranges(
+    text: &str,
+    range: Range<usize>,
+    max_bytes: usize,
+    overlap_bytes: usize,
+) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = range.start;
 
-        while end < text.len() && !text.is_char_boundary(end) {
+    while start < range.end {
+        let mut end = (start + max_bytes).min(range.end);
+
+        while end < range.end && !text.is_char_boundary(end) {
             end -= 1;
         }
 
-        if end >= text.len() {
-            chunks.push(text[start..].to_string());
+        if end >= range.end {
+            ranges.push(start..range.end);
             break;
         }
 
@@ -341,7 +777,7 @@ pub fn chunk_with_overlap(text: &str, max_bytes: usize, overlap_bytes: usize) ->
             .map(|i| start + i + 1)
             .unwrap_or(end);
 
-        chunks.push(text[start..split_at].to_string());
+        ranges.push(start..split_at);
 
         let rewind = overlap_bytes.min(split_at - start);
         let mut overlap_start = split_at - rewind;
@@ -354,7 +790,7 @@ pub fn chunk_with_overlap(text: &str, max_bytes: usize, overlap_bytes: usize) ->
         start = overlap_start;
     }
 
-    chunks
+    ranges
 }
 
 const STOP_WORDS: &[&str] = &[
@@ -460,6 +896,19 @@ mod tests {
         assert!(!chunks.is_empty());
     }
 
+    #[test]
+    fn test_semantic_chunk_slides() {
+        let deck: String = (1..=4)
+            .map(|n| format!("## Slide {}\n{}\n", n, "bullet point text\n".repeat(40)))
+            .collect();
+        let chunks = semantic_chunk(&deck, "pptx");
+        assert_eq!(chunks.len(), 4);
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.matches("## Slide ").count(), 1);
+            assert!(chunk.contains(&format!("## Slide {}", i + 1)));
+        }
+    }
+
     #[test]
     fn test_semantic_chunk_fallback() {
         let long_func = format!("fn huge() {{\n{}\n}}", "    let x = 1;\n".repeat(500));
@@ -523,4 +972,267 @@ mod tests {
         assert!(chunks.iter().all(|c| c.len() <= 200));
         assert!(chunks.len() > 1);
     }
+
+    #[test]
+    fn test_spans_track_lines_and_bytes() {
+        let text = "first line\nsecond line\nthird line\nfourth line\n";
+        let spans = semantic_chunk_spans(text, "xyz", Some(100), Some(0));
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].start_line, 1);
+        assert_eq!(spans[0].end_line, 4);
+        assert_eq!(spans[0].end_byte, text.len());
+
+        let code = format!("fn a() {{\n{}}}\n\nfn b() {{\n    let y = 2;\n}}\n", "    let x = 1;\n".repeat(80));
+        let spans = semantic_chunk_spans(&code, "rs", Some(200), Some(40));
+        assert!(spans.len() > 1);
+        for span in &spans {
+            assert_eq!(&code[span.start_byte..span.end_byte], span.text);
+            let first_line = code[..span.start_byte].matches('\n').count() + 1;
+            assert_eq!(span.start_line, first_line);
+            assert!(span.end_line >= span.start_line);
+        }
+        let last = spans.last().unwrap();
+        assert!(last.text.contains("fn b()"));
+        assert_eq!(last.end_line, code.lines().count());
+    }
+
+    #[test]
+    fn test_pdf_chunks_follow_pages() {
+        let long_page = "page body sentence. ".repeat(30);
+        let pages = [long_page.as_str(), "short", long_page.as_str(), long_page.as_str()];
+        let text = pages.join("\u{c}");
+        let spans = semantic_chunk_spans(&text, "pdf", None, None);
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans.iter().map(|s| s.page).collect::<Vec<_>>(), vec![Some(1), Some(3), Some(4)]);
+        assert!(spans[0].text.contains("short"));
+        assert!(spans[1..].iter().all(|s| s.text.matches(PAGE_BREAK).count() <= 1));
+
+        let spans = semantic_chunk_spans("plain text", "txt", None, None);
+        assert_eq!(spans[0].page, None);
+    }
+
+    #[test]
+    fn test_notebook_chunks_follow_cells() {
+        let long_cell = "x = compute(x)\n".repeat(100);
+        let text = format!(
+            "# %% [markdown]\n# Title\n\n# %% [code]\nimport os\n\n# %% [code]\n{}\n",
+            long_cell
+        );
+        let spans = semantic_chunk_spans(&text, "ipynb", None, None);
+        assert_eq!(spans[0].cell, Some(1));
+        assert_eq!(spans[1].cell, Some(2));
+        assert!(spans[1].text.starts_with("# %% [code]\nimport os"));
+        assert!(spans.len() > 3);
+        assert!(spans[2..].iter().all(|s| s.cell == Some(3)));
+        assert!(spans.iter().all(|s| s.page.is_none()));
+    }
+
+    #[test]
+    fn test_email_chunks_never_span_messages() {
+        let body = "Quoted price and delivery terms. ".repeat(30);
+        let text = format!(
+            "Subject: One\n\n{}\n\u{1e}Subject: Two\n\nShort reply.\n",
+            body
+        );
+        let spans = semantic_chunk_spans(&text, "mbox", None, None);
+        let last = spans.last().unwrap();
+        assert!(last.text.starts_with("Subject: Two"));
+        assert!(spans[..spans.len() - 1].iter().all(|s| !s.text.contains("Subject: Two")));
+    }
+
+    #[test]
+    fn test_config_chunks_carry_key_paths() {
+        let yaml = format!(
+            "services:\n  api:\n    env:\n      DATABASE_URL: postgres://db/app\n  cache:\n{}",
+            "    maxmemory: 2gb\n".repeat(60)
+        );
+        let spans = semantic_chunk_spans(&yaml, "yaml", None, None);
+        assert_eq!(spans[0].text, "Key: services.api\n  api:\n    env:\n      DATABASE_URL: postgres://db/app\n");
+        assert_eq!((spans[0].start_line, spans[0].end_line), (2, 4));
+        assert!(spans[1..].iter().all(|s| s.text.starts_with("Key: services.cache")));
+    }
+
+    #[test]
+    fn test_markdown_chunks_carry_heading_breadcrumbs() {
+        let md = format!(
+            "# Install\n\n## Linux\n\n{}\n### Wayland\n\nExport the backend variable.\n",
+            "Download the AppImage and mark it executable.\n".repeat(20)
+        );
+        let spans = semantic_chunk_spans(&md, "md", None, None);
+        assert_eq!(spans[0].breadcrumb.as_deref(), Some("Install"));
+        let linux = spans.iter().filter(|s| s.text.contains("Download")).collect::<Vec<_>>();
+        assert!(linux.len() > 1);
+        assert!(linux.iter().all(|s| s.breadcrumb.as_deref() == Some("Install > Linux")));
+        let last = spans.last().unwrap();
+        assert!(last.text.contains("Export the backend"));
+        assert_eq!(last.breadcrumb.as_deref(), Some("Install > Linux > Wayland"));
+        assert!(semantic_chunk_spans("plain words", "txt", None, None)[0].breadcrumb.is_none());
+    }
+
+    #[test]
+    fn test_token_sizing_follows_script_density() {
+        let sizing = ChunkSizing {
+            chunk_size: Some(64),
+            chunk_overlap: Some(0),
+            unit: ChunkUnit::Tokens,
+            budget: Some(TokenBudget::estimated(512)),
+            ..Default::default()
+        };
+        let latin = "The indexer splits text into pieces. ".repeat(80);
+        let cjk = "索引器将文本拆分成片段。".repeat(80);
+        let latin_spans = sized_chunk_spans(&latin, "txt", &sizing);
+        let cjk_spans = sized_chunk_spans(&cjk, "txt", &sizing);
+        for spans in [&latin_spans, &cjk_spans] {
+            assert!(spans.len() > 1);
+            assert!(spans.iter().all(|s| estimate_tokens(&s.text) <= 72));
+        }
+        assert!(latin_spans[0].text.len() > cjk_spans[0].text.len());
+        assert!(cjk_spans.len() > latin_spans.len());
+    }
+
+    #[test]
+    fn test_token_sizes_are_clamped_in_tokens() {
+        let sizing = |tokens: usize| ChunkSizing {
+            chunk_size: Some(tokens),
+            chunk_overlap: Some(0),
+            unit: ChunkUnit::Tokens,
+            budget: Some(TokenBudget::estimated(512)),
+            ..Default::default()
+        };
+        let cjk = "索引器将文本拆分成片段。".repeat(40);
+        let spans = sized_chunk_spans(&cjk, "txt", &sizing(MIN_CHUNK_TOKENS + 5));
+        assert!(spans.len() > 1);
+        assert!(spans.iter().all(|s| estimate_tokens(&s.text) <= MIN_CHUNK_TOKENS + 5));
+        assert!(spans[0].text.len() < MIN_CHUNK_BYTES);
+        assert_eq!(
+            sized_chunk_spans(&cjk, "txt", &sizing(1)),
+            sized_chunk_spans(&cjk, "txt", &sizing(MIN_CHUNK_TOKENS))
+        );
+    }
+
+    #[test]
+    fn test_chunks_over_the_model_limit_are_split() {
+        let words = |text: &str| text.split_whitespace().count();
+        let sizing = ChunkSizing {
+            chunk_size: Some(10_000),
+            unit: ChunkUnit::Bytes,
+            budget: Some(TokenBudget {
+                count: Arc::new(words),
+                max_tokens: RESERVED_TOKENS + 40,
+            }),
+            ..Default::default()
+        };
+        let code = format!("fn long() {{\n{}}}\n", "    let a = b + c;\n".repeat(30));
+        let spans = sized_chunk_spans(&code, "rs", &sizing);
+        assert!(spans.len() > 1);
+        assert!(spans.iter().all(|s| words(&s.text) <= 40));
+        assert!(spans.iter().all(|s| s.symbol.as_deref() == Some("long")));
+        assert_eq!(spans.last().unwrap().end_byte, code.len());
+    }
+
+    #[test]
+    fn test_oversized_key_path_chunks_are_split_under_their_key() {
+        let words = |text: &str| text.split_whitespace().count();
+        let yaml = format!(
+            "services:\n  cache:\n    notes: |\n{}",
+            "      evicts the oldest keys under memory pressure\n".repeat(60)
+        );
+        let sizing = ChunkSizing {
+            chunk_size: Some(600),
+            unit: ChunkUnit::Bytes,
+            budget: Some(TokenBudget {
+                count: Arc::new(words),
+                max_tokens: RESERVED_TOKENS + 40,
+            }),
+            ..Default::default()
+        };
+        let spans = sized_chunk_spans(&yaml, "yaml", &sizing);
+        assert!(spans.len() > 1);
+        for span in &spans {
+            assert!(words(&span.text) <= 40);
+            assert_eq!(&span.text[..span.prefix_len], "Key: services.cache.notes\n");
+            assert_eq!(&yaml[span.start_byte..span.end_byte], &span.text[span.prefix_len..]);
+        }
+        assert_eq!(spans.last().unwrap().end_byte, yaml.len());
+
+        let sizing = ChunkSizing {
+            parent_chunks: true,
+            ..Default::default()
+        };
+        let children = sized_chunk_spans(&yaml, "yaml", &sizing);
+        assert!(children.len() > spans.len());
+        for child in &children {
+            let parent = child.parent.as_deref().unwrap();
+            assert_eq!(child.text[..child.prefix_len], parent.text[..parent.prefix_len]);
+            assert!(parent.start_byte <= child.start_byte && child.end_byte <= parent.end_byte);
+        }
+    }
+
+    #[test]
+    fn test_oversized_rows_are_split_under_their_header() {
+        let words = |text: &str| text.split_whitespace().count();
+        let note = "the shipment left the warehouse late ".repeat(6);
+        let csv = format!("id,a,b,c\n1,{0},{0},{0}\n2,short,row,here\n", note.trim());
+        let sizing = ChunkSizing {
+            chunk_size: Some(10_000),
+            unit: ChunkUnit::Bytes,
+            budget: Some(TokenBudget {
+                count: Arc::new(words),
+                max_tokens: RESERVED_TOKENS + 40,
+            }),
+            ..Default::default()
+        };
+        let spans = sized_chunk_spans(&csv, "csv", &sizing);
+        assert!(spans.len() > 1);
+        for span in &spans {
+            assert!(words(&span.text) <= 40);
+            assert_eq!(&span.text[..span.prefix_len], "id,a,b,c\n");
+            assert!(span.start_byte < span.end_byte);
+        }
+        for pair in spans.windows(2) {
+            assert_eq!(pair[0].end_byte, pair[1].start_byte);
+            assert!(pair[0].end_line <= pair[1].start_line);
+        }
+        assert_eq!(spans[0].start_byte, 9);
+        assert_eq!(spans.last().unwrap().end_byte, csv.len());
+        assert_eq!(spans.last().unwrap().end_line, 3);
+        assert!(spans.last().unwrap().text.ends_with("id: 2 | a: short | b: row | c: here"));
+
+        let sizing = ChunkSizing {
+            parent_chunks: true,
+            ..Default::default()
+        };
+        let children = sized_chunk_spans(&csv, "csv", &sizing);
+        assert!(children.len() > 1);
+        for child in &children {
+            assert!(child.text.starts_with("id,a,b,c\n"));
+            assert!(child.parent.as_deref().unwrap().text.starts_with("id,a,b,c\nid: 1 | a: the shipment"));
+        }
+    }
+
+    #[test]
+    fn test_parent_chunks_link_children_to_their_section() {
+        let sizing = ChunkSizing {
+            parent_chunks: true,
+            ..Default::default()
+        };
+        let md = format!(
+            "# Install\n\n{}\n# Usage\n\nRun it.\n{}",
+            "Download the AppImage and mark it executable.\n".repeat(14),
+            "Pass --help for flags.\n".repeat(6)
+        );
+        let spans = sized_chunk_spans(&md, "md", &sizing);
+        let install: Vec<_> = spans.iter().filter(|s| s.breadcrumb.as_deref() == Some("Install")).collect();
+        assert!(install.len() > 2);
+        let parent = install[0].parent.as_deref().unwrap();
+        assert!(parent.text.starts_with("# Install"));
+        assert_eq!(&md[parent.start_byte..parent.end_byte], parent.text);
+        for child in &install {
+            assert!(child.text.len() < parent.text.len());
+            assert!(parent.start_byte <= child.start_byte && child.end_byte <= parent.end_byte);
+        }
+        let usage = spans.last().unwrap();
+        assert!(usage.text.contains("Run it."));
+        assert!(usage.parent.is_none());
+    }
 }
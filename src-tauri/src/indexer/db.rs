@@ -1,9 +1,9 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
-use log::{info, debug, warn};
+use log::{info, debug};
 use arrow_array::{
     Float32Array, FixedSizeListArray, Int64Array, RecordBatch, RecordBatchIterator, StringArray,
 };
@@ -13,25 +13,138 @@ use lancedb::connection::Connection;
 use lancedb::index::Index;
 use lancedb::query::{ExecutableQuery, QueryBase};
 use lancedb::Table;
+use serde::Serialize;
+
+use crate::config::IndexRoot;
+
+use super::schema::{self, TableKind};
+
+const DELETE_BATCH_SIZE: usize = 500;
+const STAGED_SUFFIX: &str = ":staged";
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ChunkMeta {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub chunk_index: usize,
+    pub language: String,
+    pub file_size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cell: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol_kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breadcrumb: Option<String>,
+    #[serde(skip)]
+    pub parent: Option<ParentSection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_start_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_end_line: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParentSection {
+    pub text: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub start_byte: usize,
+    pub end_byte: usize,
+}
 
 pub struct Record {
     pub path: String,
+    pub root_id: String,
+    pub rel_path: String,
     pub content: String,
     pub vector: Vec<f32>,
     pub mtime: i64,
+    pub file_hash: String,
+    pub chunk_hash: String,
+    pub meta: ChunkMeta,
 }
 
 pub struct PendingChunk {
     pub path: String,
+    pub root_id: String,
+    pub rel_path: String,
     pub content: String,
     pub mtime: i64,
+    pub file_hash: String,
+    pub chunk_hash: String,
+    pub meta: ChunkMeta,
+}
+
+pub const CHUNK_META_COLUMNS: &[&str] = &[
+    "start_line",
+    "end_line",
+    "start_byte",
+    "end_byte",
+    "chunk_index",
+    "language",
+    "file_size",
+    "page",
+    "cell",
+    "encoding",
+    "symbol",
+    "symbol_kind",
+    "breadcrumb",
+    "parent",
+    "parent_start_line",
+    "parent_end_line",
+    "parent_start_byte",
+    "parent_end_byte",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileKey {
+    pub root_id: String,
+    pub rel_path: String,
+}
+
+impl FileKey {
+    pub fn new(root: &IndexRoot, path: &str) -> Self {
+        Self {
+            root_id: root.id.clone(),
+            rel_path: root.relative(Path::new(path)).unwrap_or_else(|| path.to_string()),
+        }
+    }
+
+    pub fn staged(&self) -> Self {
+        Self {
+            root_id: format!("{}{}", self.root_id, STAGED_SUFFIX),
+            rel_path: self.rel_path.clone(),
+        }
+    }
+
+    fn filter(&self) -> String {
+        format!(
+            "root_id = '{}' AND rel_path = '{}'",
+            self.root_id.replace('\'', "''"),
+            self.rel_path.replace('\'', "''")
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileState {
+    pub mtime: i64,
+    pub file_hash: String,
 }
 
 pub async fn reset_index(db_path: &Path, table_name: &str) -> Result<()> {
     let db = lancedb::connect(&db_path.to_string_lossy())
         .execute()
         .await?;
-    let _ = db.drop_table(table_name, &[]).await;
+    schema::drop_versioned_table(&db, table_name).await?;
     info!("Index reset: dropped table '{}'", table_name);
     Ok(())
 }
@@ -53,12 +166,14 @@ pub async fn build_fts_index(table: &Table) -> Result<()> {
     Ok(())
 }
 
-pub async fn get_single_file_mtime(table: &Table, file_path: &str) -> Result<Option<i64>> {
-    let safe_path = file_path.replace('\'', "''");
+pub async fn get_single_file_state(table: &Table, key: &FileKey) -> Result<Option<FileState>> {
     let results = table
         .query()
-        .only_if(format!("path = '{}'", safe_path))
-        .select(lancedb::query::Select::Columns(vec!["mtime".to_string()]))
+        .only_if(key.filter())
+        .select(lancedb::query::Select::Columns(vec![
+            "mtime".to_string(),
+            "file_hash".to_string(),
+        ]))
         .limit(1)
         .execute()
         .await?
@@ -66,26 +181,106 @@ pub async fn get_single_file_mtime(table: &Table, file_path: &str) -> Result<Opt
         .await?;
 
     for batch in results {
-        if let Some(mtime_array) = batch
+        let mtime_array = batch
             .column_by_name("mtime")
-            .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
-        {
+            .and_then(|c| c.as_any().downcast_ref::<Int64Array>());
+        let hash_array = batch
+            .column_by_name("file_hash")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        if let (Some(mtimes), Some(hashes)) = (mtime_array, hash_array) {
             if batch.num_rows() > 0 {
-                return Ok(Some(mtime_array.value(0)));
+                return Ok(Some(FileState {
+                    mtime: mtimes.value(0),
+                    file_hash: hashes.value(0).to_string(),
+                }));
             }
         }
     }
     Ok(None)
 }
 
-pub async fn get_indexed_mtimes(table: &Table) -> Result<HashMap<String, i64>> {
-    let mut mtimes = HashMap::new();
+pub async fn get_indexed_files(table: &Table, root: &IndexRoot) -> Result<HashMap<String, FileState>> {
+    file_states(
+        table
+            .query()
+            .only_if(format!("root_id = '{}'", root.id.replace('\'', "''"))),
+        root,
+    )
+    .await
+}
+
+pub async fn get_archive_files(table: &Table, root: &IndexRoot, archive_path: &str) -> Result<HashMap<String, FileState>> {
+    let key = FileKey::new(root, archive_path);
+    let prefix = super::archive::virtual_path(&key.rel_path, "");
+    file_states(
+        table.query().only_if(format!(
+            "root_id = '{}' AND starts_with(rel_path, '{}')",
+            key.root_id.replace('\'', "''"),
+            prefix.replace('\'', "''")
+        )),
+        root,
+    )
+    .await
+}
 
+async fn file_states(query: lancedb::query::Query, root: &IndexRoot) -> Result<HashMap<String, FileState>> {
+    let mut files = HashMap::new();
+
+    let results = query
+        .select(lancedb::query::Select::Columns(vec![
+            "rel_path".to_string(),
+            "mtime".to_string(),
+            "file_hash".to_string(),
+        ]))
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    for batch in results {
+        let path_array = batch
+            .column_by_name("rel_path")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        let mtime_array = batch
+            .column_by_name("mtime")
+            .and_then(|c| c.as_any().downcast_ref::<Int64Array>());
+        let hash_array = batch
+            .column_by_name("file_hash")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>());
+
+        if let (Some(paths), Some(mtimes), Some(hashes)) = (path_array, mtime_array, hash_array) {
+            for i in 0..batch.num_rows() {
+                files.insert(
+                    root.absolute(paths.value(i)),
+                    FileState {
+                        mtime: mtimes.value(i),
+                        file_hash: hashes.value(i).to_string(),
+                    },
+                );
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+pub async fn parent_texts(table: &Table, spans: &[(String, usize)]) -> Result<HashMap<(String, usize), String>> {
+    let mut texts = HashMap::new();
+    if spans.is_empty() {
+        return Ok(texts);
+    }
+    let filter = spans
+        .iter()
+        .map(|(path, start)| format!("(path = '{}' AND parent_start_byte = {})", path.replace('\'', "''"), start))
+        .collect::<Vec<_>>()
+        .join(" OR ");
     let results = table
         .query()
+        .only_if(format!("parent != '' AND ({})", filter))
         .select(lancedb::query::Select::Columns(vec![
             "path".to_string(),
-            "mtime".to_string(),
+            "parent_start_byte".to_string(),
+            "parent".to_string(),
         ]))
         .execute()
         .await?
@@ -96,33 +291,252 @@ pub async fn get_indexed_mtimes(table: &Table) -> Result<HashMap<String, i64>> {
         let path_array = batch
             .column_by_name("path")
             .and_then(|c| c.as_any().downcast_ref::<StringArray>());
-        let mtime_array = batch
-            .column_by_name("mtime")
+        let start_array = batch
+            .column_by_name("parent_start_byte")
             .and_then(|c| c.as_any().downcast_ref::<Int64Array>());
+        let parent_array = batch
+            .column_by_name("parent")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>());
+
+        if let (Some(paths), Some(starts), Some(parents)) = (path_array, start_array, parent_array) {
+            for i in 0..batch.num_rows() {
+                texts.insert(
+                    (paths.value(i).to_string(), starts.value(i) as usize),
+                    parents.value(i).to_string(),
+                );
+            }
+        }
+    }
+    Ok(texts)
+}
+
+pub async fn get_chunk_vectors(table: &Table, key: &FileKey) -> Result<HashMap<String, Vec<f32>>> {
+    let results = table
+        .query()
+        .only_if(key.filter())
+        .select(lancedb::query::Select::Columns(vec![
+            "chunk_hash".to_string(),
+            "vector".to_string(),
+        ]))
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut vectors = HashMap::new();
+    for batch in results {
+        let hash_array = batch
+            .column_by_name("chunk_hash")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        let vector_array = batch
+            .column_by_name("vector")
+            .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>());
 
-        if let (Some(paths), Some(mtimes_col)) = (path_array, mtime_array) {
+        if let (Some(hashes), Some(fsl)) = (hash_array, vector_array) {
             for i in 0..batch.num_rows() {
-                mtimes.insert(paths.value(i).to_string(), mtimes_col.value(i));
+                let values = fsl.value(i);
+                if let Some(floats) = values.as_any().downcast_ref::<Float32Array>() {
+                    vectors.insert(hashes.value(i).to_string(), floats.values().to_vec());
+                }
             }
         }
     }
+    Ok(vectors)
+}
+
+pub async fn update_mtime(table: &Table, key: &FileKey, mtime: i64) -> Result<()> {
+    table
+        .update()
+        .only_if(key.filter())
+        .column("mtime", mtime.to_string())
+        .execute()
+        .await?;
+    Ok(())
+}
+
+pub async fn update_file_state(table: &Table, key: &FileKey, mtime: i64, file_hash: &str) -> Result<()> {
+    table
+        .update()
+        .only_if(key.filter())
+        .column("mtime", mtime.to_string())
+        .column("file_hash", format!("'{}'", file_hash.replace('\'', "''")))
+        .execute()
+        .await?;
+    Ok(())
+}
+
+fn sql_string_list<S: AsRef<str>>(values: &[S]) -> String {
+    values
+        .iter()
+        .map(|v| format!("'{}'", v.as_ref().replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn keys_filter(keys: &[&FileKey]) -> String {
+    let mut by_root: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for key in keys {
+        by_root.entry(&key.root_id).or_default().push(&key.rel_path);
+    }
+    by_root
+        .into_iter()
+        .map(|(root_id, rel_paths)| {
+            format!(
+                "(root_id = '{}' AND rel_path IN ({}))",
+                root_id.replace('\'', "''"),
+                sql_string_list(&rel_paths)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" OR ")
+}
+
+pub async fn update_mtimes(table: &Table, files: &[(FileKey, i64)]) -> Result<()> {
+    let mut by_mtime: HashMap<i64, Vec<&FileKey>> = HashMap::new();
+    for (key, mtime) in files {
+        by_mtime.entry(*mtime).or_default().push(key);
+    }
+    for (mtime, keys) in by_mtime {
+        for batch in keys.chunks(DELETE_BATCH_SIZE) {
+            table
+                .update()
+                .only_if(keys_filter(batch))
+                .column("mtime", mtime.to_string())
+                .execute()
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn delete_files(table: &Table, keys: &[FileKey]) -> Result<usize> {
+    let keys: Vec<&FileKey> = keys.iter().collect();
+    for batch in keys.chunks(DELETE_BATCH_SIZE) {
+        table.delete(&keys_filter(batch)).await?;
+    }
+    Ok(keys.len())
+}
 
-    Ok(mtimes)
+pub async fn append_records(table: &Table, records: Vec<Record>) -> Result<()> {
+    let batch = create_record_batch(records)?;
+    let schema = batch.schema();
+    table
+        .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
+        .execute()
+        .await?;
+    Ok(())
+}
+
+pub async fn upsert_records(table: &Table, records: Vec<Record>) -> Result<()> {
+    let mut keys: Vec<FileKey> = records
+        .iter()
+        .map(|r| FileKey { root_id: r.root_id.clone(), rel_path: r.rel_path.clone() })
+        .collect();
+    keys.sort();
+    keys.dedup();
+    let mut paths: Vec<&str> = records.iter().map(|r| r.path.as_str()).collect();
+    paths.sort_unstable();
+    paths.dedup();
+    let key_filter = format!(
+        "{} OR (root_id = '' AND path IN ({}))",
+        keys_filter(&keys.iter().collect::<Vec<_>>()),
+        sql_string_list(&paths)
+    );
+
+    let batch = create_record_batch(records)?;
+    let schema = batch.schema();
+    let mut merge = table.merge_insert(&["root_id", "rel_path", "chunk_index"]);
+    merge
+        .when_matched_update_all(None)
+        .when_not_matched_insert_all()
+        .when_not_matched_by_source_delete(Some(key_filter));
+    merge
+        .execute(Box::new(RecordBatchIterator::new(vec![Ok(batch)], schema)))
+        .await?;
+    Ok(())
+}
+
+pub fn root_prefix(root: &str) -> String {
+    if root.ends_with(std::path::MAIN_SEPARATOR) {
+        root.to_string()
+    } else {
+        format!("{}{}", root, std::path::MAIN_SEPARATOR)
+    }
+}
+
+pub async fn assign_root(table: &Table, root: &IndexRoot, nested: &[IndexRoot]) -> Result<u64> {
+    let prefix = root_prefix(&root.path);
+    let mut filter = format!(
+        "root_id = '' AND starts_with(path, '{}')",
+        prefix.replace('\'', "''")
+    );
+    for inner in nested {
+        filter.push_str(&format!(
+            " AND NOT starts_with(path, '{}')",
+            root_prefix(&inner.path).replace('\'', "''")
+        ));
+    }
+    let result = table
+        .update()
+        .only_if(filter)
+        .column("root_id", format!("'{}'", root.id.replace('\'', "''")))
+        .column(
+            "rel_path",
+            format!(
+                "replace(substr(path, {}), '{}', '/')",
+                prefix.chars().count() + 1,
+                std::path::MAIN_SEPARATOR
+            ),
+        )
+        .execute()
+        .await?;
+    Ok(result.rows_updated)
+}
+
+pub async fn clear_staged(table: &Table, root: &IndexRoot) -> Result<()> {
+    table
+        .delete(&format!("root_id = '{}{}'", root.id.replace('\'', "''"), STAGED_SUFFIX))
+        .await?;
+    Ok(())
+}
+
+pub async fn commit_staged(table: &Table, key: &FileKey, state: &FileState) -> Result<()> {
+    table.delete(&key.filter()).await?;
+    table
+        .update()
+        .only_if(key.staged().filter())
+        .column("root_id", format!("'{}'", key.root_id.replace('\'', "''")))
+        .column("mtime", state.mtime.to_string())
+        .column("file_hash", format!("'{}'", state.file_hash.replace('\'', "''")))
+        .execute()
+        .await?;
+    Ok(())
+}
+
+pub async fn relocate_root(table: &Table, root: &IndexRoot) -> Result<u64> {
+    let result = table
+        .update()
+        .only_if(format!("root_id = '{}'", root.id.replace('\'', "''")))
+        .column(
+            "path",
+            format!(
+                "concat('{}', replace(rel_path, '/', '{}'))",
+                root_prefix(&root.path).replace('\'', "''"),
+                std::path::MAIN_SEPARATOR
+            ),
+        )
+        .execute()
+        .await?;
+    info!("Relocated {} rows of root {} to {}", result.rows_updated, root.id, root.path);
+    Ok(result.rows_updated)
 }
 
 pub async fn get_or_create_table(db: &Connection, table_name: &str, dim: usize) -> Result<Table> {
     if let Ok(table) = db.open_table(table_name).execute().await {
         let schema = table.schema().await?;
-        let has_mtime = schema.field_with_name("mtime").is_ok();
-        if let Ok(field) = schema.field_with_name("vector") {
-            if let DataType::FixedSizeList(_, size) = field.data_type() {
-                if *size == dim as i32 && has_mtime {
-                    return Ok(table);
-                }
-            }
-        }
-        warn!("Table '{}' schema mismatch (dim or mtime), recreating", table_name);
-        let _ = db.drop_table(table_name, &[]).await;
+        schema::check_dimension(&schema, table_name, dim)?;
+        schema::migrate(db, &table, table_name, TableKind::Chunks).await?;
+        return Ok(table);
     }
 
     let schema = Arc::new(make_schema(dim));
@@ -131,6 +545,7 @@ pub async fn get_or_create_table(db: &Connection, table_name: &str, dim: usize)
         .create_table(table_name, RecordBatchIterator::new(vec![], schema))
         .execute()
         .await?;
+    schema::set_version(db, table_name, TableKind::Chunks.current_version()).await?;
 
     info!("Table '{}' created (dim={})", table_name, dim);
 
@@ -140,6 +555,8 @@ pub async fn get_or_create_table(db: &Connection, table_name: &str, dim: usize)
 fn make_schema(dim: usize) -> Schema {
     Schema::new(vec![
         Field::new("path", DataType::Utf8, false),
+        Field::new("root_id", DataType::Utf8, false),
+        Field::new("rel_path", DataType::Utf8, false),
         Field::new("content", DataType::Utf8, false),
         Field::new(
             "vector",
@@ -150,9 +567,114 @@ fn make_schema(dim: usize) -> Schema {
             false,
         ),
         Field::new("mtime", DataType::Int64, false),
+        Field::new("file_hash", DataType::Utf8, false),
+        Field::new("chunk_hash", DataType::Utf8, false),
+        Field::new("start_line", DataType::Int64, false),
+        Field::new("end_line", DataType::Int64, false),
+        Field::new("start_byte", DataType::Int64, false),
+        Field::new("end_byte", DataType::Int64, false),
+        Field::new("chunk_index", DataType::Int64, false),
+        Field::new("language", DataType::Utf8, false),
+        Field::new("file_size", DataType::Int64, false),
+        Field::new("page", DataType::Int64, false),
+        Field::new("cell", DataType::Int64, false),
+        Field::new("encoding", DataType::Utf8, false),
+        Field::new("symbol", DataType::Utf8, false),
+        Field::new("symbol_kind", DataType::Utf8, false),
+        Field::new("breadcrumb", DataType::Utf8, false),
+        Field::new("parent", DataType::Utf8, false),
+        Field::new("parent_start_line", DataType::Int64, false),
+        Field::new("parent_end_line", DataType::Int64, false),
+        Field::new("parent_start_byte", DataType::Int64, false),
+        Field::new("parent_end_byte", DataType::Int64, false),
     ])
 }
 
+pub fn read_chunk_meta(batch: &RecordBatch) -> Vec<Option<ChunkMeta>> {
+    let int_column = |name: &str| {
+        batch
+            .column_by_name(name)
+            .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+    };
+    let string_column = |name: &str| {
+        batch
+            .column_by_name(name)
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+    };
+    let columns = (
+        int_column("start_line"),
+        int_column("end_line"),
+        int_column("start_byte"),
+        int_column("end_byte"),
+        int_column("chunk_index"),
+        string_column("language"),
+        int_column("file_size"),
+        int_column("page"),
+        int_column("cell"),
+        string_column("encoding"),
+        string_column("symbol"),
+        string_column("symbol_kind"),
+        string_column("breadcrumb"),
+        string_column("parent"),
+        int_column("parent_start_line"),
+        int_column("parent_end_line"),
+        int_column("parent_start_byte"),
+        int_column("parent_end_byte"),
+    );
+    let (
+        Some(start_lines),
+        Some(end_lines),
+        Some(start_bytes),
+        Some(end_bytes),
+        Some(indices),
+        Some(languages),
+        Some(sizes),
+        Some(pages),
+        Some(cells),
+        Some(encodings),
+        Some(symbols),
+        Some(symbol_kinds),
+        Some(breadcrumbs),
+        Some(parents),
+        Some(parent_start_lines),
+        Some(parent_end_lines),
+        Some(parent_start_bytes),
+        Some(parent_end_bytes),
+    ) = columns
+    else {
+        return vec![None; batch.num_rows()];
+    };
+
+    (0..batch.num_rows())
+        .map(|i| {
+            Some(ChunkMeta {
+                start_line: start_lines.value(i) as usize,
+                end_line: end_lines.value(i) as usize,
+                start_byte: start_bytes.value(i) as usize,
+                end_byte: end_bytes.value(i) as usize,
+                chunk_index: indices.value(i).max(0) as usize,
+                language: languages.value(i).to_string(),
+                file_size: sizes.value(i) as u64,
+                page: (pages.value(i) > 0).then(|| pages.value(i) as usize),
+                cell: (cells.value(i) > 0).then(|| cells.value(i) as usize),
+                encoding: (!encodings.value(i).is_empty()).then(|| encodings.value(i).to_string()),
+                symbol: (!symbols.value(i).is_empty()).then(|| symbols.value(i).to_string()),
+                symbol_kind: (!symbol_kinds.value(i).is_empty()).then(|| symbol_kinds.value(i).to_string()),
+                breadcrumb: (!breadcrumbs.value(i).is_empty()).then(|| breadcrumbs.value(i).to_string()),
+                parent: (parent_end_bytes.value(i) > 0).then(|| ParentSection {
+                    text: parents.value(i).to_string(),
+                    start_line: parent_start_lines.value(i) as usize,
+                    end_line: parent_end_lines.value(i) as usize,
+                    start_byte: parent_start_bytes.value(i) as usize,
+                    end_byte: parent_end_bytes.value(i) as usize,
+                }),
+                match_start_line: None,
+                match_end_line: None,
+            })
+        })
+        .collect()
+}
+
 pub fn create_record_batch(records: Vec<Record>) -> Result<RecordBatch> {
     if records.is_empty() {
         return Err(anyhow!("No records to convert"));
@@ -162,8 +684,51 @@ pub fn create_record_batch(records: Vec<Record>) -> Result<RecordBatch> {
     let schema = Arc::new(make_schema(dim));
 
     let paths: Vec<String> = records.iter().map(|r| r.path.clone()).collect();
+    let root_ids: Vec<String> = records.iter().map(|r| r.root_id.clone()).collect();
+    let rel_paths: Vec<String> = records.iter().map(|r| r.rel_path.clone()).collect();
     let contents: Vec<String> = records.iter().map(|r| r.content.clone()).collect();
     let mtimes: Vec<i64> = records.iter().map(|r| r.mtime).collect();
+    let file_hashes: Vec<String> = records.iter().map(|r| r.file_hash.clone()).collect();
+    let chunk_hashes: Vec<String> = records.iter().map(|r| r.chunk_hash.clone()).collect();
+    let meta_column = |f: fn(&ChunkMeta) -> i64| -> Int64Array {
+        records.iter().map(|r| f(&r.meta)).collect::<Vec<i64>>().into()
+    };
+    let start_lines = meta_column(|m| m.start_line as i64);
+    let end_lines = meta_column(|m| m.end_line as i64);
+    let start_bytes = meta_column(|m| m.start_byte as i64);
+    let end_bytes = meta_column(|m| m.end_byte as i64);
+    let chunk_indices = meta_column(|m| m.chunk_index as i64);
+    let languages: Vec<String> = records.iter().map(|r| r.meta.language.clone()).collect();
+    let file_sizes = meta_column(|m| m.file_size as i64);
+    let pages = meta_column(|m| m.page.unwrap_or(0) as i64);
+    let cells = meta_column(|m| m.cell.unwrap_or(0) as i64);
+    let encodings: Vec<String> = records
+        .iter()
+        .map(|r| r.meta.encoding.clone().unwrap_or_default())
+        .collect();
+    let symbols: Vec<String> = records
+        .iter()
+        .map(|r| r.meta.symbol.clone().unwrap_or_default())
+        .collect();
+    let symbol_kinds: Vec<String> = records
+        .iter()
+        .map(|r| r.meta.symbol_kind.clone().unwrap_or_default())
+        .collect();
+    let breadcrumbs: Vec<String> = records
+        .iter()
+        .map(|r| r.meta.breadcrumb.clone().unwrap_or_default())
+        .collect();
+    let parents: Vec<String> = records
+        .iter()
+        .map(|r| r.meta.parent.as_ref().map(|p| p.text.clone()).unwrap_or_default())
+        .collect();
+    let parent_column = |f: fn(&ParentSection) -> usize| -> Int64Array {
+        records
+            .iter()
+            .map(|r| r.meta.parent.as_ref().map_or(0, |p| f(p) as i64))
+            .collect::<Vec<i64>>()
+            .into()
+    };
 
     let mut flat_vectors = Vec::with_capacity(records.len() * dim);
     for r in &records {
@@ -181,9 +746,31 @@ pub fn create_record_batch(records: Vec<Record>) -> Result<RecordBatch> {
         schema,
         vec![
             Arc::new(StringArray::from(paths)),
+            Arc::new(StringArray::from(root_ids)),
+            Arc::new(StringArray::from(rel_paths)),
             Arc::new(StringArray::from(contents)),
             Arc::new(vector_array),
             Arc::new(Int64Array::from(mtimes)),
+            Arc::new(StringArray::from(file_hashes)),
+            Arc::new(StringArray::from(chunk_hashes)),
+            Arc::new(start_lines),
+            Arc::new(end_lines),
+            Arc::new(start_bytes),
+            Arc::new(end_bytes),
+            Arc::new(chunk_indices),
+            Arc::new(StringArray::from(languages)),
+            Arc::new(file_sizes),
+            Arc::new(pages),
+            Arc::new(cells),
+            Arc::new(StringArray::from(encodings)),
+            Arc::new(StringArray::from(symbols)),
+            Arc::new(StringArray::from(symbol_kinds)),
+            Arc::new(StringArray::from(breadcrumbs)),
+            Arc::new(StringArray::from(parents)),
+            Arc::new(parent_column(|p| p.start_line)),
+            Arc::new(parent_column(|p| p.end_line)),
+            Arc::new(parent_column(|p| p.start_byte)),
+            Arc::new(parent_column(|p| p.end_byte)),
         ],
     )
     .map_err(|e| anyhow!(e))
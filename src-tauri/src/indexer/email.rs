@@ -0,0 +1,448 @@
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::{alphabet, Engine as _};
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+
+pub const MESSAGE_BREAK: char = '\u{1e}';
+
+const SHOWN_HEADERS: &[&str] = &["subject", "from", "to", "cc", "date"];
+
+const MAX_DEPTH: usize = 8;
+
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+pub fn is_email_extension(ext: &str) -> bool {
+    matches!(ext, "eml" | "mbox" | "mbx")
+}
+
+pub fn extract_text(raw: &[u8], ext: &str) -> Result<String> {
+    let messages: Vec<String> = if ext == "eml" {
+        vec![message_text(raw)]
+    } else {
+        split_mbox(raw).iter().map(|m| message_text(m)).collect()
+    };
+    if messages.is_empty() {
+        return Err(anyhow!("No messages found"));
+    }
+    Ok(messages.join(&format!("\n{}", MESSAGE_BREAK)))
+}
+
+fn split_mbox(raw: &[u8]) -> Vec<Vec<u8>> {
+    let mut messages: Vec<Vec<u8>> = Vec::new();
+    let mut current: Option<Vec<u8>> = None;
+    let mut previous_blank = true;
+    for line in raw.split_inclusive(|&b| b == b'\n') {
+        if previous_blank && line.starts_with(b"From ") {
+            messages.extend(current.take());
+            current = Some(Vec::new());
+        } else if let Some(message) = current.as_mut() {
+            let unquoted = line.strip_prefix(b">").filter(|l| {
+                let l = l.iter().position(|&b| b != b'>').map(|i| &l[i..]).unwrap_or(l);
+                l.starts_with(b"From ")
+            });
+            message.extend_from_slice(unquoted.unwrap_or(line));
+        }
+        previous_blank = line.iter().all(|b| b.is_ascii_whitespace());
+    }
+    messages.extend(current);
+    messages.retain(|m| !m.iter().all(|b| b.is_ascii_whitespace()));
+    messages
+}
+
+struct Part {
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Part {
+    fn parse(raw: &[u8]) -> Part {
+        let (head, body) = split_head(raw);
+        Part {
+            headers: parse_headers(&String::from_utf8_lossy(head)),
+            body: body.to_vec(),
+        }
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn content_type(&self) -> (String, Vec<(String, String)>) {
+        let (value, params) = split_params(self.header("content-type").unwrap_or("text/plain"));
+        (value.to_lowercase(), params)
+    }
+
+    fn decoded_body(&self) -> Vec<u8> {
+        let encoding = self
+            .header("content-transfer-encoding")
+            .unwrap_or("")
+            .trim()
+            .to_lowercase();
+        match encoding.as_str() {
+            "base64" => {
+                let compact: Vec<u8> = self
+                    .body
+                    .iter()
+                    .copied()
+                    .filter(|b| !b.is_ascii_whitespace())
+                    .collect();
+                BASE64.decode(compact).unwrap_or_default()
+            }
+            "quoted-printable" => decode_quoted_printable(&self.body, false),
+            _ => self.body.clone(),
+        }
+    }
+
+    fn filename(&self) -> Option<String> {
+        let from_disposition = self
+            .header("content-disposition")
+            .and_then(|d| param(&split_params(d).1, "filename"));
+        from_disposition
+            .or_else(|| param(&self.content_type().1, "name"))
+            .map(|name| decode_words(&name))
+    }
+
+    fn is_attachment(&self) -> bool {
+        self.header("content-disposition")
+            .is_some_and(|d| d.trim_start().to_lowercase().starts_with("attachment"))
+    }
+}
+
+fn split_head(raw: &[u8]) -> (&[u8], &[u8]) {
+    let mut offset = 0;
+    for line in raw.split_inclusive(|&b| b == b'\n') {
+        if line == b"\n" || line == b"\r\n" {
+            return (&raw[..offset], &raw[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (raw, &[])
+}
+
+fn parse_headers(head: &str) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in head.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    for (_, value) in headers.iter_mut() {
+        *value = decode_words(value);
+    }
+    headers
+}
+
+fn split_params(value: &str) -> (&str, Vec<(String, String)>) {
+    let mut pieces = value.split(';');
+    let main = pieces.next().unwrap_or("").trim();
+    let params = pieces
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().trim_matches('"').to_string()))
+        .collect();
+    (main, params)
+}
+
+fn param(params: &[(String, String)], name: &str) -> Option<String> {
+    params
+        .iter()
+        .find(|(k, _)| k == name || k.strip_suffix('*') == Some(name))
+        .map(|(_, v)| v.clone())
+}
+
+fn decode_charset(bytes: &[u8], charset: Option<&str>) -> String {
+    let encoding = charset
+        .and_then(|c| Encoding::for_label(c.trim().as_bytes()))
+        .unwrap_or(if std::str::from_utf8(bytes).is_ok() { UTF_8 } else { WINDOWS_1252 });
+    encoding.decode(bytes).0.into_owned()
+}
+
+fn decode_quoted_printable(input: &[u8], header: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        match input[i] {
+            b'=' => {
+                let rest = &input[i + 1..];
+                if rest.starts_with(b"\r\n") {
+                    i += 3;
+                } else if rest.starts_with(b"\n") {
+                    i += 2;
+                } else if let Some(byte) = rest
+                    .get(..2)
+                    .and_then(|h| std::str::from_utf8(h).ok())
+                    .and_then(|h| u8::from_str_radix(h, 16).ok())
+                {
+                    out.push(byte);
+                    i += 3;
+                } else {
+                    out.push(b'=');
+                    i += 1;
+                }
+            }
+            b'_' if header => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+fn decode_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let decoded = rest[start + 2..].split_once('?').and_then(|(charset, tail)| {
+            let (mode, tail) = tail.split_once('?')?;
+            let end = tail.find("?=")?;
+            let text = &tail[..end];
+            let bytes = match mode {
+                "B" | "b" => BASE64.decode(text).ok()?,
+                "Q" | "q" => decode_quoted_printable(text.as_bytes(), true),
+                _ => return None,
+            };
+            let consumed = start + 2 + charset.len() + 1 + mode.len() + 1 + end + 2;
+            Some((decode_charset(&bytes, Some(charset)), consumed))
+        });
+        match decoded {
+            Some((text, consumed)) => {
+                let between = &rest[..start];
+                if !(after_word && between.trim().is_empty()) {
+                    out.push_str(between);
+                }
+                out.push_str(&text);
+                rest = &rest[consumed..];
+                after_word = true;
+            }
+            None => {
+                out.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+                after_word = false;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[derive(Default)]
+struct Content {
+    text: Vec<String>,
+    attachments: Vec<String>,
+}
+
+fn collect(part: &Part, content: &mut Content, depth: usize) {
+    let (mime, params) = part.content_type();
+    if depth > MAX_DEPTH {
+        return;
+    }
+
+    if mime.starts_with("multipart/") {
+        let Some(boundary) = param(&params, "boundary") else {
+            return;
+        };
+        let parts = split_multipart(&part.body, &boundary);
+        if mime == "multipart/alternative" {
+            let preferred = parts
+                .iter()
+                .find(|p| p.content_type().0 == "text/plain")
+                .or_else(|| parts.last());
+            if let Some(p) = preferred {
+                collect(p, content, depth + 1);
+            }
+        } else {
+            for p in &parts {
+                collect(p, content, depth + 1);
+            }
+        }
+        return;
+    }
+
+    let filename = part.filename();
+    let inline_text = matches!(mime.as_str(), "text/plain" | "text/html" | "text/markdown");
+    if part.is_attachment() || filename.is_some() || !inline_text {
+        let body = part.decoded_body();
+        let label = if mime == "message/rfc822" {
+            let inner = Part::parse(&body);
+            let subject = inner.header("subject").unwrap_or("no subject").to_string();
+            format!("{} (forwarded message: {})", filename.unwrap_or_default(), subject)
+        } else {
+            format!(
+                "{} ({}, {})",
+                filename.unwrap_or_else(|| "unnamed".to_string()),
+                mime,
+                format_size(body.len())
+            )
+        };
+        content.attachments.push(label.trim().to_string());
+        return;
+    }
+
+    let text = decode_charset(&part.decoded_body(), param(&params, "charset").as_deref());
+    let text = if mime == "text/html" {
+        super::markup::html_text(&text)
+    } else {
+        text.replace("\r\n", "\n")
+    };
+    if !text.trim().is_empty() {
+        content.text.push(text.trim().to_string());
+    }
+}
+
+fn split_multipart(body: &[u8], boundary: &str) -> Vec<Part> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts: Vec<Part> = Vec::new();
+    let mut current: Option<Vec<u8>> = None;
+    for line in body.split_inclusive(|&b| b == b'\n') {
+        let trimmed = line.trim_ascii_end();
+        if trimmed.starts_with(delimiter.as_bytes()) {
+            if let Some(mut raw) = current.take() {
+                if raw.ends_with(b"\n") {
+                    raw.pop();
+                    if raw.ends_with(b"\r") {
+                        raw.pop();
+                    }
+                }
+                parts.push(Part::parse(&raw));
+            }
+            if trimmed[delimiter.len()..].starts_with(b"--") {
+                break;
+            }
+            current = Some(Vec::new());
+        } else if let Some(raw) = current.as_mut() {
+            raw.extend_from_slice(line);
+        }
+    }
+    parts
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{} KB", bytes / 1024)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+fn message_text(raw: &[u8]) -> String {
+    let message = Part::parse(raw);
+    let mut out = String::new();
+    for name in SHOWN_HEADERS {
+        if let Some(value) = message.header(name).filter(|v| !v.is_empty()) {
+            let mut label = name.to_string();
+            label[..1].make_ascii_uppercase();
+            out.push_str(&format!("{}: {}\n", label, value));
+        }
+    }
+
+    let mut content = Content::default();
+    collect(&message, &mut content, 0);
+    if !content.text.is_empty() {
+        out.push('\n');
+        out.push_str(&content.text.join("\n\n"));
+        out.push('\n');
+    }
+    if !content.attachments.is_empty() {
+        out.push_str("\nAttachments: ");
+        out.push_str(&content.attachments.join(", "));
+        out.push('\n');
+    }
+    out
+}
+
+pub fn message_starts(text: &str) -> Vec<usize> {
+    let mut starts = vec![0];
+    starts.extend(text.match_indices(MESSAGE_BREAK).map(|(i, c)| i + c.len()));
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUOTE: &str = "From: =?UTF-8?B?SsO8cmdlbg==?= <j@vendor.example>\r\n\
+To: team@example.com\r\n\
+Subject: =?ISO-8859-1?Q?Price_quote_for_r=E9sum=E9?=\r\n \
+ tooling\r\n\
+Date: Tue, 7 Jan 2025 09:12:00 +0100\r\n\
+MIME-Version: 1.0\r\n\
+Content-Type: multipart/mixed; boundary=\"outer\"\r\n\
+\r\n\
+--outer\r\n\
+Content-Type: multipart/alternative; boundary=\"alt\"\r\n\
+\r\n\
+--alt\r\n\
+Content-Type: text/plain; charset=utf-8\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+\r\n\
+The price is 4.200 =E2=82=AC per seat, valid for a long =\r\n\
+time.\r\n\
+--alt\r\n\
+Content-Type: text/html\r\n\
+\r\n\
+<p>The price is <b>4.200</b></p>\r\n\
+--alt--\r\n\
+--outer\r\n\
+Content-Type: application/pdf; name=\"quote.pdf\"\r\n\
+Content-Disposition: attachment; filename=\"quote.pdf\"\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+JVBERi0xLjQK\r\n\
+--outer--\r\n";
+
+    #[test]
+    fn test_eml_headers_body_and_attachments() {
+        let text = extract_text(QUOTE.as_bytes(), "eml").unwrap();
+        assert!(text.starts_with("Subject: Price quote for résumé tooling\nFrom: Jürgen <j@vendor.example>\nTo: team@example.com\nDate: Tue, 7 Jan 2025"));
+        assert!(text.contains("The price is 4.200 € per seat, valid for a long time."));
+        assert!(!text.contains("<b>"));
+        assert!(text.contains("Attachments: quote.pdf (application/pdf, 9 B)"));
+    }
+
+    #[test]
+    fn test_mbox_splits_messages() {
+        let mbox = "From alice@example.com Mon Jan  6 10:00:00 2025\n\
+From: alice@example.com\n\
+Subject: First\n\
+\n\
+Hello there.\n\
+>From the archive.\n\
+\n\
+From bob@example.com Mon Jan  6 11:00:00 2025\n\
+From: bob@example.com\n\
+Subject: Second\n\
+Content-Type: text/html; charset=windows-1252\n\
+\n\
+<p>Caf\u{e9} &amp; more</p>\n";
+        let text = extract_text(&mbox_bytes(mbox), "mbox").unwrap();
+        let starts = message_starts(&text);
+        assert_eq!(starts.len(), 2);
+        let first = &text[..starts[1]];
+        assert!(first.starts_with("Subject: First\nFrom: alice@example.com\n\nHello there.\nFrom the archive."));
+        assert!(text[starts[1]..].contains("Subject: Second"));
+        assert!(text[starts[1]..].contains("Café & more"));
+    }
+
+    fn mbox_bytes(text: &str) -> Vec<u8> {
+        text.chars().map(|c| c as u32 as u8).collect()
+    }
+}
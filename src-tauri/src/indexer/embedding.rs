@@ -5,6 +5,9 @@ use log::{debug, warn};
 use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
 use fastembed::{RerankInitOptions, RerankResult, RerankerModel, TextRerank};
 
+use super::chunking::TokenBudget;
+use super::search::SearchHit;
+
 const QUERY_PREFIX: &str = "query: ";
 const PASSAGE_PREFIX: &str = "passage: ";
 
@@ -55,17 +58,29 @@ pub fn get_model_dimension(model: &mut TextEmbedding) -> Result<usize> {
         .ok_or_else(|| anyhow!("No vector returned from dimension probe"))
 }
 
+pub fn token_budget(model: &TextEmbedding) -> Option<TokenBudget> {
+    let max_tokens = model.tokenizer.get_truncation()?.max_length;
+    let mut tokenizer = model.tokenizer.clone();
+    tokenizer.with_truncation(None).ok()?;
+    tokenizer.with_padding(None);
+    Some(TokenBudget {
+        count: std::sync::Arc::new(move |text: &str| {
+            tokenizer.encode(text, true).map(|e| e.len()).unwrap_or(0)
+        }),
+        max_tokens,
+    })
+}
 
 pub fn rerank_results(
     reranker: &mut TextRerank,
     query: &str,
-    results: &[(String, String, f32)],
-) -> Result<Vec<(String, String, f32)>> {
+    results: &[SearchHit],
+) -> Result<Vec<SearchHit>> {
     if results.is_empty() {
         return Ok(vec![]);
     }
 
-    let doc_refs: Vec<&str> = results.iter().map(|(_, snippet, _)| snippet.as_str()).collect();
+    let doc_refs: Vec<&str> = results.iter().map(|(_, snippet, _, _)| snippet.as_str()).collect();
     let reranked = reranker
         .rerank(query, &doc_refs, false, None)
         .map_err(|e| anyhow!("Reranking failed: {}", e))?;
@@ -73,8 +88,8 @@ pub fn rerank_results(
     Ok(reranked
         .into_iter()
         .map(|RerankResult { index, score, .. }| {
-            let (path, snippet, _) = &results[index];
-            (path.clone(), snippet.clone(), score)
+            let (path, snippet, _, meta) = &results[index];
+            (path.clone(), snippet.clone(), score, meta.clone())
         })
         .collect())
 }
@@ -82,8 +97,8 @@ pub fn rerank_results(
 pub async fn safe_rerank(
     reranker: fastembed::TextRerank,
     query: String,
-    input: Vec<(String, String, f32)>,
-) -> (Option<fastembed::TextRerank>, Vec<(String, String, f32)>, bool) {
+    input: Vec<SearchHit>,
+) -> (Option<fastembed::TextRerank>, Vec<SearchHit>, bool) {
     let fallback = input.clone();
     match tokio::task::spawn_blocking(move || {
         let mut r = reranker;
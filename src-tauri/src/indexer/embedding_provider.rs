@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use super::chunking::TokenBudget;
 use super::embedding;
 use crate::state::ModelState;
 
@@ -13,6 +14,7 @@ pub trait EmbeddingProvider: Send + Sync {
     async fn embed_passages(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>>;
     async fn embed_query(&self, query: &str) -> Result<Vec<f32>>;
     async fn get_dimension(&self) -> Result<usize>;
+    async fn token_budget(&self) -> Option<TokenBudget>;
     fn provider_id(&self) -> String;
 }
 
@@ -54,6 +56,11 @@ impl EmbeddingProvider for LocalProvider {
         Ok(dim)
     }
 
+    async fn token_budget(&self) -> Option<TokenBudget> {
+        let guard = self.model_state.lock().await;
+        embedding::token_budget(guard.model.as_ref()?)
+    }
+
     fn provider_id(&self) -> String {
         let guard = self.model_state.blocking_lock();
         format!(
@@ -73,6 +80,8 @@ pub struct RemoteProviderConfig {
     pub api_key: Option<String>,
     pub model: String,
     pub dimensions: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
 }
 
 pub struct RemoteProvider {
@@ -169,6 +178,10 @@ impl EmbeddingProvider for RemoteProvider {
         Ok(self.config.dimensions)
     }
 
+    async fn token_budget(&self) -> Option<TokenBudget> {
+        self.config.max_tokens.map(TokenBudget::estimated)
+    }
+
     fn provider_id(&self) -> String {
         format!("remote:{}:{}", self.config.endpoint, self.config.model)
     }
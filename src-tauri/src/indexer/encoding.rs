@@ -0,0 +1,83 @@
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+const SNIFF_BYTES: usize = 4096;
+
+fn utf16_without_bom(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(SNIFF_BYTES) & !1];
+    let units = sample.len() / 2;
+    if units < 2 {
+        return None;
+    }
+    let high_zeros = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+    let low_zeros = sample.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+    let mostly = |zeros: usize| zeros * 10 >= units * 3;
+    let rarely = |zeros: usize| zeros * 20 < units;
+    if mostly(low_zeros) && rarely(high_zeros) {
+        Some(UTF_16LE)
+    } else if mostly(high_zeros) && rarely(low_zeros) {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+pub fn detect(bytes: &[u8]) -> Option<(&'static Encoding, usize)> {
+    if let Some(bom) = Encoding::for_bom(bytes) {
+        return Some(bom);
+    }
+    if let Some(encoding) = utf16_without_bom(bytes) {
+        return Some((encoding, 0));
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => return Some((UTF_8, 0)),
+        Err(e) if e.error_len().is_none() => return Some((UTF_8, 0)),
+        Err(_) => {}
+    }
+    if bytes.contains(&0) {
+        return None;
+    }
+
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    Some((detector.guess(None, true), 0))
+}
+
+pub fn decode_text(bytes: &[u8]) -> Option<(String, &'static str)> {
+    let (encoding, bom_len) = detect(bytes)?;
+    let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+    Some((text.into_owned(), encoding.name()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_boms_and_utf16() {
+        assert_eq!(decode_text(b"plain ascii"), Some(("plain ascii".to_string(), "UTF-8")));
+        assert_eq!(decode_text(b"\xef\xbb\xbfcaf\xc3\xa9"), Some(("café".to_string(), "UTF-8")));
+
+        let le: Vec<u8> = "key=värde\n".encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+        let with_bom = [&[0xff, 0xfe][..], &le].concat();
+        assert_eq!(decode_text(&with_bom), Some(("key=värde\n".to_string(), "UTF-16LE")));
+        assert_eq!(decode_text(&le), Some(("key=värde\n".to_string(), "UTF-16LE")));
+
+        let be: Vec<u8> = "subtitle 1".encode_utf16().flat_map(|u| u.to_be_bytes()).collect();
+        assert_eq!(decode_text(&be), Some(("subtitle 1".to_string(), "UTF-16BE")));
+    }
+
+    #[test]
+    fn test_decode_legacy_encodings() {
+        let (text, encoding) = decode_text(b"Caf\xe9 cr\xe8me br\xfbl\xe9e \x96 d\xe9j\xe0 vu").unwrap();
+        assert_eq!(text, "Café crème brûlée – déjà vu");
+        assert_eq!(encoding, "windows-1252");
+
+        let (sjis, _, _) = encoding_rs::SHIFT_JIS.encode("設定ファイルの説明です。日本語のテキスト。");
+        let (text, encoding) = decode_text(&sjis).unwrap();
+        assert_eq!(text, "設定ファイルの説明です。日本語のテキスト。");
+        assert_eq!(encoding, "Shift_JIS");
+
+        assert_eq!(decode_text(b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR\xff"), None);
+    }
+}
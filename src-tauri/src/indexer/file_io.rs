@@ -1,4 +1,5 @@
 use std::fs;
+use std::io::Read;
 use std::path::Path;
 use std::time::UNIX_EPOCH;
 
@@ -89,8 +90,11 @@ pub fn is_text_extension(ext: &str) -> bool {
             | "rkt"
             | "html"
             | "htm"
+            | "xhtml"
             | "xml"
             | "svg"
+            | "rss"
+            | "atom"
             | "css"
             | "scss"
             | "sass"
@@ -146,76 +150,206 @@ pub fn is_text_extension_with_config(ext: &str, config: &IndexingConfig) -> bool
     config.extra_extensions.iter().any(|e| e == ext)
 }
 
-const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+const MB: u64 = 1024 * 1024;
 
-pub fn read_file_content(path: &Path) -> Option<String> {
-    if let Ok(meta) = fs::metadata(path) {
-        if meta.len() > MAX_FILE_SIZE {
-            return None;
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StreamLimits {
+    pub threshold: u64,
+    pub segment_bytes: usize,
+}
+
+impl Default for StreamLimits {
+    fn default() -> Self {
+        Self {
+            threshold: 10 * MB,
+            segment_bytes: 4 * MB as usize,
         }
     }
+}
 
-    let ext = path
+pub const PAGE_BREAK: char = '\u{c}';
+
+fn pdf_text(bytes: &[u8]) -> Option<String> {
+    let pages: Vec<String> = pdf_extract::extract_text_from_mem_by_pages(bytes)
+        .ok()?
+        .into_iter()
+        .map(|page| page.replace(PAGE_BREAK, ""))
+        .collect();
+    Some(pages.join(&PAGE_BREAK.to_string()))
+}
+
+fn is_dotfile(file_name: &str) -> bool {
+    matches!(
+        file_name,
+        "dockerfile" | "makefile" | ".gitignore" | ".env" | ".editorconfig"
+    )
+}
+
+fn lowercase_extension(file_name: &str) -> String {
+    Path::new(file_name)
         .extension()
         .and_then(|s| s.to_str())
         .unwrap_or("")
-        .to_lowercase();
+        .to_lowercase()
+}
 
-    let file_name = path
-        .file_name()
-        .and_then(|s| s.to_str())
-        .unwrap_or("")
-        .to_lowercase();
+pub fn is_extractable(file_name: &str, config: &IndexingConfig) -> bool {
+    let file_name = file_name.to_lowercase();
+    let ext = lowercase_extension(&file_name);
+    if config.excluded_extensions.iter().any(|e| e == &ext) {
+        return false;
+    }
+    is_text_extension_with_config(&ext, config)
+        || is_dotfile(&file_name)
+        || ext == "pdf"
+        || ext == "ipynb"
+        || super::email::is_email_extension(&ext)
+        || super::office::is_office_extension(&ext)
+}
 
-    let is_dotfile = matches!(
-        file_name.as_str(),
-        "dockerfile" | "makefile" | ".gitignore" | ".env" | ".editorconfig"
-    );
+pub struct ExtractedText {
+    pub text: String,
+    pub encoding: Option<&'static str>,
+}
+
+impl ExtractedText {
+    fn binary(text: String) -> Self {
+        Self { text, encoding: None }
+    }
+}
+
+pub fn extract_document(file_name: &str, bytes: &[u8], config: &IndexingConfig) -> Option<ExtractedText> {
+    if !is_extractable(file_name, config) {
+        return None;
+    }
+    let ext = lowercase_extension(file_name);
 
-    if is_text_extension(&ext) || is_dotfile {
-        fs::read_to_string(path).ok()
-    } else if ext == "pdf" {
-        pdf_extract::extract_text(path).ok()
+    if ext == "pdf" {
+        pdf_text(bytes).map(ExtractedText::binary)
+    } else if super::email::is_email_extension(&ext) {
+        super::email::extract_text(bytes, &ext).ok().map(ExtractedText::binary)
+    } else if ext == "ipynb" {
+        let json = std::str::from_utf8(bytes).ok()?;
+        super::notebook::extract_text(json, config.notebook_outputs)
+            .ok()
+            .map(ExtractedText::binary)
+    } else if super::office::is_office_extension(&ext) {
+        super::office::extract_text(bytes, &ext).ok().map(ExtractedText::binary)
     } else {
-        None
+        let (text, encoding) = super::encoding::decode_text(bytes)?;
+        let text = if super::markup::is_markup_extension(&ext) {
+            super::markup::extract_text(&text, &ext)
+        } else {
+            text
+        };
+        Some(ExtractedText {
+            text,
+            encoding: Some(encoding),
+        })
     }
 }
 
+pub fn extract_content(file_name: &str, bytes: &[u8], config: &IndexingConfig) -> Option<String> {
+    extract_document(file_name, bytes, config).map(|doc| doc.text)
+}
+
+pub fn read_file_content(path: &Path) -> Option<String> {
+    read_file_content_with_config(path, &IndexingConfig::default())
+}
+
 pub fn read_file_content_with_config(path: &Path, config: &IndexingConfig) -> Option<String> {
-    if let Ok(meta) = fs::metadata(path) {
-        if meta.len() > MAX_FILE_SIZE {
-            return None;
-        }
+    read_document_with_config(path, config).map(|doc| doc.text)
+}
+
+pub fn read_document_with_config(path: &Path, config: &IndexingConfig) -> Option<ExtractedText> {
+    let file_name = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
+    if !is_extractable(file_name, config) || oversize_reason(path, config).is_some() {
+        return None;
     }
+    extract_document(file_name, &fs::read(path).ok()?, config)
+}
 
-    let ext = path
-        .extension()
-        .and_then(|s| s.to_str())
-        .unwrap_or("")
-        .to_lowercase();
+pub fn max_file_bytes(config: &IndexingConfig) -> u64 {
+    config.max_file_size_mb * MB
+}
 
+pub fn oversize_reason(path: &Path, config: &IndexingConfig) -> Option<String> {
+    let file_name = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
+    if !is_extractable(file_name, config) {
+        return None;
+    }
+    let size = fs::metadata(path).ok()?.len();
+    (size > max_file_bytes(config)).then(|| {
+        format!(
+            "file is {} MB, over the {} MB max_file_size_mb limit",
+            size.div_ceil(MB),
+            config.max_file_size_mb
+        )
+    })
+}
+
+pub fn is_plain_text(path: &Path, config: &IndexingConfig) -> bool {
     let file_name = path
         .file_name()
         .and_then(|s| s.to_str())
         .unwrap_or("")
         .to_lowercase();
+    let ext = lowercase_extension(&file_name);
+    (is_text_extension_with_config(&ext, config) || is_dotfile(&file_name))
+        && !super::markup::is_markup_extension(&ext)
+}
 
-    let is_dotfile = matches!(
-        file_name.as_str(),
-        "dockerfile" | "makefile" | ".gitignore" | ".env" | ".editorconfig"
-    );
+pub fn is_streamable(path: &Path, config: &IndexingConfig, limits: &StreamLimits) -> bool {
+    let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    size > limits.threshold && is_plain_text(path, config) && !super::tabular::is_tabular_path(path)
+}
 
-    if config.excluded_extensions.iter().any(|e| e == &ext) {
-        return None;
+pub fn for_each_text_segment(
+    path: &Path,
+    segment_bytes: usize,
+    mut visit: impl FnMut(&str, &'static str),
+) -> Option<&'static str> {
+    let mut file = fs::File::open(path).ok()?;
+    let mut buf = vec![0u8; segment_bytes];
+    let mut filled = read_full(&mut file, &mut buf).ok()?;
+    let (encoding, bom_len) = super::encoding::detect(&buf[..filled])?;
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut pending = String::new();
+    let mut start = bom_len;
+
+    loop {
+        let last = filled < buf.len();
+        let input = &buf[start..filled];
+        pending.reserve(decoder.max_utf8_buffer_length(input.len()).unwrap_or(input.len() * 3));
+        let _ = decoder.decode_to_string(input, &mut pending, last);
+        if last {
+            break;
+        }
+        if let Some(cut) = pending.rfind('\n') {
+            visit(&pending[..=cut], encoding.name());
+            pending.drain(..=cut);
+        } else if pending.len() >= segment_bytes {
+            visit(&pending, encoding.name());
+            pending.clear();
+        }
+        filled = read_full(&mut file, &mut buf).ok()?;
+        start = 0;
+    }
+    if !pending.is_empty() {
+        visit(&pending, encoding.name());
     }
+    Some(encoding.name())
+}
 
-    if is_text_extension_with_config(&ext, config) || is_dotfile {
-        fs::read_to_string(path).ok()
-    } else if ext == "pdf" {
-        pdf_extract::extract_text(path).ok()
-    } else {
-        None
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
     }
+    Ok(filled)
 }
 
 pub async fn read_file_content_with_ocr(path: &Path) -> Option<String> {
@@ -232,6 +366,76 @@ pub async fn read_file_content_with_ocr(path: &Path) -> Option<String> {
     }
 }
 
+pub fn hash_file(path: &Path) -> Option<String> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(fs::File::open(path).ok()?).ok()?;
+    Some(hasher.finalize().to_hex().to_string())
+}
+
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    blake3::hash(bytes).to_hex().to_string()
+}
+
+pub fn hash_text(text: &str) -> String {
+    hash_bytes(text.as_bytes())
+}
+
+pub fn detect_language(path: &Path) -> String {
+    let file_name = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let ext = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    let language = match (file_name.as_str(), ext.as_str()) {
+        ("dockerfile", _) => "dockerfile",
+        ("makefile", _) => "makefile",
+        (_, "rs") => "rust",
+        (_, "py" | "pyi" | "pyw") => "python",
+        (_, "js" | "mjs" | "cjs" | "jsx") => "javascript",
+        (_, "ts" | "mts" | "cts" | "tsx") => "typescript",
+        (_, "go") => "go",
+        (_, "java") => "java",
+        (_, "kt" | "kts") => "kotlin",
+        (_, "scala" | "sc") => "scala",
+        (_, "c" | "h") => "c",
+        (_, "cpp" | "cc" | "cxx" | "hpp" | "hxx" | "hh") => "cpp",
+        (_, "cs") => "csharp",
+        (_, "fs" | "fsi" | "fsx") => "fsharp",
+        (_, "rb" | "erb") => "ruby",
+        (_, "swift") => "swift",
+        (_, "m" | "mm") => "objective-c",
+        (_, "php") => "php",
+        (_, "pl" | "pm") => "perl",
+        (_, "ex" | "exs") => "elixir",
+        (_, "erl" | "hrl") => "erlang",
+        (_, "hs" | "lhs") => "haskell",
+        (_, "ml" | "mli") => "ocaml",
+        (_, "clj" | "cljs" | "cljc") => "clojure",
+        (_, "sh" | "bash" | "zsh" | "fish") => "shell",
+        (_, "ps1") => "powershell",
+        (_, "md" | "markdown") => "markdown",
+        (_, "html" | "htm" | "xhtml") => "html",
+        (_, "rss" | "atom") => "xml",
+        (_, "yaml" | "yml") => "yaml",
+        (_, "json" | "jsonc" | "json5") => "json",
+        (_, "txt") => "text",
+        (_, "docx" | "odt") => "document",
+        (_, "xlsx" | "xlsm" | "xls" | "ods") => "spreadsheet",
+        (_, "pptx" | "odp") => "presentation",
+        (_, "ipynb") => "notebook",
+        (_, "eml" | "mbox" | "mbx") => "email",
+        (_, "tf" | "tfvars" | "hcl") => "hcl",
+        (_, other) => other,
+    };
+    language.to_string()
+}
+
 pub fn get_file_mtime(path: &Path) -> i64 {
     fs::metadata(path)
         .and_then(|m| m.modified())
@@ -254,4 +458,71 @@ mod tests {
         assert!(!is_text_extension("exe"));
         assert!(!is_text_extension("png"));
     }
+
+    #[test]
+    fn test_hash_file_matches_hash_text() {
+        let path = std::env::temp_dir().join("rememex_hash_file_test.txt");
+        fs::write(&path, "hello rememex").unwrap();
+        assert_eq!(hash_file(&path), Some(hash_text("hello rememex")));
+        assert_ne!(hash_text("hello rememex"), hash_text("hello rememex!"));
+        assert_eq!(hash_file(&path.with_extension("missing")), None);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_read_legacy_encoded_file() {
+        let path = std::env::temp_dir().join("rememex_latin1_test.srt");
+        fs::write(&path, b"1\n00:00:01,000 --> 00:00:02,000\n\xbfC\xf3mo est\xe1, se\xf1or? La canci\xf3n termin\xf3.\n").unwrap();
+        let config = IndexingConfig {
+            extra_extensions: vec!["srt".to_string()],
+            ..Default::default()
+        };
+        let doc = read_document_with_config(&path, &config).unwrap();
+        assert!(doc.text.ends_with("¿Cómo está, señor? La canción terminó.\n"));
+        assert_eq!(doc.encoding, Some("windows-1252"));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_stream_segments_split_on_lines() {
+        let path = std::env::temp_dir().join("rememex_stream_test.log");
+        let line = "2024-05-01 12:00:00 INFO request served in 12ms\n";
+        let limits = StreamLimits {
+            threshold: MB,
+            segment_bytes: MB as usize / 2,
+        };
+        let count = limits.segment_bytes * 3 / line.len() + 10;
+        fs::write(&path, line.repeat(count)).unwrap();
+
+        let mut segments = Vec::new();
+        let encoding = for_each_text_segment(&path, limits.segment_bytes, |segment, _| {
+            segments.push(segment.to_string())
+        });
+        assert_eq!(encoding, Some("UTF-8"));
+        assert_eq!(segments.len(), 4);
+        assert!(segments.iter().all(|s| s.ends_with('\n') && s.starts_with("2024-")));
+        assert_eq!(segments.concat().len(), line.len() * count);
+
+        let config = IndexingConfig {
+            max_file_size_mb: 1,
+            ..Default::default()
+        };
+        assert!(is_streamable(&path, &config, &limits));
+        assert!(!is_streamable(&path, &config, &StreamLimits::default()));
+        assert_eq!(
+            oversize_reason(&path, &config).as_deref(),
+            Some("file is 2 MB, over the 1 MB max_file_size_mb limit")
+        );
+        assert!(read_document_with_config(&path, &config).is_none());
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(detect_language(Path::new("src/main.rs")), "rust");
+        assert_eq!(detect_language(Path::new("App.TSX")), "typescript");
+        assert_eq!(detect_language(Path::new("build/Dockerfile")), "dockerfile");
+        assert_eq!(detect_language(Path::new("notes.org")), "org");
+        assert_eq!(detect_language(Path::new("LICENSE")), "");
+    }
 }
@@ -0,0 +1,239 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+const SEPARATOR: &str = " > ";
+
+static MARKDOWN_ATX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^ {0,3}(#{1,6})[ \t]+(.*?)(?:[ \t]+#+)?[ \t]*$").unwrap());
+static ASCIIDOC: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(={1,6})[ \t]+(\S.*?)[ \t]*$").unwrap());
+static LATEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*\\(part|chapter|section|subsection|subsubsection|paragraph)\*?\s*(?:\[[^\]]*\])?\{([^}]*)\}").unwrap()
+});
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heading {
+    pub offset: usize,
+    pub level: usize,
+    pub title: String,
+}
+
+pub fn is_heading_extension(ext: &str) -> bool {
+    matches!(ext, "md" | "markdown" | "rst" | "adoc" | "tex")
+}
+
+fn lines(text: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+    text.split_inclusive('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len();
+            (start, line.trim_end_matches(['\n', '\r']))
+        })
+        .collect()
+}
+
+fn underline(line: &str, chars: &str) -> Option<char> {
+    let line = line.trim_end();
+    let first = line.chars().next().filter(|c| chars.contains(*c))?;
+    (line.chars().all(|c| c == first)).then_some(first)
+}
+
+fn markdown(text: &str) -> Vec<Heading> {
+    let lines = lines(text);
+    let mut headings = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut skip_to = 0;
+    if lines.first().is_some_and(|(_, l)| *l == "---") {
+        skip_to = lines.iter().skip(1).position(|(_, l)| *l == "---").map_or(0, |i| i + 2);
+    }
+
+    for (i, &(offset, line)) in lines.iter().enumerate().skip(skip_to) {
+        let trimmed = line.trim_start();
+        if let Some(open) = fence {
+            if trimmed.starts_with(open) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+            continue;
+        }
+        if let Some(caps) = MARKDOWN_ATX.captures(line) {
+            headings.push(Heading {
+                offset,
+                level: caps[1].len(),
+                title: caps[2].trim().to_string(),
+            });
+            continue;
+        }
+        let Some(&(_, next)) = lines.get(i + 1) else {
+            continue;
+        };
+        let level = match underline(next, "=-") {
+            Some('=') => 1,
+            Some(_) if next.trim().len() >= 2 => 2,
+            _ => continue,
+        };
+        if !trimmed.is_empty() && !line.starts_with("    ") && !trimmed.starts_with(['-', '*', '>', '|']) {
+            headings.push(Heading {
+                offset,
+                level,
+                title: trimmed.trim_end().to_string(),
+            });
+        }
+    }
+    headings
+}
+
+fn rst(text: &str) -> Vec<Heading> {
+    let lines = lines(text);
+    let mut styles: Vec<(char, bool)> = Vec::new();
+    let mut headings = Vec::new();
+    let adornment = "=-~^\"'`#*+.:_";
+
+    let mut i = 0;
+    while i + 1 < lines.len() {
+        let (offset, line) = lines[i];
+        let title = line.trim();
+        let overline = underline(line, adornment).filter(|_| i + 2 < lines.len());
+        let (title_index, over) = match overline {
+            Some(c) if underline(lines[i + 2].1, adornment) == Some(c) && !lines[i + 1].1.trim().is_empty() => {
+                (i + 1, true)
+            }
+            _ => (i, false),
+        };
+        let title_line = if over { lines[title_index].1.trim() } else { title };
+        let under = lines.get(title_index + 1).and_then(|(_, l)| underline(l, adornment));
+        let valid = !title_line.is_empty()
+            && underline(title_line, adornment).is_none()
+            && under.is_some_and(|_| lines[title_index + 1].1.trim().len() >= title_line.chars().count().min(3));
+        if let (true, Some(c)) = (valid, under) {
+            let style = (c, over);
+            let level = match styles.iter().position(|s| *s == style) {
+                Some(p) => p + 1,
+                None => {
+                    styles.push(style);
+                    styles.len()
+                }
+            };
+            headings.push(Heading {
+                offset,
+                level,
+                title: title_line.to_string(),
+            });
+            i = title_index + 2;
+            continue;
+        }
+        i += 1;
+    }
+    headings
+}
+
+fn asciidoc(text: &str) -> Vec<Heading> {
+    let mut block: Option<&str> = None;
+    lines(text)
+        .into_iter()
+        .filter_map(|(offset, line)| {
+            if line == "----" || line == "...." || line == "++++" {
+                block = match block {
+                    Some(open) if open == line => None,
+                    None => Some(line),
+                    other => other,
+                };
+                return None;
+            }
+            if block.is_some() {
+                return None;
+            }
+            let caps = ASCIIDOC.captures(line)?;
+            Some(Heading {
+                offset,
+                level: caps[1].len(),
+                title: caps[2].to_string(),
+            })
+        })
+        .collect()
+}
+
+fn latex(text: &str) -> Vec<Heading> {
+    lines(text)
+        .into_iter()
+        .filter_map(|(offset, line)| {
+            let caps = LATEX.captures(line)?;
+            let level = match &caps[1] {
+                "part" => 1,
+                "chapter" => 2,
+                "section" => 3,
+                "subsection" => 4,
+                "subsubsection" => 5,
+                _ => 6,
+            };
+            Some(Heading {
+                offset,
+                level,
+                title: caps[2].trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+pub fn headings(text: &str, ext: &str) -> Vec<Heading> {
+    match ext {
+        "md" | "markdown" => markdown(text),
+        "rst" => rst(text),
+        "adoc" => asciidoc(text),
+        "tex" => latex(text),
+        _ => Vec::new(),
+    }
+}
+
+pub fn breadcrumb_at(headings: &[Heading], offset: usize) -> Option<String> {
+    let mut trail: Vec<&Heading> = Vec::new();
+    for heading in headings.iter().take_while(|h| h.offset <= offset) {
+        while trail.last().is_some_and(|t| t.level >= heading.level) {
+            trail.pop();
+        }
+        trail.push(heading);
+    }
+    (!trail.is_empty()).then(|| {
+        trail
+            .iter()
+            .map(|h| h.title.as_str())
+            .collect::<Vec<_>>()
+            .join(SEPARATOR)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trail(text: &str, ext: &str, needle: &str) -> Option<String> {
+        breadcrumb_at(&headings(text, ext), text.find(needle).unwrap())
+    }
+
+    #[test]
+    fn test_markdown_breadcrumbs() {
+        let md = "---\ntitle: Guide\n---\n# Setup\n\n## Install\n\n```sh\n# not a heading\n```\n\n### Linux\n\n#### Wayland\n\nSet the env var.\n\n### macOS\n\nUse brew.\n\nFAQ\n---\n\nAsk away.\n";
+        assert_eq!(trail(md, "md", "Set the env").as_deref(), Some("Setup > Install > Linux > Wayland"));
+        assert_eq!(trail(md, "md", "Use brew").as_deref(), Some("Setup > Install > macOS"));
+        assert_eq!(trail(md, "md", "Ask away").as_deref(), Some("Setup > FAQ"));
+        assert_eq!(trail(md, "md", "title: Guide"), None);
+    }
+
+    #[test]
+    fn test_rst_asciidoc_and_latex_breadcrumbs() {
+        let rst = "=====\nGuide\n=====\n\nInstall\n=======\n\nLinux\n-----\n\napt install rememex\n\nUsage\n=======\n\nRun it.\n";
+        assert_eq!(trail(rst, "rst", "apt install").as_deref(), Some("Guide > Install > Linux"));
+        assert_eq!(trail(rst, "rst", "Run it").as_deref(), Some("Guide > Usage"));
+
+        let adoc = "= Guide\n\n== Install\n\n----\n== not a heading\n----\n\n=== Linux\n\nUse the AppImage.\n";
+        assert_eq!(trail(adoc, "adoc", "Use the AppImage").as_deref(), Some("Guide > Install > Linux"));
+
+        let tex = "\\chapter{Results}\n\\section*{Accuracy}\nWe measured.\n\\subsection[short]{Recall at k}\nRecall rose.\n\\section{Latency}\nFast.\n";
+        assert_eq!(trail(tex, "tex", "Recall rose").as_deref(), Some("Results > Accuracy > Recall at k"));
+        assert_eq!(trail(tex, "tex", "Fast").as_deref(), Some("Results > Latency"));
+    }
+}
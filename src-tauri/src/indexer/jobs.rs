@@ -0,0 +1,442 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, Notify};
+
+const KEEP_FINISHED_JOBS: usize = 20;
+const CONTROL_EXTENSION: &str = "control";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Running,
+    Paused,
+    Completed,
+    Cancelled,
+    Failed,
+    Interrupted,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Cancelled | JobStatus::Failed)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobAction {
+    Cancel,
+    Pause,
+    Resume,
+}
+
+impl JobAction {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "cancel" => Some(JobAction::Cancel),
+            "pause" => Some(JobAction::Pause),
+            "resume" => Some(JobAction::Resume),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobAction::Cancel => "cancel",
+            JobAction::Pause => "pause",
+            JobAction::Resume => "resume",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JobInfo {
+    pub id: String,
+    pub container: String,
+    pub root_dir: String,
+    pub status: JobStatus,
+    pub processed: usize,
+    pub checkpoint: Option<String>,
+    pub files_indexed: usize,
+    #[serde(default)]
+    pub skipped: Vec<SkippedFile>,
+    pub error: Option<String>,
+    pub started_at: i64,
+    pub updated_at: i64,
+}
+
+pub struct Job {
+    file: PathBuf,
+    info: std::sync::Mutex<JobInfo>,
+    resume_from: Option<(usize, String)>,
+    cancelled: AtomicBool,
+    paused: AtomicBool,
+    wake: Notify,
+}
+
+impl Job {
+    fn new(file: PathBuf, info: JobInfo) -> Self {
+        let resume_from = match (&info.checkpoint, info.processed) {
+            (Some(path), processed) if processed > 0 => Some((processed, path.clone())),
+            _ => None,
+        };
+        Self {
+            file,
+            info: std::sync::Mutex::new(info),
+            resume_from,
+            cancelled: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+            wake: Notify::new(),
+        }
+    }
+
+    pub fn id(&self) -> String {
+        self.lock_info().id.clone()
+    }
+
+    pub fn info(&self) -> JobInfo {
+        self.lock_info().clone()
+    }
+
+    pub fn resume_from(&self) -> Option<(usize, String)> {
+        self.resume_from.clone()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+        self.paused.store(false, Ordering::Release);
+        self.wake.notify_waiters();
+    }
+
+    pub fn pause(&self) {
+        if self.is_cancelled() {
+            return;
+        }
+        self.paused.store(true, Ordering::Release);
+        self.update(|info| info.status = JobStatus::Paused);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Release);
+        self.wake.notify_waiters();
+        self.update(|info| {
+            if info.status == JobStatus::Paused {
+                info.status = JobStatus::Running;
+            }
+        });
+    }
+
+    pub async fn wait_while_paused(&self) {
+        while self.is_paused() && !self.is_cancelled() {
+            let notified = self.wake.notified();
+            if !self.is_paused() || self.is_cancelled() {
+                break;
+            }
+            notified.await;
+        }
+    }
+
+    pub fn checkpoint(&self, processed: usize, last_path: String) {
+        self.update(|info| {
+            info.processed = processed;
+            info.checkpoint = Some(last_path);
+        });
+    }
+
+    pub fn skip(&self, path: String, reason: String) {
+        warn!("Skipping {}: {}", path, reason);
+        self.update(|info| {
+            info.skipped.retain(|s| s.path != path);
+            info.skipped.push(SkippedFile { path, reason });
+        });
+    }
+
+    pub fn finish(&self, result: &Result<usize, String>) {
+        let cancelled = self.is_cancelled();
+        self.update(|info| match result {
+            Ok(count) => {
+                info.files_indexed += count;
+                info.status = if cancelled { JobStatus::Cancelled } else { JobStatus::Completed };
+            }
+            Err(e) => {
+                info.status = JobStatus::Failed;
+                info.error = Some(e.clone());
+            }
+        });
+    }
+
+    fn update<F: FnOnce(&mut JobInfo)>(&self, f: F) {
+        let mut info = self.lock_info();
+        f(&mut info);
+        info.updated_at = now_unix();
+        if let Err(e) = write_job_file(&self.file, &info) {
+            warn!("Failed to persist job {}: {}", info.id, e);
+        }
+    }
+
+    fn lock_info(&self) -> std::sync::MutexGuard<'_, JobInfo> {
+        self.info.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+pub struct JobManager {
+    dir: PathBuf,
+    jobs: Mutex<HashMap<String, Arc<Job>>>,
+    interrupted: std::sync::Mutex<Vec<String>>,
+}
+
+pub type JobState = Arc<JobManager>;
+
+impl JobManager {
+    pub fn new(dir: PathBuf) -> Self {
+        std::fs::create_dir_all(&dir).ok();
+        let mut persisted = read_job_files(&dir);
+        let mut interrupted = Vec::new();
+        for info in persisted.iter_mut() {
+            if matches!(info.status, JobStatus::Running | JobStatus::Paused) {
+                info.status = JobStatus::Interrupted;
+                let _ = write_job_file(&job_file(&dir, &info.id), info);
+                interrupted.push(info.id.clone());
+            }
+        }
+        let mut finished: Vec<&JobInfo> = persisted.iter().filter(|j| j.status.is_finished()).collect();
+        finished.sort_by_key(|j| std::cmp::Reverse(j.updated_at));
+        for info in finished.into_iter().skip(KEEP_FINISHED_JOBS) {
+            let _ = std::fs::remove_file(job_file(&dir, &info.id));
+        }
+        Self {
+            dir,
+            jobs: Mutex::new(HashMap::new()),
+            interrupted: std::sync::Mutex::new(interrupted),
+        }
+    }
+
+    pub fn take_interrupted(&self) -> Vec<String> {
+        std::mem::take(&mut *self.interrupted.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    pub async fn start(&self, container: &str, root_dir: &str) -> Arc<Job> {
+        let now = now_unix();
+        let info = JobInfo {
+            id: generate_id(),
+            container: container.to_string(),
+            root_dir: root_dir.to_string(),
+            status: JobStatus::Running,
+            processed: 0,
+            checkpoint: None,
+            files_indexed: 0,
+            skipped: Vec::new(),
+            error: None,
+            started_at: now,
+            updated_at: now,
+        };
+        self.register(info).await
+    }
+
+    pub async fn restart(&self, id: &str) -> Result<Arc<Job>, String> {
+        if self.jobs.lock().await.get(id).is_some_and(|j| !j.info().status.is_finished()) {
+            return Err(format!("Job {} is already running", id));
+        }
+        let mut info = read_job_file(&job_file(&self.dir, id))
+            .ok_or_else(|| format!("Job {} not found", id))?;
+        if info.status != JobStatus::Interrupted {
+            return Err(format!("Job {} is {:?} and cannot be resumed", id, info.status));
+        }
+        info.status = JobStatus::Running;
+        info.updated_at = now_unix();
+        Ok(self.register(info).await)
+    }
+
+    pub async fn get(&self, id: &str) -> Option<Arc<Job>> {
+        self.jobs.lock().await.get(id).cloned()
+    }
+
+    pub async fn list(&self) -> Vec<JobInfo> {
+        let live = self.jobs.lock().await;
+        let mut all: Vec<JobInfo> = read_job_files(&self.dir)
+            .into_iter()
+            .filter(|j| !live.contains_key(&j.id))
+            .collect();
+        all.extend(live.values().map(|j| j.info()));
+        all.sort_by_key(|j| std::cmp::Reverse(j.started_at));
+        all
+    }
+
+    pub fn take_control_requests(&self) -> Vec<(String, JobAction)> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(e) => e,
+            Err(_) => return Vec::new(),
+        };
+        let mut requests = Vec::new();
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(CONTROL_EXTENSION) {
+                continue;
+            }
+            let id = path.file_stem().and_then(|s| s.to_str()).unwrap_or("").to_string();
+            let action = std::fs::read_to_string(&path).ok().and_then(|s| JobAction::parse(&s));
+            let _ = std::fs::remove_file(&path);
+            match action {
+                Some(action) if !id.is_empty() => requests.push((id, action)),
+                _ => warn!("Ignoring malformed job control file {:?}", path),
+            }
+        }
+        requests
+    }
+
+    async fn register(&self, info: JobInfo) -> Arc<Job> {
+        let file = job_file(&self.dir, &info.id);
+        if let Err(e) = write_job_file(&file, &info) {
+            warn!("Failed to persist job {}: {}", info.id, e);
+        }
+        debug!("Registered indexing job {} for {}", info.id, info.root_dir);
+        let job = Arc::new(Job::new(file, info));
+        self.jobs.lock().await.insert(job.id(), job.clone());
+        job
+    }
+}
+
+pub fn request_control(dir: &Path, id: &str, action: JobAction) -> Result<(), String> {
+    if read_job_file(&job_file(dir, id)).is_none() {
+        return Err(format!("Job {} not found", id));
+    }
+    let file = dir.join(format!("{}.{}", id, CONTROL_EXTENSION));
+    std::fs::write(file, action.as_str()).map_err(|e| e.to_string())
+}
+
+pub fn read_job_files(dir: &Path) -> Vec<JobInfo> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("json"))
+        .filter_map(|p| read_job_file(&p))
+        .collect()
+}
+
+fn read_job_file(path: &Path) -> Option<JobInfo> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_job_file(path: &Path, info: &JobInfo) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(info)?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, path)
+}
+
+fn job_file(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.json", id))
+}
+
+fn generate_id() -> String {
+    let ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    format!("job_{}", ts)
+}
+
+pub(super) fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rememex_jobs_{}_{}", name, generate_id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_persisted_and_interrupted_on_reload() {
+        let dir = temp_dir("reload");
+        let manager = JobManager::new(dir.clone());
+        let job = manager.start("Default", "/tmp/root").await;
+        job.checkpoint(42, "/tmp/root/b.txt".to_string());
+        job.skip("/tmp/root/dump.sql".to_string(), "too large".to_string());
+        let id = job.id();
+        drop(manager);
+
+        let manager = JobManager::new(dir.clone());
+        let jobs = manager.list().await;
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].status, JobStatus::Interrupted);
+        assert_eq!(jobs[0].processed, 42);
+        assert_eq!(manager.take_interrupted(), vec![id.clone()]);
+        assert!(manager.take_interrupted().is_empty());
+        assert_eq!(
+            jobs[0].skipped,
+            vec![SkippedFile { path: "/tmp/root/dump.sql".to_string(), reason: "too large".to_string() }]
+        );
+
+        let resumed = manager.restart(&id).await.unwrap();
+        assert_eq!(resumed.resume_from(), Some((42, "/tmp/root/b.txt".to_string())));
+        assert_eq!(resumed.info().status, JobStatus::Running);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_releases_paused_job() {
+        let dir = temp_dir("cancel");
+        let manager = JobManager::new(dir.clone());
+        let job = manager.start("Default", "/tmp/root").await;
+        job.pause();
+        assert_eq!(job.info().status, JobStatus::Paused);
+
+        let waiter = {
+            let job = job.clone();
+            tokio::spawn(async move { job.wait_while_paused().await })
+        };
+        job.cancel();
+        waiter.await.unwrap();
+        job.finish(&Ok(3));
+        assert_eq!(job.info().status, JobStatus::Cancelled);
+        assert!(manager.restart(&job.id()).await.is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_control_requests_round_trip() {
+        let dir = temp_dir("control");
+        let manager = JobManager::new(dir.clone());
+        let job = manager.start("Default", "/tmp/root").await;
+        request_control(&dir, &job.id(), JobAction::Pause).unwrap();
+        assert!(request_control(&dir, "job_missing", JobAction::Cancel).is_err());
+
+        let requests = manager.take_control_requests();
+        assert_eq!(requests, vec![(job.id(), JobAction::Pause)]);
+        assert!(manager.take_control_requests().is_empty());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use lancedb::connection::Connection;
+use lancedb::table::{CompactionOptions, OptimizeAction, OptimizeOptions};
+use lancedb::Table;
+use log::{debug, info};
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use super::db;
+use super::jobs::now_unix;
+
+pub const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(60);
+const ANN_RETRAIN_GROWTH: usize = 2;
+const COMPACTION_INTERVAL_SECS: i64 = 60 * 60;
+const PRUNE_OLDER_THAN_DAYS: i64 = 1;
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct MaintenanceStatus {
+    pub table: String,
+    pub rows: usize,
+    pub version: u64,
+    pub has_fts_index: bool,
+    pub has_vector_index: bool,
+    pub unindexed_fts_rows: usize,
+    pub unindexed_vector_rows: usize,
+    pub ann_trained_rows: usize,
+    pub compacted_version: u64,
+    pub last_run: Option<i64>,
+    pub last_index_refresh: Option<i64>,
+    pub last_ann_rebuild: Option<i64>,
+    pub last_compaction: Option<i64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Plan {
+    build_fts: bool,
+    train_ann: bool,
+    refresh_indices: bool,
+    compact: bool,
+}
+
+impl Plan {
+    fn is_empty(&self) -> bool {
+        *self == Plan::default()
+    }
+}
+
+fn plan(status: &MaintenanceStatus, now: i64, force: bool) -> Plan {
+    let build_fts = status.rows > 0 && !status.has_fts_index;
+    let train_ann = status.rows >= super::ANN_INDEX_THRESHOLD
+        && (!status.has_vector_index || status.rows >= status.ann_trained_rows * ANN_RETRAIN_GROWTH);
+    let refresh_indices = (status.has_fts_index && status.unindexed_fts_rows > 0)
+        || (status.has_vector_index && !train_ann && status.unindexed_vector_rows > 0);
+    let compaction_due = status
+        .last_compaction
+        .is_none_or(|t| now - t >= COMPACTION_INTERVAL_SECS);
+    let compact = status.version > status.compacted_version && (force || compaction_due);
+    Plan {
+        build_fts,
+        train_ann,
+        refresh_indices,
+        compact,
+    }
+}
+
+#[derive(Default)]
+pub struct Maintainer {
+    tables: std::sync::Mutex<HashMap<String, MaintenanceStatus>>,
+    running: Mutex<()>,
+}
+
+pub type MaintenanceState = Arc<Maintainer>;
+
+impl Maintainer {
+    pub fn status(&self) -> Vec<MaintenanceStatus> {
+        let mut all: Vec<MaintenanceStatus> = self.lock_tables().values().cloned().collect();
+        all.sort_by(|a, b| a.table.cmp(&b.table));
+        all
+    }
+
+    pub async fn run(&self, db: &Connection, table_name: &str, force: bool) -> Result<Option<MaintenanceStatus>> {
+        let _guard = self.running.lock().await;
+        let table = match db.open_table(table_name).execute().await {
+            Ok(t) => t,
+            Err(_) => {
+                self.lock_tables().remove(table_name);
+                return Ok(None);
+            }
+        };
+
+        let mut status = self
+            .lock_tables()
+            .get(table_name)
+            .cloned()
+            .unwrap_or_else(|| MaintenanceStatus {
+                table: table_name.to_string(),
+                ..Default::default()
+            });
+        let result = maintain(&table, &mut status, force).await;
+        status.last_run = Some(now_unix());
+        status.error = result.as_ref().err().map(|e| e.to_string());
+        self.lock_tables().insert(table_name.to_string(), status.clone());
+        result.map(|_| Some(status))
+    }
+
+    fn lock_tables(&self) -> std::sync::MutexGuard<'_, HashMap<String, MaintenanceStatus>> {
+        self.tables.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+async fn refresh_status(table: &Table, status: &mut MaintenanceStatus) -> Result<()> {
+    status.rows = table.count_rows(None).await?;
+    status.version = table.version().await?;
+    status.has_fts_index = false;
+    status.has_vector_index = false;
+    status.unindexed_fts_rows = 0;
+    status.unindexed_vector_rows = 0;
+
+    for index in table.list_indices().await? {
+        let stats = table.index_stats(&index.name).await?;
+        let unindexed = stats.as_ref().map(|s| s.num_unindexed_rows).unwrap_or(0);
+        if index.columns.iter().any(|c| c == "content") {
+            status.has_fts_index = true;
+            status.unindexed_fts_rows = unindexed;
+        } else if index.columns.iter().any(|c| c == "vector") {
+            status.has_vector_index = true;
+            status.unindexed_vector_rows = unindexed;
+            if status.ann_trained_rows == 0 {
+                status.ann_trained_rows = stats.map(|s| s.num_indexed_rows).unwrap_or(0);
+            }
+        }
+    }
+    if !status.has_vector_index {
+        status.ann_trained_rows = 0;
+    }
+    Ok(())
+}
+
+async fn maintain(table: &Table, status: &mut MaintenanceStatus, force: bool) -> Result<()> {
+    refresh_status(table, status).await?;
+    let now = now_unix();
+    let plan = plan(status, now, force);
+    if plan.is_empty() {
+        return Ok(());
+    }
+    debug!("Maintenance for '{}': {:?}", status.table, plan);
+
+    if plan.build_fts {
+        db::build_fts_index(table).await?;
+        status.last_index_refresh = Some(now);
+    }
+    if plan.train_ann {
+        db::build_ann_index(table).await?;
+        status.ann_trained_rows = status.rows;
+        status.last_ann_rebuild = Some(now);
+        info!("Retrained vector index for '{}' on {} rows", status.table, status.rows);
+    }
+    if plan.refresh_indices {
+        table
+            .optimize(OptimizeAction::Index(OptimizeOptions::default()))
+            .await?;
+        status.last_index_refresh = Some(now);
+    }
+    if plan.compact {
+        table
+            .optimize(OptimizeAction::Compact {
+                options: CompactionOptions::default(),
+                remap_options: None,
+            })
+            .await?;
+        table
+            .optimize(OptimizeAction::Prune {
+                older_than: Some(chrono::Duration::days(PRUNE_OLDER_THAN_DAYS)),
+                delete_unverified: Some(false),
+                error_if_tagged_old_versions: None,
+            })
+            .await?;
+        status.last_compaction = Some(now);
+        info!("Compacted '{}' and pruned old versions", status.table);
+    }
+
+    refresh_status(table, status).await?;
+    if plan.compact {
+        status.compacted_version = status.version;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(rows: usize) -> MaintenanceStatus {
+        MaintenanceStatus {
+            table: "c_Default".to_string(),
+            rows,
+            version: 5,
+            compacted_version: 5,
+            last_compaction: Some(1_000),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_plan_builds_missing_indices() {
+        let p = plan(&status(10), 1_000, false);
+        assert!(p.build_fts);
+        assert!(!p.train_ann);
+
+        let p = plan(&status(1_000), 1_000, false);
+        assert!(p.build_fts);
+        assert!(p.train_ann);
+    }
+
+    #[test]
+    fn test_plan_refreshes_unindexed_rows_and_retrains_on_growth() {
+        let mut s = status(1_000);
+        s.has_fts_index = true;
+        s.has_vector_index = true;
+        s.ann_trained_rows = 800;
+        s.unindexed_fts_rows = 40;
+        s.unindexed_vector_rows = 40;
+        assert_eq!(
+            plan(&s, 1_000, false),
+            Plan { refresh_indices: true, ..Default::default() }
+        );
+
+        s.rows = 1_600;
+        let p = plan(&s, 1_000, false);
+        assert!(p.train_ann);
+        assert!(p.refresh_indices);
+    }
+
+    #[test]
+    fn test_plan_compacts_only_when_due_and_changed() {
+        let mut s = status(10);
+        s.has_fts_index = true;
+        assert!(plan(&s, 1_000 + COMPACTION_INTERVAL_SECS, false).is_empty());
+
+        s.version = 9;
+        assert!(!plan(&s, 1_010, false).compact);
+        assert!(plan(&s, 1_010, true).compact);
+        assert!(plan(&s, 1_000 + COMPACTION_INTERVAL_SECS, false).compact);
+    }
+}
@@ -0,0 +1,602 @@
+use anyhow::Result;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "canvas", "iframe", "object",
+];
+
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p", "div", "br", "li", "ul", "ol", "dl", "dt", "dd", "tr", "table", "thead", "tbody",
+    "tfoot", "caption", "blockquote", "pre", "figure", "figcaption", "header", "footer", "nav",
+    "main", "aside", "form", "fieldset", "legend", "address", "details", "summary", "body",
+];
+
+const SECTION_ELEMENTS: &[&str] = &["section", "article", "hr"];
+
+const SECTION_MARKER: &str = "---\n\n";
+
+pub fn is_markup_extension(ext: &str) -> bool {
+    matches!(ext, "html" | "htm" | "xhtml" | "xml" | "svg" | "rss" | "atom")
+}
+
+pub fn extract_text(raw: &str, ext: &str) -> String {
+    match ext {
+        "html" | "htm" | "xhtml" => html_text(raw),
+        _ => xml_text(raw).unwrap_or_else(|_| html_text(raw)),
+    }
+}
+
+#[derive(Default)]
+struct HtmlDoc {
+    out: String,
+    para: String,
+    space: bool,
+    heading: usize,
+    title: String,
+    description: Option<String>,
+}
+
+impl HtmlDoc {
+    fn text(&mut self, raw: &str) {
+        for c in decode_entities(raw).chars() {
+            if c.is_whitespace() {
+                self.space = true;
+            } else {
+                if self.space && !self.para.is_empty() {
+                    self.para.push(' ');
+                }
+                self.space = false;
+                self.para.push(c);
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        let text = self.para.trim();
+        if !text.is_empty() {
+            if self.heading > 0 {
+                self.out.push_str(&"#".repeat(self.heading));
+                self.out.push(' ');
+            }
+            self.out.push_str(text);
+            self.out.push_str("\n\n");
+        }
+        self.para.clear();
+        self.space = false;
+    }
+
+    fn section(&mut self) {
+        self.flush();
+        if !self.out.is_empty() && !self.out.ends_with(SECTION_MARKER) {
+            self.out.push_str(SECTION_MARKER);
+        }
+    }
+
+    fn tag(&mut self, name: &str, closing: bool) {
+        let heading = heading_level(name);
+        if heading > 0 {
+            self.flush();
+            self.heading = if closing { 0 } else { heading };
+        } else if SECTION_ELEMENTS.contains(&name) {
+            self.section();
+        } else if BLOCK_ELEMENTS.contains(&name) {
+            self.flush();
+        } else if matches!(name, "td" | "th") {
+            self.space = true;
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        let mut result = String::new();
+        if !self.title.is_empty() {
+            result.push_str("# ");
+            result.push_str(&self.title);
+            result.push_str("\n\n");
+        }
+        if let Some(description) = self.description.filter(|d| !d.is_empty()) {
+            result.push_str(&description);
+            result.push_str("\n\n");
+        }
+        let body = self.out.trim_end_matches(SECTION_MARKER);
+        result.push_str(body);
+        result.trim_end().to_string()
+    }
+}
+
+fn heading_level(name: &str) -> usize {
+    match name.as_bytes() {
+        [b'h', level @ b'1'..=b'6'] => (level - b'0') as usize,
+        _ => 0,
+    }
+}
+
+pub fn html_text(html: &str) -> String {
+    let mut doc = HtmlDoc::default();
+    let mut rest = html;
+
+    while let Some(lt) = rest.find('<') {
+        doc.text(&rest[..lt]);
+        rest = &rest[lt..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map(|i| &comment[i + 3..]).unwrap_or("");
+            continue;
+        }
+        let opens_tag = rest[1..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?'));
+        if !opens_tag {
+            doc.text("<");
+            rest = &rest[1..];
+            continue;
+        }
+        let Some(gt) = tag_end(rest) else {
+            break;
+        };
+        let tag = &rest[1..gt];
+        rest = &rest[gt + 1..];
+
+        let closing = tag.starts_with('/');
+        let body = tag.trim_start_matches('/');
+        let name_end = body
+            .find(|c: char| c.is_whitespace() || c == '/')
+            .unwrap_or(body.len());
+        let name = body[..name_end].to_ascii_lowercase();
+        let attrs = &body[name_end..];
+
+        if closing {
+            doc.tag(&name, true);
+            continue;
+        }
+        match name.as_str() {
+            "title" => {
+                let (inner, after) = raw_element(rest, "title");
+                rest = after;
+                if doc.title.is_empty() {
+                    doc.title = collapse_whitespace(&decode_entities(inner));
+                }
+            }
+            "meta" => {
+                let key = attr_value(attrs, "name").or_else(|| attr_value(attrs, "property"));
+                if doc.description.is_none()
+                    && key.is_some_and(|k| {
+                        k.eq_ignore_ascii_case("description") || k.eq_ignore_ascii_case("og:description")
+                    })
+                {
+                    doc.description = attr_value(attrs, "content").map(|c| collapse_whitespace(&c));
+                }
+            }
+            _ if SKIPPED_ELEMENTS.contains(&name.as_str()) && !tag.ends_with('/') => {
+                rest = raw_element(rest, &name).1;
+            }
+            _ => doc.tag(&name, false),
+        }
+    }
+    doc.text(rest);
+    doc.finish()
+}
+
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in tag.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn raw_element<'a>(rest: &'a str, name: &str) -> (&'a str, &'a str) {
+    let close = format!("</{}", name);
+    let end = rest
+        .as_bytes()
+        .windows(close.len())
+        .position(|w| w.eq_ignore_ascii_case(close.as_bytes()));
+    match end {
+        Some(end) => {
+            let after = &rest[end..];
+            let after = after.find('>').map(|i| &after[i + 1..]).unwrap_or("");
+            (&rest[..end], after)
+        }
+        None => (rest, ""),
+    }
+}
+
+fn attr_value(attrs: &str, name: &str) -> Option<String> {
+    let mut rest = attrs.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let key = &rest[..key_end];
+        rest = rest[key_end..].trim_start();
+
+        let mut value = "";
+        if let Some(after_eq) = rest.strip_prefix('=') {
+            let after_eq = after_eq.trim_start();
+            match after_eq.chars().next() {
+                Some(q @ ('"' | '\'')) => {
+                    let inner = &after_eq[1..];
+                    let end = inner.find(q).unwrap_or(inner.len());
+                    value = &inner[..end];
+                    rest = inner.get(end + 1..).unwrap_or("");
+                }
+                _ => {
+                    let end = after_eq.find(char::is_whitespace).unwrap_or(after_eq.len());
+                    value = &after_eq[..end];
+                    rest = &after_eq[end..];
+                }
+            }
+        }
+        if key.eq_ignore_ascii_case(name) {
+            return Some(decode_entities(value));
+        }
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+    }
+    None
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&i| i > 0 && i <= 32)
+            .and_then(|i| entity(&rest[1..i + 1]).map(|c| (c, i + 2)));
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn entity(name: &str) -> Option<char> {
+    if let Some(num) = name.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "hellip" => '…',
+        "mdash" => '—',
+        "ndash" => '–',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "laquo" => '«',
+        "raquo" => '»',
+        "middot" => '·',
+        "bull" => '•',
+        "deg" => '°',
+        "euro" => '€',
+        "pound" => '£',
+        "yen" => '¥',
+        "cent" => '¢',
+        "sect" => '§',
+        "para" => '¶',
+        "times" => '×',
+        "divide" => '÷',
+        _ => return None,
+    })
+}
+
+#[derive(Default)]
+struct Element {
+    name: String,
+    attrs: Vec<(String, String)>,
+    children: Vec<XmlNode>,
+}
+
+enum XmlNode {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    fn from_start(e: &BytesStart) -> Self {
+        let attrs = e
+            .attributes()
+            .flatten()
+            .map(|a| {
+                let key = String::from_utf8_lossy(a.key.local_name().as_ref()).to_lowercase();
+                let value = a
+                    .unescape_value()
+                    .map(|v| v.into_owned())
+                    .unwrap_or_else(|_| decode_entities(&String::from_utf8_lossy(&a.value)));
+                (key, value)
+            })
+            .collect();
+        Element {
+            name: String::from_utf8_lossy(e.local_name().as_ref()).to_lowercase(),
+            attrs,
+            children: Vec::new(),
+        }
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|c| match c {
+            XmlNode::Element(e) => Some(e),
+            XmlNode::Text(_) => None,
+        })
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.elements().find(|e| e.name == name)
+    }
+
+    fn descendants(&self, name: &str) -> Vec<&Element> {
+        let mut found = Vec::new();
+        let mut stack: Vec<&Element> = self.elements().collect();
+        stack.reverse();
+        while let Some(e) = stack.pop() {
+            if e.name == name {
+                found.push(e);
+                continue;
+            }
+            let start = stack.len();
+            stack.extend(e.elements());
+            stack[start..].reverse();
+        }
+        found
+    }
+
+    fn texts(&self) -> Vec<&str> {
+        let mut texts = Vec::new();
+        let mut stack: Vec<&XmlNode> = self.children.iter().rev().collect();
+        while let Some(node) = stack.pop() {
+            match node {
+                XmlNode::Text(t) => texts.push(t.as_str()),
+                XmlNode::Element(e) if SKIPPED_ELEMENTS.contains(&e.name.as_str()) => {}
+                XmlNode::Element(e) => stack.extend(e.children.iter().rev()),
+            }
+        }
+        texts
+    }
+
+    fn text(&self) -> String {
+        collapse_whitespace(&self.texts().join(" "))
+    }
+
+    fn child_text(&self, name: &str) -> Option<String> {
+        self.child(name).map(|e| e.text()).filter(|t| !t.is_empty())
+    }
+}
+
+fn parse_xml(xml: &str) -> Result<Element> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    reader.config_mut().check_end_names = false;
+
+    let mut stack = vec![Element::default()];
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => stack.push(Element::from_start(&e)),
+            Event::Empty(e) => {
+                let element = Element::from_start(&e);
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(XmlNode::Element(element));
+                }
+            }
+            Event::End(_) if stack.len() > 1 => {
+                let element = stack.pop().unwrap_or_default();
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(XmlNode::Element(element));
+                }
+            }
+            Event::Text(t) => {
+                let text = t
+                    .unescape()
+                    .map(|s| s.into_owned())
+                    .unwrap_or_else(|_| decode_entities(&String::from_utf8_lossy(&t)));
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(XmlNode::Text(text));
+                }
+            }
+            Event::CData(t) => {
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(XmlNode::Text(String::from_utf8_lossy(&t).into_owned()));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    while stack.len() > 1 {
+        let element = stack.pop().unwrap_or_default();
+        if let Some(parent) = stack.last_mut() {
+            parent.children.push(XmlNode::Element(element));
+        }
+    }
+    Ok(stack.pop().unwrap_or_default())
+}
+
+pub fn xml_text(xml: &str) -> Result<String> {
+    let doc = parse_xml(xml)?;
+    let Some(root) = doc.elements().next() else {
+        return Ok(String::new());
+    };
+    Ok(match root.name.as_str() {
+        "urlset" | "sitemapindex" => sitemap_text(root),
+        "rss" | "rdf" => rss_text(root),
+        "feed" => atom_text(root),
+        "html" => html_text(xml),
+        _ => root
+            .texts()
+            .into_iter()
+            .map(collapse_whitespace)
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"),
+    })
+}
+
+fn push_item(out: &mut String, title: Option<String>, lines: &[Option<String>], body: Option<String>) {
+    out.push_str("## ");
+    out.push_str(title.as_deref().unwrap_or("Untitled"));
+    out.push('\n');
+    for line in lines.iter().flatten() {
+        out.push_str(line);
+        out.push('\n');
+    }
+    if let Some(body) = body.map(|b| html_text(&b)).filter(|b| !b.is_empty()) {
+        out.push('\n');
+        out.push_str(&body);
+        out.push('\n');
+    }
+    out.push('\n');
+}
+
+fn push_feed_header(out: &mut String, title: Option<String>, description: Option<String>) {
+    if let Some(title) = title {
+        out.push_str("# ");
+        out.push_str(&title);
+        out.push_str("\n\n");
+    }
+    if let Some(description) = description {
+        out.push_str(&description);
+        out.push_str("\n\n");
+    }
+}
+
+fn rss_text(root: &Element) -> String {
+    let channel = root.child("channel").unwrap_or(root);
+    let mut out = String::new();
+    push_feed_header(&mut out, channel.child_text("title"), channel.child_text("description"));
+    for item in root.descendants("item") {
+        let date = item.child_text("pubdate").or_else(|| item.child_text("date"));
+        let body = item.child_text("encoded").or_else(|| item.child_text("description"));
+        push_item(&mut out, item.child_text("title"), &[item.child_text("link"), date], body);
+    }
+    out.trim_end().to_string()
+}
+
+fn atom_text(root: &Element) -> String {
+    let mut out = String::new();
+    push_feed_header(&mut out, root.child_text("title"), root.child_text("subtitle"));
+    for entry in root.descendants("entry") {
+        let link = entry
+            .elements()
+            .filter(|e| e.name == "link")
+            .find(|e| e.attr("rel").is_none_or(|rel| rel == "alternate"))
+            .and_then(|e| e.attr("href"))
+            .map(str::to_string);
+        let date = entry.child_text("updated").or_else(|| entry.child_text("published"));
+        let body = entry.child_text("content").or_else(|| entry.child_text("summary"));
+        push_item(&mut out, entry.child_text("title"), &[link, date], body);
+    }
+    out.trim_end().to_string()
+}
+
+fn sitemap_text(root: &Element) -> String {
+    root.elements()
+        .filter_map(|entry| {
+            let loc = entry.child_text("loc")?;
+            let mut line = format!("- {}", loc);
+            let title = entry.descendants("title").first().map(|t| t.text());
+            if let Some(title) = title.filter(|t| !t.is_empty()) {
+                line.push_str(&format!(" \"{}\"", title));
+            }
+            if let Some(lastmod) = entry.child_text("lastmod") {
+                line.push_str(&format!(" (updated {})", lastmod));
+            }
+            Some(line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_text_strips_markup() {
+        let html = r#"<!DOCTYPE html>
+<html><head>
+<title>Release &amp; Notes</title>
+<meta name="description" content="What changed in 2.5">
+<style>body { color: red; }</style>
+<script>var x = "<p>not text</p>";</script>
+</head><body>
+<!-- hidden comment -->
+<h1>Overview</h1>
+<p>Faster&nbsp;indexing &mdash; now with <b>page</b> numbers &#x2713;</p>
+<section><h2>Fixes</h2><ul><li>one &lt; two</li><li>three</li></ul></section>
+</body></html>"#;
+        let text = html_text(html);
+        assert!(text.starts_with("# Release & Notes\n\nWhat changed in 2.5\n\n# Overview"));
+        assert!(text.contains("Faster indexing — now with page numbers ✓"));
+        assert!(text.contains("---\n\n## Fixes\n\none < two\n\nthree"));
+        assert!(!text.contains("color"));
+        assert!(!text.contains("not text"));
+        assert!(!text.contains("hidden"));
+    }
+
+    #[test]
+    fn test_rss_items_become_sections() {
+        let rss = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel>
+<title>Dev Blog</title><description>Updates</description>
+<item><title>First post</title><link>https://example.com/1</link>
+<pubDate>Mon, 06 Jan 2025 10:00:00 GMT</pubDate>
+<description><![CDATA[<p>Hello <em>world</em></p>]]></description></item>
+<item><title>Second post</title><description>Plain &amp; simple</description></item>
+</channel></rss>"#;
+        let text = extract_text(rss, "xml");
+        assert!(text.starts_with("# Dev Blog\n\nUpdates\n\n## First post\nhttps://example.com/1\n"));
+        assert!(text.contains("Hello world"));
+        assert!(text.contains("## Second post\n\nPlain & simple"));
+    }
+
+    #[test]
+    fn test_sitemap_and_generic_xml() {
+        let sitemap = r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+<url><loc>https://example.com/</loc><lastmod>2025-01-01</lastmod></url>
+<url><loc>https://example.com/about</loc></url>
+</urlset>"#;
+        assert_eq!(
+            extract_text(sitemap, "xml"),
+            "- https://example.com/ (updated 2025-01-01)\n- https://example.com/about"
+        );
+
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><title>Logo</title><style>.a{}</style><text x="1">Rememex</text></svg>"#;
+        assert_eq!(extract_text(svg, "svg"), "Logo\nRememex");
+    }
+}
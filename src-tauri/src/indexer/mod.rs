@@ -1,42 +1,613 @@
 pub mod annotations;
+pub mod archive;
 pub mod chunking;
 pub mod db;
+pub mod email;
 pub mod embedding;
 pub mod embedding_provider;
+pub mod encoding;
 pub mod file_io;
 pub mod git;
+pub mod headings;
 pub mod ocr;
+pub mod office;
 pub mod hyde;
+pub mod jobs;
+pub mod maintenance;
+pub mod markup;
+pub mod notebook;
 pub mod pipeline;
 pub mod query_router;
+pub mod schema;
 pub mod search;
+pub mod snapshot;
+pub mod structured;
+pub mod syntax;
+pub mod tabular;
+pub mod tail;
 
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
-use arrow_array::RecordBatchIterator;
 use lancedb::connection::Connection;
-use log::{info, debug};
+use log::{info, debug, warn};
 use rayon::prelude::*;
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
 
-use crate::config::IndexingConfig;
-use crate::state::ProviderState;
+use crate::config::{IndexRoot, IndexingConfig};
+use crate::state::{IndexingProgress, ProviderState};
 
 use ignore::WalkBuilder;
 
 pub use chunking::expand_query;
 pub use db::reset_index;
 pub use embedding::{embed_query, load_model, load_reranker, rerank_results, safe_rerank};
-pub use search::{build_filter_expr, hybrid_merge, search_files, search_fts, search_pipeline};
+pub use search::{build_filter_expr, hybrid_merge, search_files, search_fts, search_pipeline, SearchHit};
 
 const ANN_INDEX_THRESHOLD: usize = 256;
 const EMBED_BATCH_SIZE: usize = 256;
+const WALK_QUEUE_SIZE: usize = 1024;
+const EXTRACT_QUEUE_SIZE: usize = 64;
+const OCR_QUEUE_SIZE: usize = 16;
+const WRITE_QUEUE_SIZE: usize = 2;
+const CHECKPOINT_INTERVAL: usize = 512;
+
+struct WalkedPath {
+    seq: usize,
+    path: PathBuf,
+    checkpointed: bool,
+}
 
 struct ExtractedFile {
-    path: String,
-    chunks: Vec<String>,
+    chunks: Vec<(String, db::ChunkMeta)>,
+    mtime: i64,
+    file_hash: String,
+    reuse: bool,
+}
+
+struct PendingFile {
     mtime: i64,
+    known_hash: Option<String>,
+    reuse: bool,
+}
+
+struct StreamedSegment {
+    chunks: Vec<(String, db::ChunkMeta)>,
+    first: bool,
+    reuse: bool,
+}
+
+enum FileChange {
+    Touched(i64),
+    Changed(ExtractedFile),
+    Appended(ExtractedFile),
+    Segment(StreamedSegment),
+    StreamEnd(db::FileState),
+    Image(PendingFile),
+    Archive(ArchiveChange),
+    Skipped(String),
+}
+
+struct ArchiveChange {
+    entries: Vec<(String, FileChange)>,
+    removed: Vec<String>,
+}
+
+struct IndexedFiles {
+    files: HashMap<String, db::FileState>,
+    archives: HashMap<String, HashMap<String, db::FileState>>,
+}
+
+impl IndexedFiles {
+    fn new(indexed: HashMap<String, db::FileState>) -> Self {
+        let mut files = HashMap::new();
+        let mut archives: HashMap<String, HashMap<String, db::FileState>> = HashMap::new();
+        for (path, state) in indexed {
+            match archive::split_virtual(&path) {
+                Some((archive_path, _)) => {
+                    let archive_path = archive_path.to_string();
+                    archives.entry(archive_path).or_default().insert(path, state);
+                }
+                None => {
+                    files.insert(path, state);
+                }
+            }
+        }
+        Self { files, archives }
+    }
+}
+
+struct ProcessedFile {
+    seq: usize,
+    path: String,
+    change: Option<FileChange>,
+}
+
+struct WriteBatch {
+    records: Vec<db::Record>,
+    touched: Vec<(db::FileKey, i64)>,
+    appended: Vec<db::FileKey>,
+    states: Vec<(db::FileKey, db::FileState)>,
+    committed: Vec<(db::FileKey, db::FileState)>,
+    deleted: Vec<db::FileKey>,
+    done: Vec<(usize, String)>,
+}
+
+fn file_extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+fn disk_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+fn build_chunks(
+    text: &str,
+    path: &Path,
+    file_size: u64,
+    encoding: Option<&str>,
+    sizing: &chunking::ChunkSizing,
+) -> Vec<(String, db::ChunkMeta)> {
+    let ext = file_extension(path);
+    let language = file_io::detect_language(path);
+    let mut last_parent = None;
+    chunking::sized_chunk_spans(text, &ext, sizing)
+        .into_iter()
+        .enumerate()
+        .map(|(chunk_index, chunk)| {
+            let meta = db::ChunkMeta {
+                start_line: chunk.start_line,
+                end_line: chunk.end_line,
+                start_byte: chunk.start_byte,
+                end_byte: chunk.end_byte,
+                chunk_index,
+                language: language.clone(),
+                file_size,
+                page: chunk.page,
+                cell: chunk.cell,
+                encoding: encoding.map(str::to_string),
+                symbol: chunk.symbol,
+                symbol_kind: chunk.symbol_kind,
+                breadcrumb: chunk.breadcrumb,
+                parent: chunk.parent.map(|parent| db::ParentSection {
+                    text: match last_parent.replace(parent.start_byte) {
+                        Some(start) if start == parent.start_byte => String::new(),
+                        _ => parent.text,
+                    },
+                    start_line: parent.start_line,
+                    end_line: parent.end_line,
+                    start_byte: parent.start_byte,
+                    end_byte: parent.end_byte,
+                }),
+                ..Default::default()
+            };
+            (chunk.text, meta)
+        })
+        .collect()
+}
+
+fn embedding_text(path: &str, meta: &db::ChunkMeta, content: &str) -> String {
+    let file_name = Path::new(path).file_name().and_then(|n| n.to_str()).unwrap_or("");
+    match &meta.breadcrumb {
+        Some(breadcrumb) => format!("File: {}\nSection: {}\n{}", file_name, breadcrumb, content),
+        None => format!("File: {}\n{}", file_name, content),
+    }
+}
+
+fn shift_chunks(chunks: &mut [(String, db::ChunkMeta)], bytes: usize, lines: usize, index: usize) {
+    for (_, meta) in chunks.iter_mut() {
+        meta.start_byte += bytes;
+        meta.end_byte += bytes;
+        meta.start_line += lines;
+        meta.end_line += lines;
+        meta.chunk_index += index;
+        if let Some(parent) = meta.parent.as_mut() {
+            parent.start_byte += bytes;
+            parent.end_byte += bytes;
+            parent.start_line += lines;
+            parent.end_line += lines;
+        }
+    }
+}
+
+fn stream_chunks(
+    path: &Path,
+    indexing_config: &IndexingConfig,
+    sizing: &chunking::ChunkSizing,
+    limits: &file_io::StreamLimits,
+    mut emit: impl FnMut(Vec<(String, db::ChunkMeta)>),
+) -> Option<usize> {
+    let file_size = disk_size(path);
+    let (mut byte_offset, mut line_offset, mut chunk_count) = (0usize, 0usize, 0usize);
+    let mut push_segment = |segment: &str, encoding: &str| {
+        let mut chunks = build_chunks(
+            segment,
+            path,
+            file_size,
+            Some(encoding),
+            sizing,
+        );
+        shift_chunks(&mut chunks, byte_offset, line_offset, chunk_count);
+        byte_offset += segment.len();
+        line_offset += segment.matches('\n').count();
+        chunk_count += chunks.len();
+        if !chunks.is_empty() {
+            emit(chunks);
+        }
+    };
+
+    let encoding = file_io::for_each_text_segment(path, limits.segment_bytes, &mut push_segment)?;
+    if indexing_config.use_git_history {
+        if let Some(git_ctx) = git::get_commit_context(path) {
+            push_segment(&git_ctx, encoding);
+        }
+    }
+    Some(chunk_count)
+}
+
+fn extract_tail(
+    path: &Path,
+    indexing_config: &IndexingConfig,
+    sizing: &chunking::ChunkSizing,
+    existing: Option<&db::FileState>,
+) -> Option<FileChange> {
+    let mut mtime = file_io::get_file_mtime(path);
+    let max_bytes = file_io::max_file_bytes(indexing_config);
+    let previous = existing.and_then(|f| tail::TailState::parse(&f.file_hash));
+    let (start, appended) = match previous.map(|p| (tail::read_appended(path, &p, max_bytes), p)) {
+        Some((Ok(Some(bytes)), previous)) => (previous, bytes),
+        _ => {
+            let start = tail::TailState::default();
+            let bytes = tail::read_appended(path, &start, max_bytes).ok()??;
+            (start, bytes)
+        }
+    };
+    let restart = start.offset == 0;
+    if appended.is_empty() {
+        return (!restart).then_some(FileChange::Touched(mtime));
+    }
+    if disk_size(path).saturating_sub(start.offset) > max_bytes {
+        mtime = 0;
+    }
+
+    let (text, encoding) = encoding::decode_text(&appended)?;
+    let mut chunks = build_chunks(
+        &text,
+        path,
+        disk_size(path),
+        Some(encoding),
+        sizing,
+    );
+    shift_chunks(&mut chunks, start.offset as usize, start.lines, start.chunks);
+    let offset = start.offset + appended.len() as u64;
+    let end = tail::TailState {
+        offset,
+        lines: start.lines + text.matches('\n').count(),
+        chunks: start.chunks + chunks.len(),
+        head: tail::head_hash(path, offset).ok()?,
+    };
+    let file = ExtractedFile {
+        chunks,
+        mtime,
+        file_hash: end.to_file_hash(),
+        reuse: restart && existing.is_some(),
+    };
+    Some(if restart {
+        FileChange::Changed(file)
+    } else {
+        FileChange::Appended(file)
+    })
+}
+
+fn extract_archive(
+    path: &Path,
+    indexing_config: &IndexingConfig,
+    sizing: &chunking::ChunkSizing,
+    previous: &HashMap<String, db::FileState>,
+) -> Option<FileChange> {
+    let archive_path = path.to_string_lossy();
+    let mtime = file_io::get_file_mtime(path);
+    if !previous.is_empty() && previous.values().all(|f| f.mtime == mtime) {
+        return None;
+    }
+
+    let mut entries: Vec<(String, FileChange)> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let result = archive::for_each_entry(path, |name, data| {
+        let entry_path = archive::virtual_path(&archive_path, name);
+        if seen.contains(&entry_path) || !file_io::is_extractable(name, indexing_config) {
+            return;
+        }
+        let existing = previous.get(&entry_path);
+        let file_hash = file_io::hash_bytes(data);
+        if existing.is_some_and(|f| f.file_hash == file_hash) {
+            seen.insert(entry_path.clone());
+            entries.push((entry_path, FileChange::Touched(mtime)));
+            return;
+        }
+        let Some(doc) = file_io::extract_document(name, data, indexing_config) else {
+            return;
+        };
+        if doc.text.trim().is_empty() {
+            return;
+        }
+        let chunks = build_chunks(
+            &doc.text,
+            Path::new(&entry_path),
+            data.len() as u64,
+            doc.encoding,
+            sizing,
+        );
+        seen.insert(entry_path.clone());
+        entries.push((
+            entry_path,
+            FileChange::Changed(ExtractedFile {
+                chunks,
+                mtime,
+                file_hash,
+                reuse: existing.is_some(),
+            }),
+        ));
+    });
+    if let Err(e) = result {
+        debug!("Skipping unreadable archive {}: {}", archive_path, e);
+        return None;
+    }
+
+    let removed = previous.keys().filter(|p| !seen.contains(*p)).cloned().collect();
+    Some(FileChange::Archive(ArchiveChange { entries, removed }))
+}
+
+fn is_tailed(path: &Path, indexing_config: &IndexingConfig, tail_matcher: Option<&globset::GlobSet>) -> bool {
+    tail_matcher.is_some_and(|m| tail::is_match(m, path)) && file_io::is_plain_text(path, indexing_config)
+}
+
+fn extract_file(
+    path: &Path,
+    indexing_config: &IndexingConfig,
+    sizing: &chunking::ChunkSizing,
+    indexed: &IndexedFiles,
+    tail_matcher: Option<&globset::GlobSet>,
+    mut emit: impl FnMut(StreamedSegment),
+) -> Option<FileChange> {
+    let path_str = path.to_string_lossy();
+    if indexing_config.index_archives && archive::is_archive_path(path) {
+        return match indexed.archives.get(path_str.as_ref()) {
+            Some(previous) => extract_archive(path, indexing_config, sizing, previous),
+            None => extract_archive(path, indexing_config, sizing, &HashMap::new()),
+        };
+    }
+    let mtime = file_io::get_file_mtime(path);
+    let existing = indexed.files.get(path_str.as_ref());
+
+    if existing.is_some_and(|f| f.mtime == mtime) {
+        return None;
+    }
+    if is_tailed(path, indexing_config, tail_matcher) {
+        return extract_tail(path, indexing_config, sizing, existing);
+    }
+    if let Some(reason) = file_io::oversize_reason(path, indexing_config) {
+        return Some(FileChange::Skipped(reason));
+    }
+    let known_hash = match existing {
+        Some(state) => {
+            let hash = file_io::hash_file(path)?;
+            if hash == state.file_hash {
+                return Some(FileChange::Touched(mtime));
+            }
+            Some(hash)
+        }
+        None => None,
+    };
+
+    let limits = file_io::StreamLimits::default();
+    if file_io::is_streamable(path, indexing_config, &limits) {
+        let file_hash = known_hash.or_else(|| file_io::hash_file(path)).unwrap_or_default();
+        let mut first = true;
+        let chunk_count = stream_chunks(path, indexing_config, sizing, &limits, |chunks| {
+            emit(StreamedSegment { chunks, first, reuse: existing.is_some() });
+            first = false;
+        })?;
+        return (chunk_count > 0).then_some(FileChange::StreamEnd(db::FileState { mtime, file_hash }));
+    }
+
+    let pending = PendingFile {
+        mtime,
+        known_hash,
+        reuse: existing.is_some(),
+    };
+    if ocr::is_image_extension(&file_extension(path)) {
+        return Some(FileChange::Image(pending));
+    }
+    let doc = file_io::read_document_with_config(path, indexing_config)?;
+    text_change(path, doc.text, doc.encoding, indexing_config, sizing, pending)
+}
+
+fn text_change(
+    path: &Path,
+    mut text: String,
+    encoding: Option<&str>,
+    indexing_config: &IndexingConfig,
+    sizing: &chunking::ChunkSizing,
+    pending: PendingFile,
+) -> Option<FileChange> {
+    if text.trim().is_empty() {
+        return None;
+    }
+    if indexing_config.use_git_history {
+        if let Some(git_ctx) = git::get_commit_context(path) {
+            text.push_str(&git_ctx);
+        }
+    }
+
+    let chunks = build_chunks(
+        &text,
+        path,
+        disk_size(path),
+        encoding,
+        sizing,
+    );
+    if chunks.is_empty() {
+        return None;
+    }
+    let file_hash = pending.known_hash.or_else(|| file_io::hash_file(path)).unwrap_or_default();
+    Some(FileChange::Changed(ExtractedFile {
+        chunks,
+        mtime: pending.mtime,
+        file_hash,
+        reuse: pending.reuse,
+    }))
+}
+
+fn walk_files(root_dir: &str, nested: &[PathBuf]) -> impl Iterator<Item = PathBuf> {
+    let nested = nested.to_vec();
+    WalkBuilder::new(root_dir)
+        .hidden(true)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .add_custom_ignore_filename(".rcignore")
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |entry| !nested.iter().any(|dir| entry.path() == dir))
+        .build()
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+        .map(|entry| entry.into_path())
+}
+
+fn checkpoint_matches(root_dir: &str, nested: &[PathBuf], skip_count: usize, skip_path: &str) -> bool {
+    skip_count > 0
+        && walk_files(root_dir, nested)
+            .nth(skip_count - 1)
+            .is_some_and(|path| path.to_string_lossy() == skip_path)
+}
+
+fn spawn_walker(
+    root_dir: String,
+    nested: Vec<PathBuf>,
+    resume_from: Option<(usize, String)>,
+    discovered: Arc<AtomicUsize>,
+) -> (std::sync::mpsc::Receiver<WalkedPath>, JoinHandle<HashSet<String>>) {
+    let (tx, rx) = std::sync::mpsc::sync_channel::<WalkedPath>(WALK_QUEUE_SIZE);
+    let handle = tokio::task::spawn_blocking(move || {
+        let (skip_count, skip_path) = resume_from.unwrap_or_default();
+        let skip_count = if checkpoint_matches(&root_dir, &nested, skip_count, &skip_path) {
+            skip_count
+        } else {
+            0
+        };
+        let mut seen: HashSet<String> = HashSet::new();
+        for (seq, path) in walk_files(&root_dir, &nested).enumerate() {
+            discovered.store(seq + 1, Ordering::Relaxed);
+            seen.insert(path.to_string_lossy().to_string());
+            let item = WalkedPath { seq, path, checkpointed: seq < skip_count };
+            if tx.send(item).is_err() {
+                return seen;
+            }
+        }
+        seen
+    });
+    (rx, handle)
+}
+
+fn spawn_extractors(
+    paths: std::sync::mpsc::Receiver<WalkedPath>,
+    files: mpsc::Sender<ProcessedFile>,
+    indexing_config: IndexingConfig,
+    sizing: chunking::ChunkSizing,
+    indexed: IndexedFiles,
+    scanned: Arc<AtomicUsize>,
+) -> JoinHandle<()> {
+    let tail_matcher = tail::build_matcher(&indexing_config.tail_patterns);
+    let (image_tx, image_rx) = mpsc::channel::<(WalkedPath, PendingFile)>(OCR_QUEUE_SIZE);
+    let ocr = spawn_ocr(image_rx, files.clone(), indexing_config.clone(), sizing.clone());
+    let extractors = tokio::task::spawn_blocking(move || {
+        let _ = paths.into_iter().par_bridge().try_for_each(|walked| {
+            scanned.fetch_add(1, Ordering::Relaxed);
+            let path = walked.path.to_string_lossy().to_string();
+            let send_segment = |segment| {
+                let _ = files.blocking_send(ProcessedFile {
+                    seq: walked.seq,
+                    path: path.clone(),
+                    change: Some(FileChange::Segment(segment)),
+                });
+            };
+            let change = if walked.checkpointed {
+                None
+            } else {
+                extract_file(&walked.path, &indexing_config, &sizing, &indexed, tail_matcher.as_ref(), send_segment)
+            };
+            if let Some(FileChange::Image(pending)) = change {
+                image_tx.blocking_send((walked, pending)).ok()?;
+                return (!files.is_closed()).then_some(());
+            }
+            let processed = ProcessedFile {
+                seq: walked.seq,
+                path,
+                change,
+            };
+            files.blocking_send(processed).ok()?;
+            (!files.is_closed()).then_some(())
+        });
+    });
+    tokio::spawn(async move {
+        let _ = extractors.await;
+        let _ = ocr.await;
+    })
+}
+
+fn spawn_ocr(
+    mut images: mpsc::Receiver<(WalkedPath, PendingFile)>,
+    files: mpsc::Sender<ProcessedFile>,
+    indexing_config: IndexingConfig,
+    sizing: chunking::ChunkSizing,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        while let Some((walked, pending)) = images.recv().await {
+            let change = match file_io::read_file_content_with_ocr(&walked.path).await {
+                Some(text) => text_change(&walked.path, text, None, &indexing_config, &sizing, pending),
+                None => None,
+            };
+            let processed = ProcessedFile {
+                seq: walked.seq,
+                path: walked.path.to_string_lossy().to_string(),
+                change,
+            };
+            if files.send(processed).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+async fn embed_records(
+    provider_state: &Arc<Mutex<ProviderState>>,
+    chunks: Vec<db::PendingChunk>,
+) -> Result<Vec<db::Record>> {
+    let texts: Vec<String> = chunks.iter().map(|c| embedding_text(&c.path, &c.meta, &c.content)).collect();
+    let embeddings = embed_batch(provider_state, texts).await?;
+    Ok(chunks
+        .into_iter()
+        .zip(embeddings)
+        .map(|(chunk, vector)| db::Record {
+            path: chunk.path,
+            root_id: chunk.root_id,
+            rel_path: chunk.rel_path,
+            content: chunk.content,
+            vector,
+            mtime: chunk.mtime,
+            file_hash: chunk.file_hash,
+            chunk_hash: chunk.chunk_hash,
+            meta: chunk.meta,
+        })
+        .collect())
 }
 
 async fn embed_batch(
@@ -51,6 +622,24 @@ async fn embed_batch(
     provider.embed_passages(texts).await
 }
 
+async fn chunk_sizing(
+    provider_state: &Arc<Mutex<ProviderState>>,
+    indexing_config: &IndexingConfig,
+) -> chunking::ChunkSizing {
+    let guard = provider_state.lock().await;
+    let budget = match guard.provider.as_ref() {
+        Some(provider) => provider.token_budget().await,
+        None => None,
+    };
+    chunking::ChunkSizing {
+        chunk_size: indexing_config.chunk_size,
+        chunk_overlap: indexing_config.chunk_overlap,
+        unit: indexing_config.chunk_unit,
+        budget,
+        parent_chunks: indexing_config.parent_chunks,
+    }
+}
+
 async fn get_provider_dim(provider_state: &Arc<Mutex<ProviderState>>) -> Result<usize> {
     let guard = provider_state.lock().await;
     let provider = guard
@@ -60,251 +649,330 @@ async fn get_provider_dim(provider_state: &Arc<Mutex<ProviderState>>) -> Result<
     provider.get_dimension().await
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn index_directory<F>(
-    root_dir: &str,
+    root: &IndexRoot,
+    nested: &[IndexRoot],
     table_name: &str,
     db: &Connection,
     provider_state: &Arc<Mutex<ProviderState>>,
     indexing_config: &IndexingConfig,
+    job: &Arc<jobs::Job>,
     progress_callback: F,
 ) -> Result<usize>
 where
-    F: Fn(usize, usize, String) + Send + Sync + 'static,
+    F: Fn(IndexingProgress) + Send + Sync + 'static,
 {
+    let root_dir = root.path.as_str();
+    let job_id = job.id();
+    let report = |current: usize, total: usize, pruned: usize, path: String| {
+        progress_callback(IndexingProgress {
+            current,
+            total,
+            path,
+            pruned,
+            job_id: Some(job_id.clone()),
+        });
+    };
+
     let dim = get_provider_dim(provider_state).await?;
     let table = db::get_or_create_table(db, table_name, dim).await?;
+    db::assign_root(&table, root, nested).await?;
+    db::clear_staged(&table, root).await?;
 
-    let existing_mtimes = db::get_indexed_mtimes(&table).await.unwrap_or_default();
-
-    info!("Indexing directory: {}", root_dir);
-
-    let all_files: Vec<_> = WalkBuilder::new(root_dir)
-        .hidden(true)
-        .git_ignore(true)
-        .git_global(true)
-        .git_exclude(true)
-        .add_custom_ignore_filename(".rcignore")
-        .build()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_some_and(|ft| ft.is_file()))
-        .map(|e| e.into_path())
-        .collect();
-    let total_files = all_files.len();
-    debug!("Found {} files ({} image, {} text)", total_files, all_files.iter().filter(|p| ocr::is_image_extension(&p.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase())).count(), all_files.iter().filter(|p| !ocr::is_image_extension(&p.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase())).count());
-
-    progress_callback(0, total_files, "Scanning files...".to_string());
-
-    let image_files: Vec<_> = all_files
-        .iter()
-        .filter(|p| {
-            let ext = p.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
-            ocr::is_image_extension(&ext)
-        })
-        .cloned()
-        .collect();
+    let existing_files = db::get_indexed_files(&table, root).await.unwrap_or_default();
+    let indexed_under_root: Vec<String> = existing_files.keys().cloned().collect();
 
-    let non_image_files: Vec<_> = all_files
-        .iter()
-        .filter(|p| {
-            let ext = p.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
-            !ocr::is_image_extension(&ext)
-        })
-        .cloned()
-        .collect();
+    let resume_from = job.resume_from();
+    match &resume_from {
+        Some((processed, path)) => info!("Resuming indexing of {} after {} files ({})", root_dir, processed, path),
+        None => info!("Indexing directory: {}", root_dir),
+    }
+    report(0, 0, 0, "Scanning files...".to_string());
 
-    let extracted: Vec<ExtractedFile> = non_image_files
-        .par_iter()
-        .filter_map(|path| {
-            let path_str = path.to_string_lossy().to_string();
-            let mtime = file_io::get_file_mtime(path);
+    let discovered = Arc::new(AtomicUsize::new(0));
+    let scanned = Arc::new(AtomicUsize::new(0));
+    let nested_dirs: Vec<PathBuf> = nested.iter().map(|n| PathBuf::from(&n.path)).collect();
+    let (path_rx, walker) = spawn_walker(root_dir.to_string(), nested_dirs.clone(), resume_from, discovered.clone());
+    let (file_tx, mut file_rx) = mpsc::channel::<ProcessedFile>(EXTRACT_QUEUE_SIZE);
+    let extractors = spawn_extractors(
+        path_rx,
+        file_tx,
+        indexing_config.clone(),
+        chunk_sizing(provider_state, indexing_config).await,
+        IndexedFiles::new(existing_files),
+        scanned.clone(),
+    );
 
-            if let Some(&existing_mtime) = existing_mtimes.get(&path_str) {
-                if existing_mtime == mtime {
-                    return None;
+    let (batch_tx, mut batch_rx) = mpsc::channel::<WriteBatch>(WRITE_QUEUE_SIZE);
+    let writer_table = table.clone();
+    let writer_job = job.clone();
+    let writer = tokio::spawn(async move {
+        let mut written = 0usize;
+        let mut next_seq = 0usize;
+        let mut completed: BTreeMap<usize, String> = BTreeMap::new();
+        while let Some(batch) = batch_rx.recv().await {
+            if !batch.deleted.is_empty() {
+                db::delete_files(&writer_table, &batch.deleted).await?;
+            }
+            if !batch.records.is_empty() {
+                written += batch.records.len();
+                let (appends, upserts): (Vec<_>, Vec<_>) = batch
+                    .records
+                    .into_iter()
+                    .partition(|r| {
+                        batch
+                            .appended
+                            .iter()
+                            .any(|key| key.root_id == r.root_id && key.rel_path == r.rel_path)
+                    });
+                if !upserts.is_empty() {
+                    db::upsert_records(&writer_table, upserts).await?;
+                }
+                if !appends.is_empty() {
+                    db::append_records(&writer_table, appends).await?;
                 }
             }
-
-            let mut text = file_io::read_file_content_with_config(path, indexing_config)?;
-            if text.trim().is_empty() {
-                return None;
+            for (key, state) in &batch.states {
+                db::update_file_state(&writer_table, key, state.mtime, &state.file_hash).await?;
             }
-            if indexing_config.use_git_history {
-                if let Some(git_ctx) = git::get_commit_context(path) {
-                    text.push_str(&git_ctx);
-                }
+            for (key, state) in &batch.committed {
+                db::commit_staged(&writer_table, key, state).await?;
+            }
+            if !batch.touched.is_empty() {
+                db::update_mtimes(&writer_table, &batch.touched).await?;
+            }
+            completed.extend(batch.done);
+            let mut last_path = None;
+            while let Some(path) = completed.remove(&next_seq) {
+                next_seq += 1;
+                last_path = Some(path);
             }
+            if let Some(path) = last_path {
+                writer_job.checkpoint(next_seq, path);
+            }
+        }
+        Ok::<usize, anyhow::Error>(written)
+    });
 
-            let ext = path
-                .extension()
-                .and_then(|s| s.to_str())
-                .unwrap_or("")
-                .to_lowercase();
-            let chunks = chunking::semantic_chunk_with_overrides(
-                &text,
-                &ext,
-                indexing_config.chunk_size,
-                indexing_config.chunk_overlap,
-            );
-            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-            let chunks: Vec<String> = chunks.into_iter().map(|c| format!("File: {}\n{}", file_name, c)).collect();
+    let mut pending_chunks: Vec<db::PendingChunk> = Vec::new();
+    let mut pending_reused: Vec<db::Record> = Vec::new();
+    let mut pending_touched: Vec<(db::FileKey, i64)> = Vec::new();
+    let mut pending_deleted: Vec<db::FileKey> = Vec::new();
+    let mut pending_appended: Vec<db::FileKey> = Vec::new();
+    let mut pending_states: Vec<(db::FileKey, db::FileState)> = Vec::new();
+    let mut pending_committed: Vec<(db::FileKey, db::FileState)> = Vec::new();
+    let mut streamed_reuse: HashMap<String, HashMap<String, Vec<f32>>> = HashMap::new();
+    let no_reuse: HashMap<String, Vec<f32>> = HashMap::new();
+    let mut pending_done: Vec<(usize, String)> = Vec::new();
+    let mut files_indexed = 0usize;
+    let mut chunks_reused = 0usize;
+    let mut batches_written = 0usize;
+    let mut embed_result: Result<()> = Ok(());
 
-            Some(ExtractedFile {
-                path: path_str,
-                chunks,
-                mtime,
-            })
-        })
-        .collect();
+    loop {
+        if job.is_paused() {
+            report(
+                scanned.load(Ordering::Relaxed),
+                discovered.load(Ordering::Relaxed),
+                0,
+                "Paused".to_string(),
+            );
+            job.wait_while_paused().await;
+        }
+        if job.is_cancelled() {
+            break;
+        }
 
-    let mut image_futures = Vec::new();
-    for path in &image_files {
-        let path_str = path.to_string_lossy().to_string();
-        let mtime = file_io::get_file_mtime(path);
+        let next = file_rx.recv().await;
+        let flush = match &next {
+            Some(_) => {
+                pending_chunks.len() + pending_reused.len() >= EMBED_BATCH_SIZE
+                    || pending_done.len() >= CHECKPOINT_INTERVAL
+            }
+            None => !pending_done.is_empty(),
+        };
 
-        if let Some(&existing_mtime) = existing_mtimes.get(&path_str) {
-            if existing_mtime == mtime {
-                continue;
+        if flush {
+            let batch_chunks = std::mem::take(&mut pending_chunks);
+            let touched = std::mem::take(&mut pending_touched);
+            let deleted = std::mem::take(&mut pending_deleted);
+            let appended = std::mem::take(&mut pending_appended);
+            let states = std::mem::take(&mut pending_states);
+            let committed = std::mem::take(&mut pending_committed);
+            let done = std::mem::take(&mut pending_done);
+            let mut records = if batch_chunks.is_empty() {
+                Vec::new()
+            } else {
+                batches_written += 1;
+                report(
+                    scanned.load(Ordering::Relaxed),
+                    discovered.load(Ordering::Relaxed),
+                    0,
+                    format!("Embedding batch {}", batches_written),
+                );
+                match embed_records(provider_state, batch_chunks).await {
+                    Ok(r) => r,
+                    Err(e) => {
+                        embed_result = Err(e);
+                        break;
+                    }
+                }
+            };
+            records.append(&mut pending_reused);
+            if batch_tx.send(WriteBatch { records, touched, appended, states, committed, deleted, done }).await.is_err() {
+                break;
             }
         }
 
-        let path_clone = path.clone();
-        let use_git = indexing_config.use_git_history;
-        let chunk_size = indexing_config.chunk_size;
-        let chunk_overlap = indexing_config.chunk_overlap;
-        image_futures.push(tokio::spawn(async move {
-            if let Some(mut text) = file_io::read_file_content_with_ocr(&path_clone).await {
-                if !text.trim().is_empty() {
-                    if use_git {
-                        if let Some(git_ctx) = git::get_commit_context(&path_clone) {
-                            text.push_str(&git_ctx);
-                        }
+        let Some(processed) = next else { break };
+        if !matches!(processed.change, Some(FileChange::Segment(_))) {
+            pending_done.push((processed.seq, processed.path.clone()));
+        }
+        let changes = match processed.change {
+            None => continue,
+            Some(FileChange::Archive(archive)) => {
+                pending_deleted.extend(archive.removed.iter().map(|p| db::FileKey::new(root, p)));
+                archive.entries
+            }
+            Some(change) => vec![(processed.path, change)],
+        };
+        for (path, change) in changes {
+            let mut key = db::FileKey::new(root, &path);
+            let fetched;
+            let (chunks, mtime, file_hash, reusable) = match change {
+                FileChange::Changed(ef) => {
+                    files_indexed += 1;
+                    fetched = reusable_vectors(&table, &key, ef.reuse).await;
+                    (ef.chunks, ef.mtime, ef.file_hash, &fetched)
+                }
+                FileChange::Appended(ef) => {
+                    files_indexed += 1;
+                    pending_appended.push(key.clone());
+                    pending_states.push((key.clone(), db::FileState { mtime: ef.mtime, file_hash: ef.file_hash.clone() }));
+                    fetched = reusable_vectors(&table, &key, ef.reuse).await;
+                    (ef.chunks, ef.mtime, ef.file_hash, &fetched)
+                }
+                FileChange::Segment(segment) => {
+                    if segment.first && segment.reuse {
+                        streamed_reuse.insert(path.clone(), reusable_vectors(&table, &key, true).await);
+                    }
+                    key = key.staged();
+                    pending_appended.push(key.clone());
+                    (segment.chunks, 0, String::new(), streamed_reuse.get(&path).unwrap_or(&no_reuse))
+                }
+                FileChange::StreamEnd(state) => {
+                    files_indexed += 1;
+                    streamed_reuse.remove(&path);
+                    pending_committed.push((key, state));
+                    continue;
+                }
+                FileChange::Touched(mtime) => {
+                    pending_touched.push((key, mtime));
+                    continue;
+                }
+                FileChange::Skipped(reason) => {
+                    job.skip(path, reason);
+                    pending_deleted.push(key);
+                    continue;
+                }
+                FileChange::Image(_) | FileChange::Archive(_) => continue,
+            };
+            for (chunk, meta) in chunks {
+                let chunk_hash = file_io::hash_text(&embedding_text(&path, &meta, &chunk));
+                match reusable.get(&chunk_hash) {
+                    Some(vector) => {
+                        chunks_reused += 1;
+                        pending_reused.push(db::Record {
+                            path: path.clone(),
+                            root_id: key.root_id.clone(),
+                            rel_path: key.rel_path.clone(),
+                            content: chunk,
+                            vector: vector.clone(),
+                            mtime,
+                            file_hash: file_hash.clone(),
+                            chunk_hash,
+                            meta,
+                        });
                     }
-                    let ext = path_clone
-                        .extension()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("")
-                        .to_lowercase();
-                    let chunks = chunking::semantic_chunk_with_overrides(&text, &ext, chunk_size, chunk_overlap);
-                    let file_name = path_clone.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                    let chunks: Vec<String> = chunks.into_iter().map(|c| format!("File: {}\n{}", file_name, c)).collect();
-                    return Some(ExtractedFile {
-                        path: path_clone.to_string_lossy().to_string(),
-                        chunks,
+                    None => pending_chunks.push(db::PendingChunk {
+                        path: path.clone(),
+                        root_id: key.root_id.clone(),
+                        rel_path: key.rel_path.clone(),
+                        content: chunk,
                         mtime,
-                    });
+                        file_hash: file_hash.clone(),
+                        chunk_hash,
+                        meta,
+                    }),
                 }
             }
-            None
-        }));
+        }
+    }
+
+    drop(file_rx);
+    drop(batch_tx);
+    let write_result = writer.await.map_err(|e| anyhow!("Index writer panicked: {}", e))?;
+    let _ = extractors.await;
+    let seen = walker.await.unwrap_or_default();
+    let total_files = seen.len();
+
+    embed_result?;
+    let chunks_written = write_result?;
+
+    if job.is_cancelled() {
+        info!("Indexing of {} cancelled after {} files", root_dir, files_indexed);
+        report(
+            scanned.load(Ordering::Relaxed),
+            total_files,
+            0,
+            "Cancelled".to_string(),
+        );
+        return Ok(files_indexed);
     }
 
-    let image_results = futures::future::join_all(image_futures).await;
-    let image_extracted: Vec<ExtractedFile> = image_results
+    let orphans: Vec<db::FileKey> = indexed_under_root
         .into_iter()
-        .filter_map(|r| r.ok().flatten())
+        .filter(|p| {
+            if nested_dirs.iter().any(|dir| Path::new(p).starts_with(dir)) {
+                return true;
+            }
+            match archive::split_virtual(p) {
+                Some((archive_path, _)) => {
+                    !indexing_config.index_archives
+                        || (!seen.contains(archive_path) && !Path::new(archive_path).exists())
+                }
+                None => !seen.contains(p) && !Path::new(p).exists(),
+            }
+        })
+        .map(|p| db::FileKey::new(root, &p))
         .collect();
-
-    let mut all_extracted = extracted;
-    all_extracted.extend(image_extracted);
-    let files_indexed = all_extracted.len();
+    let pruned = if orphans.is_empty() {
+        0
+    } else {
+        report(total_files, total_files, 0, format!("Pruning {} missing files...", orphans.len()));
+        let pruned = db::delete_files(&table, &orphans).await?;
+        info!("Pruned {} missing files from {}", pruned, root_dir);
+        pruned
+    };
 
     if files_indexed == 0 {
         info!("No new files to index in {}", root_dir);
-        progress_callback(total_files, total_files, "Done -- no new files".to_string());
+        report(total_files, total_files, pruned, "Done -- no new files".to_string());
         return Ok(0);
     }
 
-    progress_callback(
-        0,
-        files_indexed,
-        format!("Extracted {} files, starting embedding...", files_indexed),
+    debug!(
+        "Wrote {} chunks ({} reused) from {} files ({} scanned)",
+        chunks_written, chunks_reused, files_indexed, total_files
     );
 
-    let mut pending_chunks: Vec<db::PendingChunk> = Vec::new();
-    let mut batches_written = 0;
-
-    for (idx, ef) in all_extracted.iter().enumerate() {
-        let safe_path = ef.path.replace('\'', "''");
-        let _ = table.delete(&format!("path = '{}'", safe_path)).await;
-
-        for chunk in &ef.chunks {
-            pending_chunks.push(db::PendingChunk {
-                path: ef.path.clone(),
-                content: chunk.clone(),
-                mtime: ef.mtime,
-            });
-        }
-
-        if pending_chunks.len() >= EMBED_BATCH_SIZE {
-            batches_written += 1;
-            progress_callback(
-                idx + 1,
-                files_indexed,
-                format!("Embedding batch {}", batches_written),
-            );
-
-            let batch_chunks: Vec<db::PendingChunk> = std::mem::take(&mut pending_chunks);
-            let texts: Vec<String> = batch_chunks.iter().map(|c| c.content.clone()).collect();
-            let embeddings = embed_batch(provider_state, texts).await?;
-
-            let records: Vec<db::Record> = batch_chunks
-                .into_iter()
-                .zip(embeddings)
-                .map(|(chunk, vector)| db::Record {
-                    path: chunk.path,
-                    content: chunk.content,
-                    vector,
-                    mtime: chunk.mtime,
-                })
-                .collect();
-
-            let batch = db::create_record_batch(records)?;
-            let schema = batch.schema();
-            table
-                .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
-                .execute()
-                .await?;
-        }
-    }
-
-    if !pending_chunks.is_empty() {
-        batches_written += 1;
-        progress_callback(
-            files_indexed,
-            files_indexed,
-            format!("Embedding batch {}", batches_written),
-        );
-
-        let texts: Vec<String> = pending_chunks.iter().map(|c| c.content.clone()).collect();
-        let embeddings = embed_batch(provider_state, texts).await?;
-
-        let records: Vec<db::Record> = pending_chunks
-            .into_iter()
-            .zip(embeddings)
-            .map(|(chunk, vector)| db::Record {
-                path: chunk.path,
-                content: chunk.content,
-                vector,
-                mtime: chunk.mtime,
-            })
-            .collect();
-
-        let batch = db::create_record_batch(records)?;
-        let schema = batch.schema();
-        table
-            .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
-            .execute()
-            .await?;
-    }
-
-    let total_indexed = total_files - image_files.len() + files_indexed;
-
-    if total_indexed >= ANN_INDEX_THRESHOLD {
-        progress_callback(files_indexed, files_indexed, "Building vector index...".to_string());
+    if total_files >= ANN_INDEX_THRESHOLD {
+        report(total_files, total_files, pruned, "Building vector index...".to_string());
         let _ = db::build_ann_index(&table).await;
     }
 
-    progress_callback(files_indexed, files_indexed, "Building search index...".to_string());
+    report(total_files, total_files, pruned, "Building search index...".to_string());
     let _ = db::build_fts_index(&table).await;
 
     info!("Indexing complete: {} files indexed in {}", files_indexed, root_dir);
@@ -312,13 +980,12 @@ where
 }
 
 pub async fn index_single_file(
-    file_path: &std::path::Path,
+    file_path: &Path,
+    root: &IndexRoot,
     table_name: &str,
     db: &Connection,
     provider_state: &Arc<Mutex<ProviderState>>,
-    use_git_history: bool,
-    chunk_size: Option<usize>,
-    chunk_overlap: Option<usize>,
+    indexing_config: &IndexingConfig,
 ) -> Result<bool> {
     debug!("index_single_file: {}", file_path.display());
     if !file_path.is_file() {
@@ -327,77 +994,331 @@ pub async fn index_single_file(
 
     let dim = get_provider_dim(provider_state).await?;
     let table = db::get_or_create_table(db, table_name, dim).await?;
+    let sizing = chunk_sizing(provider_state, indexing_config).await;
     let path_str = file_path.to_string_lossy().to_string();
+    if indexing_config.index_archives && archive::is_archive_path(file_path) {
+        return index_single_archive(file_path, root, &table, provider_state, indexing_config, &sizing).await;
+    }
     let mtime = file_io::get_file_mtime(file_path);
+    let key = db::FileKey::new(root, &path_str);
 
-    if let Ok(Some(existing_mtime)) = db::get_single_file_mtime(&table, &path_str).await {
-        if existing_mtime == mtime {
-            return Ok(false);
+    let existing = db::get_single_file_state(&table, &key).await.ok().flatten();
+    if existing.as_ref().is_some_and(|f| f.mtime == mtime) {
+        return Ok(false);
+    }
+    let tail_matcher = tail::build_matcher(&indexing_config.tail_patterns);
+    if is_tailed(file_path, indexing_config, tail_matcher.as_ref()) {
+        return match extract_tail(file_path, indexing_config, &sizing, existing.as_ref()) {
+            Some(FileChange::Changed(file)) => {
+                write_single_file(&table, provider_state, root, &path_str, file, false).await
+            }
+            Some(FileChange::Appended(file)) => {
+                write_single_file(&table, provider_state, root, &path_str, file, true).await
+            }
+            Some(FileChange::Touched(mtime)) => {
+                db::update_mtime(&table, &key, mtime).await?;
+                Ok(false)
+            }
+            _ => Ok(false),
+        };
+    }
+    if let Some(reason) = file_io::oversize_reason(file_path, indexing_config) {
+        warn!("Skipping {}: {}", path_str, reason);
+        if existing.is_some() {
+            db::delete_files(&table, std::slice::from_ref(&key)).await?;
         }
+        return Ok(false);
+    }
+    let file_hash = file_io::hash_file(file_path).unwrap_or_default();
+    if existing.as_ref().is_some_and(|f| f.file_hash == file_hash) {
+        db::update_mtime(&table, &key, mtime).await?;
+        return Ok(false);
     }
 
-    let safe_path = path_str.replace('\'', "''");
-    let _ = table.delete(&format!("path = '{}'", safe_path)).await;
-
-    let ext = file_path
-        .extension()
-        .and_then(|s| s.to_str())
-        .unwrap_or("")
-        .to_lowercase();
+    if file_io::is_streamable(file_path, indexing_config, &file_io::StreamLimits::default()) {
+        let state = db::FileState { mtime, file_hash };
+        return write_streamed_file(&table, provider_state, root, file_path, indexing_config, &sizing, state, existing.is_some())
+            .await;
+    }
 
-    let text = if ocr::is_image_extension(&ext) {
-        file_io::read_file_content_with_ocr(file_path).await
+    let doc = if ocr::is_image_extension(&file_extension(file_path)) {
+        file_io::read_file_content_with_ocr(file_path)
+            .await
+            .map(|text| file_io::ExtractedText { text, encoding: None })
     } else {
-        file_io::read_file_content(file_path)
+        file_io::read_document_with_config(file_path, indexing_config)
     };
-
-    let mut text = match text {
-        Some(t) if !t.trim().is_empty() => t,
-        _ => return Ok(false),
+    let chunks = match doc {
+        Some(mut doc) if !doc.text.trim().is_empty() => {
+            if indexing_config.use_git_history {
+                if let Some(git_ctx) = git::get_commit_context(file_path) {
+                    doc.text.push_str(&git_ctx);
+                }
+            }
+            build_chunks(
+                &doc.text,
+                file_path,
+                disk_size(file_path),
+                doc.encoding,
+                &sizing,
+            )
+        }
+        _ => Vec::new(),
     };
-    if use_git_history {
-        if let Some(git_ctx) = git::get_commit_context(file_path) {
-            text.push_str(&git_ctx);
+    if chunks.is_empty() {
+        if existing.is_some() {
+            db::delete_files(&table, std::slice::from_ref(&key)).await?;
         }
+        return Ok(false);
     }
 
-    let chunks = chunking::semantic_chunk_with_overrides(&text, &ext, chunk_size, chunk_overlap);
-    if chunks.is_empty() {
+    let file = ExtractedFile {
+        chunks,
+        mtime,
+        file_hash,
+        reuse: existing.is_some(),
+    };
+    write_single_file(&table, provider_state, root, &path_str, file, false).await
+}
+
+async fn index_single_archive(
+    file_path: &Path,
+    root: &IndexRoot,
+    table: &lancedb::Table,
+    provider_state: &Arc<Mutex<ProviderState>>,
+    indexing_config: &IndexingConfig,
+    sizing: &chunking::ChunkSizing,
+) -> Result<bool> {
+    let archive_path = file_path.to_string_lossy();
+    let previous = db::get_archive_files(table, root, &archive_path).await.unwrap_or_default();
+    let Some(FileChange::Archive(change)) = extract_archive(file_path, indexing_config, sizing, &previous) else {
         return Ok(false);
+    };
+
+    if !change.removed.is_empty() {
+        let removed: Vec<db::FileKey> = change.removed.iter().map(|p| db::FileKey::new(root, p)).collect();
+        db::delete_files(table, &removed).await?;
+    }
+    let mut indexed = false;
+    for (path, entry) in change.entries {
+        match entry {
+            FileChange::Touched(mtime) => db::update_mtime(table, &db::FileKey::new(root, &path), mtime).await?,
+            FileChange::Changed(file) => {
+                indexed |= write_single_file(table, provider_state, root, &path, file, false).await?;
+            }
+            FileChange::Appended(_)
+            | FileChange::Segment(_)
+            | FileChange::StreamEnd(_)
+            | FileChange::Image(_)
+            | FileChange::Archive(_)
+            | FileChange::Skipped(_) => {}
+        }
+    }
+    Ok(indexed)
+}
+
+async fn reusable_vectors(table: &lancedb::Table, key: &db::FileKey, reuse: bool) -> HashMap<String, Vec<f32>> {
+    if reuse {
+        db::get_chunk_vectors(table, key).await.unwrap_or_default()
+    } else {
+        HashMap::new()
     }
-    let file_name = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-    let texts: Vec<String> = chunks.into_iter().map(|c| format!("File: {}\n{}", file_name, c)).collect();
-    let embeddings = embed_batch(provider_state, texts.clone()).await?;
+}
 
-    let records: Vec<db::Record> = texts
+async fn file_records(
+    provider_state: &Arc<Mutex<ProviderState>>,
+    key: &db::FileKey,
+    path_str: &str,
+    chunks: Vec<(String, db::ChunkMeta)>,
+    state: &db::FileState,
+    reusable: &HashMap<String, Vec<f32>>,
+) -> Result<Vec<db::Record>> {
+    let texts: Vec<String> = chunks.iter().map(|(c, m)| embedding_text(path_str, m, c)).collect();
+    let hashes: Vec<String> = texts.iter().map(|t| file_io::hash_text(t)).collect();
+    let to_embed: Vec<String> = texts
         .into_iter()
-        .zip(embeddings)
-        .map(|(content, vector)| db::Record {
-            path: path_str.clone(),
-            content,
-            vector,
-            mtime,
-        })
+        .zip(&hashes)
+        .filter(|(_, h)| !reusable.contains_key(*h))
+        .map(|(t, _)| t)
         .collect();
+    let mut embeddings = if to_embed.is_empty() {
+        Vec::new()
+    } else {
+        embed_batch(provider_state, to_embed).await?
+    }
+    .into_iter();
 
-    let batch = db::create_record_batch(records)?;
-    let schema = batch.schema();
-    table
-        .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
-        .execute()
-        .await?;
+    chunks
+        .into_iter()
+        .zip(hashes)
+        .map(|((content, meta), chunk_hash)| {
+            let vector = match reusable.get(&chunk_hash) {
+                Some(v) => v.clone(),
+                None => embeddings
+                    .next()
+                    .ok_or_else(|| anyhow!("Embedding count mismatch for {}", path_str))?,
+            };
+            Ok(db::Record {
+                path: path_str.to_string(),
+                root_id: key.root_id.clone(),
+                rel_path: key.rel_path.clone(),
+                content,
+                vector,
+                mtime: state.mtime,
+                file_hash: state.file_hash.clone(),
+                chunk_hash,
+                meta,
+            })
+        })
+        .collect()
+}
+
+async fn write_single_file(
+    table: &lancedb::Table,
+    provider_state: &Arc<Mutex<ProviderState>>,
+    root: &IndexRoot,
+    path_str: &str,
+    file: ExtractedFile,
+    append: bool,
+) -> Result<bool> {
+    if file.chunks.is_empty() {
+        return Ok(false);
+    }
+    let key = db::FileKey::new(root, path_str);
+    let reusable = reusable_vectors(table, &key, file.reuse).await;
+    let state = db::FileState { mtime: file.mtime, file_hash: file.file_hash };
+    let records = file_records(provider_state, &key, path_str, file.chunks, &state, &reusable).await?;
+
+    if append {
+        db::append_records(table, records).await?;
+        db::update_file_state(table, &key, state.mtime, &state.file_hash).await?;
+    } else {
+        db::upsert_records(table, records).await?;
+    }
 
     Ok(true)
 }
 
+#[allow(clippy::too_many_arguments)]
+async fn write_streamed_file(
+    table: &lancedb::Table,
+    provider_state: &Arc<Mutex<ProviderState>>,
+    root: &IndexRoot,
+    file_path: &Path,
+    indexing_config: &IndexingConfig,
+    sizing: &chunking::ChunkSizing,
+    state: db::FileState,
+    reuse: bool,
+) -> Result<bool> {
+    let path_str = file_path.to_string_lossy().to_string();
+    let key = db::FileKey::new(root, &path_str);
+    let staged = key.staged();
+    let reusable = reusable_vectors(table, &key, reuse).await;
+    db::delete_files(table, std::slice::from_ref(&staged)).await?;
+
+    let (tx, mut rx) = mpsc::channel(1);
+    let reader = tokio::task::spawn_blocking({
+        let (path, indexing_config, sizing) = (file_path.to_path_buf(), indexing_config.clone(), sizing.clone());
+        move || {
+            stream_chunks(&path, &indexing_config, &sizing, &file_io::StreamLimits::default(), |chunks| {
+                let _ = tx.blocking_send(chunks);
+            })
+        }
+    });
+    let pending = db::FileState { mtime: 0, file_hash: String::new() };
+    while let Some(chunks) = rx.recv().await {
+        let records = file_records(provider_state, &staged, &path_str, chunks, &pending, &reusable).await?;
+        db::append_records(table, records).await?;
+    }
+    if reader.await?.unwrap_or(0) == 0 {
+        if reuse {
+            db::delete_files(table, std::slice::from_ref(&key)).await?;
+        }
+        return Ok(false);
+    }
+    db::commit_staged(table, &key, &state).await?;
+    Ok(true)
+}
+
 pub async fn delete_file_from_index(
     file_path: &str,
+    root: &IndexRoot,
     table_name: &str,
     db: &Connection,
 ) -> Result<()> {
     debug!("delete_file_from_index: {}", file_path);
     let table = db.open_table(table_name).execute().await?;
-    let safe_path = file_path.replace('\'', "''");
-    table.delete(&format!("path = '{}'", safe_path)).await?;
+    let key = db::FileKey::new(root, file_path);
+    db::delete_files(&table, std::slice::from_ref(&key)).await?;
+    if archive::is_archive_path(Path::new(file_path)) {
+        let prefix = archive::virtual_path(&key.rel_path, "");
+        table
+            .delete(&format!(
+                "root_id = '{}' AND starts_with(rel_path, '{}')",
+                key.root_id.replace('\'', "''"),
+                prefix.replace('\'', "''")
+            ))
+            .await?;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streamed_chunks_are_emitted_per_segment() {
+        let path = std::env::temp_dir().join("rememex_stream_chunks_test.log");
+        let line = "2024-05-01 12:00:00 INFO request served in 12ms\n";
+        let limits = file_io::StreamLimits {
+            threshold: 0,
+            segment_bytes: 64 * 1024,
+        };
+        std::fs::write(&path, line.repeat(160 * 1024 / line.len())).unwrap();
+
+        let mut emitted: Vec<Vec<(String, db::ChunkMeta)>> = Vec::new();
+        let sizing = chunking::ChunkSizing::default();
+        let total = stream_chunks(&path, &IndexingConfig::default(), &sizing, &limits, |chunks| emitted.push(chunks)).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(emitted.len(), 3);
+        assert_eq!(emitted.iter().map(Vec::len).sum::<usize>(), total);
+        assert!(emitted.iter().all(|chunks| chunks.len() < total / 2));
+        let metas: Vec<&db::ChunkMeta> = emitted.iter().flatten().map(|(_, meta)| meta).collect();
+        assert!(metas.iter().enumerate().all(|(i, meta)| meta.chunk_index == i));
+        assert!(metas.windows(2).all(|w| w[1].start_byte >= w[0].start_byte));
+        assert!(metas.iter().all(|meta| meta.encoding.as_deref() == Some("UTF-8")));
+    }
+
+    #[test]
+    fn test_parent_text_is_stored_once_per_section() {
+        let sizing = chunking::ChunkSizing {
+            parent_chunks: true,
+            ..Default::default()
+        };
+        let md = format!("# Install\n\n{}", "Download the AppImage and mark it executable.\n".repeat(14));
+        let chunks = build_chunks(&md, Path::new("README.md"), md.len() as u64, None, &sizing);
+        let parents: Vec<&db::ParentSection> = chunks.iter().filter_map(|(_, meta)| meta.parent.as_ref()).collect();
+        assert!(parents.len() > 2);
+        assert_eq!(&md[parents[0].start_byte..parents[0].end_byte], parents[0].text);
+        assert!(parents[1..].iter().all(|p| p.text.is_empty() && p.start_byte == parents[0].start_byte));
+    }
+
+    #[test]
+    fn test_walk_skips_nested_roots() {
+        let root = std::env::temp_dir().join(format!("rememex_nested_roots_{}", std::process::id()));
+        let vendor = root.join("vendor");
+        std::fs::create_dir_all(&vendor).unwrap();
+        std::fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(vendor.join("x.rs"), "fn x() {}").unwrap();
+
+        let root_dir = root.to_string_lossy().to_string();
+        let all: Vec<PathBuf> = walk_files(&root_dir, &[]).collect();
+        let outer: Vec<PathBuf> = walk_files(&root_dir, std::slice::from_ref(&vendor)).collect();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(all, vec![root.join("main.rs"), vendor.join("x.rs")]);
+        assert_eq!(outer, vec![root.join("main.rs")]);
+    }
+}
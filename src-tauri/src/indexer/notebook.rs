@@ -0,0 +1,124 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+pub const CELL_MARKER: &str = "# %% [";
+
+const MAX_OUTPUT_BYTES: usize = 2000;
+
+fn joined(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(parts)) => parts.iter().filter_map(|p| p.as_str()).collect(),
+        _ => String::new(),
+    }
+}
+
+fn truncate(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+fn output_text(output: &Value) -> Option<String> {
+    let text = match output.get("output_type").and_then(|t| t.as_str())? {
+        "stream" => joined(output.get("text")),
+        "execute_result" | "display_data" | "pyout" => {
+            let data = output.get("data").unwrap_or(output);
+            if let Some(plain) = data.get("text/plain").or_else(|| data.get("text")) {
+                joined(Some(plain))
+            } else if let Some(markdown) = data.get("text/markdown") {
+                joined(Some(markdown))
+            } else if let Some(html) = data.get("text/html") {
+                super::markup::html_text(&joined(Some(html)))
+            } else {
+                return None;
+            }
+        }
+        "error" | "pyerr" => {
+            let name = output.get("ename").and_then(|v| v.as_str()).unwrap_or("Error");
+            let value = output.get("evalue").and_then(|v| v.as_str()).unwrap_or("");
+            format!("{}: {}", name, value)
+        }
+        _ => return None,
+    };
+    let text = truncate(text.trim_end(), MAX_OUTPUT_BYTES);
+    (!text.trim().is_empty()).then(|| text.to_string())
+}
+
+pub fn extract_text(json: &str, include_outputs: bool) -> Result<String> {
+    let notebook: Value = serde_json::from_str(json)?;
+    let cells = notebook
+        .get("cells")
+        .or_else(|| notebook.pointer("/worksheets/0/cells"))
+        .and_then(|c| c.as_array())
+        .ok_or_else(|| anyhow!("Notebook has no cells"))?;
+
+    let mut out = String::new();
+    for cell in cells {
+        let kind = cell.get("cell_type").and_then(|t| t.as_str()).unwrap_or("raw");
+        let source = joined(cell.get("source").or_else(|| cell.get("input")));
+        out.push_str(CELL_MARKER);
+        out.push_str(kind);
+        out.push_str("]\n");
+        out.push_str(source.trim_end());
+        out.push('\n');
+
+        if include_outputs && kind == "code" {
+            let outputs = cell.get("outputs").and_then(|o| o.as_array());
+            for text in outputs.into_iter().flatten().filter_map(output_text) {
+                out.push_str("\n# Output:\n");
+                out.push_str(&text);
+                out.push('\n');
+            }
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+pub fn cell_starts(text: &str) -> Vec<usize> {
+    let mut starts: Vec<usize> = Vec::new();
+    if text.starts_with(CELL_MARKER) {
+        starts.push(0);
+    }
+    let marker = format!("\n{}", CELL_MARKER);
+    starts.extend(text.match_indices(&marker).map(|(i, _)| i + 1));
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+        "cells": [
+            {"cell_type": "markdown", "source": ["# Analysis\n", "Load the data."]},
+            {"cell_type": "code", "source": "df = load()\ndf.head()", "outputs": [
+                {"output_type": "stream", "name": "stdout", "text": ["loaded 3 rows\n"]},
+                {"output_type": "display_data", "data": {"image/png": "iVBORw0KGgo="}},
+                {"output_type": "execute_result", "data": {"text/plain": ["   a  b\n", "0  1  2"]}}
+            ]},
+            {"cell_type": "code", "source": [], "outputs": []}
+        ],
+        "metadata": {}, "nbformat": 4, "nbformat_minor": 5
+    }"##;
+
+    #[test]
+    fn test_extract_cells_with_outputs() {
+        let text = extract_text(NOTEBOOK, true).unwrap();
+        assert!(text.starts_with("# %% [markdown]\n# Analysis\nLoad the data.\n\n# %% [code]\ndf = load()"));
+        assert!(text.contains("# Output:\nloaded 3 rows\n"));
+        assert!(text.contains("   a  b\n0  1  2"));
+        assert!(!text.contains("iVBOR"));
+        assert_eq!(cell_starts(&text).len(), 3);
+
+        let without = extract_text(NOTEBOOK, false).unwrap();
+        assert!(!without.contains("loaded 3 rows"));
+        assert!(extract_text("{\"metadata\": {}}", true).is_err());
+    }
+}
@@ -0,0 +1,454 @@
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek};
+
+use anyhow::{anyhow, Result};
+use calamine::{open_workbook_auto_from_rs, Data, Reader as _};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use zip::ZipArchive;
+
+const MAX_XML_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+
+pub fn is_office_extension(ext: &str) -> bool {
+    matches!(
+        ext,
+        "docx" | "odt" | "xlsx" | "xlsm" | "xls" | "ods" | "pptx" | "odp"
+    )
+}
+
+pub fn extract_text(data: &[u8], ext: &str) -> Result<String> {
+    match ext {
+        "xlsx" | "xlsm" | "xls" | "ods" => workbook_text(Cursor::new(data)),
+        "docx" => docx_text(&mut open_archive(data)?),
+        "odt" => odt_text(&read_entry(&mut open_archive(data)?, "content.xml")?),
+        "pptx" => pptx_text(&mut open_archive(data)?),
+        "odp" => odp_text(&read_entry(&mut open_archive(data)?, "content.xml")?),
+        _ => Err(anyhow!("Unsupported office format: {}", ext)),
+    }
+}
+
+fn open_archive(data: &[u8]) -> Result<ZipArchive<Cursor<&[u8]>>> {
+    Ok(ZipArchive::new(Cursor::new(data))?)
+}
+
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Result<Vec<u8>> {
+    let entry = archive.by_name(name)?;
+    if entry.size() > MAX_XML_ENTRY_SIZE {
+        return Err(anyhow!("{} is too large to extract", name));
+    }
+    let mut data = Vec::with_capacity(entry.size() as usize);
+    entry.take(MAX_XML_ENTRY_SIZE).read_to_end(&mut data)?;
+    Ok(data)
+}
+
+enum Node<'a> {
+    Start(&'a BytesStart<'a>),
+    Empty(&'a BytesStart<'a>),
+    End(&'a [u8]),
+    Text(&'a str),
+}
+
+fn walk_xml(xml: &[u8], mut visit: impl FnMut(Node)) -> Result<()> {
+    let mut reader = Reader::from_reader(xml);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => visit(Node::Start(&e)),
+            Event::Empty(e) => visit(Node::Empty(&e)),
+            Event::End(e) => visit(Node::End(e.name().as_ref())),
+            Event::Text(t) => visit(Node::Text(&t.unescape()?)),
+            Event::CData(t) => visit(Node::Text(&String::from_utf8_lossy(&t))),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn attr(e: &BytesStart, name: &str) -> Option<String> {
+    e.try_get_attribute(name)
+        .ok()
+        .flatten()
+        .and_then(|a| a.unescape_value().ok())
+        .map(|v| v.to_string())
+}
+
+fn push_paragraph(out: &mut String, para: &mut String, heading: usize) {
+    let text = para.trim();
+    if !text.is_empty() {
+        if heading > 0 {
+            out.push_str(&"#".repeat(heading.min(6)));
+            out.push(' ');
+        }
+        out.push_str(text);
+        out.push_str("\n\n");
+    }
+    para.clear();
+}
+
+fn heading_level(style: &str) -> usize {
+    let style = style.to_lowercase();
+    if style == "title" {
+        return 1;
+    }
+    style
+        .strip_prefix("heading")
+        .and_then(|level| level.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+fn docx_paragraphs(xml: &[u8]) -> Result<String> {
+    let mut out = String::new();
+    let mut para = String::new();
+    let mut heading = 0;
+    let mut in_text = false;
+    walk_xml(xml, |node| match node {
+        Node::Start(e) if e.name().as_ref() == b"w:t" => in_text = true,
+        Node::End(b"w:t") => in_text = false,
+        Node::Text(t) if in_text => para.push_str(t),
+        Node::Start(e) | Node::Empty(e) => match e.name().as_ref() {
+            b"w:tab" => para.push('\t'),
+            b"w:br" | b"w:cr" => para.push('\n'),
+            b"w:pStyle" => heading = attr(e, "w:val").map(|s| heading_level(&s)).unwrap_or(0),
+            _ => {}
+        },
+        Node::End(b"w:p") => {
+            push_paragraph(&mut out, &mut para, heading);
+            heading = 0;
+        }
+        _ => {}
+    })?;
+    Ok(out)
+}
+
+fn docx_text<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<String> {
+    docx_paragraphs(&read_entry(archive, "word/document.xml")?)
+}
+
+fn odf_inline(e: &BytesStart, para: &mut String) {
+    match e.name().as_ref() {
+        b"text:s" => {
+            let count = attr(e, "text:c").and_then(|c| c.parse().ok()).unwrap_or(1);
+            para.push_str(&" ".repeat(count));
+        }
+        b"text:tab" => para.push('\t'),
+        b"text:line-break" => para.push('\n'),
+        _ => {}
+    }
+}
+
+fn odt_text(xml: &[u8]) -> Result<String> {
+    let mut out = String::new();
+    let mut para = String::new();
+    let mut heading = 0;
+    let mut depth = 0usize;
+    walk_xml(xml, |node| match node {
+        Node::Start(e) => match e.name().as_ref() {
+            b"text:p" => depth += 1,
+            b"text:h" => {
+                depth += 1;
+                heading = attr(e, "text:outline-level")
+                    .and_then(|l| l.parse().ok())
+                    .unwrap_or(1);
+            }
+            _ => {}
+        },
+        Node::Empty(e) if depth > 0 => odf_inline(e, &mut para),
+        Node::Text(t) if depth > 0 => para.push_str(t),
+        Node::End(b"text:p" | b"text:h") => {
+            depth = depth.saturating_sub(1);
+            push_paragraph(&mut out, &mut para, heading);
+            heading = 0;
+        }
+        _ => {}
+    })?;
+    Ok(out)
+}
+
+fn workbook_text<RS: Read + Seek + Clone>(data: RS) -> Result<String> {
+    let mut workbook = open_workbook_auto_from_rs(data)?;
+    let mut out = String::new();
+    for name in workbook.sheet_names() {
+        let Ok(range) = workbook.worksheet_range(&name) else {
+            continue;
+        };
+        let rows: Vec<String> = range.rows().filter_map(row_text).collect();
+        push_sheet(&mut out, &name, &rows);
+    }
+    Ok(out)
+}
+
+fn row_text(row: &[Data]) -> Option<String> {
+    let cells: Vec<String> = row.iter().map(|c| c.to_string().trim().to_string()).collect();
+    let last = cells.iter().rposition(|c| !c.is_empty())?;
+    Some(cells[..=last].join(" | "))
+}
+
+fn push_sheet(out: &mut String, name: &str, rows: &[String]) {
+    if rows.is_empty() {
+        return;
+    }
+    out.push_str(&format!("## Sheet: {}\n", name));
+    for row in rows {
+        out.push_str(row);
+        out.push('\n');
+    }
+    out.push('\n');
+}
+
+fn push_slide(out: &mut String, number: usize, lines: &[String], notes: &[String]) {
+    if lines.is_empty() && notes.is_empty() {
+        return;
+    }
+    out.push_str(&format!("## Slide {}\n", number));
+    for line in lines {
+        out.push_str(line);
+        out.push('\n');
+    }
+    if !notes.is_empty() {
+        out.push_str("\nNotes:\n");
+        for line in notes {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out.push('\n');
+}
+
+fn read_rels<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> HashMap<String, (String, String)> {
+    let mut rels = HashMap::new();
+    let Ok(xml) = read_entry(archive, name) else {
+        return rels;
+    };
+    let _ = walk_xml(&xml, |node| {
+        if let Node::Start(e) | Node::Empty(e) = node {
+            if e.local_name().as_ref() == b"Relationship" {
+                if let (Some(id), Some(kind), Some(target)) =
+                    (attr(e, "Id"), attr(e, "Type"), attr(e, "Target"))
+                {
+                    rels.insert(id, (kind, target));
+                }
+            }
+        }
+    });
+    rels
+}
+
+fn resolve_target(base_dir: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+    let mut parts: Vec<&str> = base_dir.split('/').filter(|p| !p.is_empty()).collect();
+    for part in target.split('/') {
+        match part {
+            ".." => {
+                parts.pop();
+            }
+            "." | "" => {}
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+fn slide_paragraphs(xml: &[u8], body_only: bool) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    let mut shape: Vec<String> = Vec::new();
+    let mut para = String::new();
+    let mut in_text = false;
+    let mut skip_shape = false;
+    walk_xml(xml, |node| match node {
+        Node::Start(e) if e.name().as_ref() == b"a:t" => in_text = true,
+        Node::End(b"a:t") => in_text = false,
+        Node::Text(t) if in_text => para.push_str(t),
+        Node::Start(e) | Node::Empty(e) => match e.name().as_ref() {
+            b"p:sp" => skip_shape = false,
+            b"a:br" => para.push('\n'),
+            b"p:ph" if body_only => {
+                skip_shape = attr(e, "type").is_none_or(|t| t != "body");
+            }
+            _ => {}
+        },
+        Node::End(b"a:p") => {
+            let text = para.trim();
+            if !text.is_empty() {
+                shape.push(text.to_string());
+            }
+            para.clear();
+        }
+        Node::End(b"p:sp" | b"p:graphicFrame") => {
+            if !skip_shape {
+                lines.append(&mut shape);
+            }
+            shape.clear();
+            skip_shape = false;
+        }
+        _ => {}
+    })?;
+    lines.append(&mut shape);
+    Ok(lines)
+}
+
+fn pptx_slide_order<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Vec<String> {
+    let rels = read_rels(archive, "ppt/_rels/presentation.xml.rels");
+    let mut ordered = Vec::new();
+    if let Ok(xml) = read_entry(archive, "ppt/presentation.xml") {
+        let _ = walk_xml(&xml, |node| {
+            if let Node::Start(e) | Node::Empty(e) = node {
+                if e.name().as_ref() == b"p:sldId" {
+                    if let Some((_, target)) = attr(e, "r:id").and_then(|id| rels.get(&id)) {
+                        ordered.push(resolve_target("ppt", target));
+                    }
+                }
+            }
+        });
+    }
+    if !ordered.is_empty() {
+        return ordered;
+    }
+
+    let mut numbered: Vec<(usize, String)> = archive
+        .file_names()
+        .filter_map(|name| {
+            let number = name
+                .strip_prefix("ppt/slides/slide")?
+                .strip_suffix(".xml")?
+                .parse()
+                .ok()?;
+            Some((number, name.to_string()))
+        })
+        .collect();
+    numbered.sort();
+    numbered.into_iter().map(|(_, name)| name).collect()
+}
+
+fn pptx_text<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<String> {
+    let mut out = String::new();
+    for (i, slide) in pptx_slide_order(archive).into_iter().enumerate() {
+        let Ok(xml) = read_entry(archive, &slide) else {
+            continue;
+        };
+        let lines = slide_paragraphs(&xml, false)?;
+
+        let (dir, file) = slide.rsplit_once('/').unwrap_or(("", slide.as_str()));
+        let notes_entry = read_rels(archive, &format!("{}/_rels/{}.rels", dir, file))
+            .into_values()
+            .find(|(kind, _)| kind.ends_with("/notesSlide"))
+            .map(|(_, target)| resolve_target(dir, &target));
+        let notes = match notes_entry.and_then(|entry| read_entry(archive, &entry).ok()) {
+            Some(xml) => slide_paragraphs(&xml, true)?,
+            None => Vec::new(),
+        };
+
+        push_slide(&mut out, i + 1, &lines, &notes);
+    }
+    Ok(out)
+}
+
+fn odp_text(xml: &[u8]) -> Result<String> {
+    let mut out = String::new();
+    let mut lines: Vec<String> = Vec::new();
+    let mut notes: Vec<String> = Vec::new();
+    let mut para = String::new();
+    let mut slide = 0usize;
+    let mut depth = 0usize;
+    let mut in_notes = false;
+    walk_xml(xml, |node| match node {
+        Node::Start(e) => match e.name().as_ref() {
+            b"draw:page" => slide += 1,
+            b"presentation:notes" => in_notes = true,
+            b"text:p" | b"text:h" => depth += 1,
+            _ => {}
+        },
+        Node::Empty(e) if e.name().as_ref() == b"draw:page" => slide += 1,
+        Node::Empty(e) if depth > 0 => odf_inline(e, &mut para),
+        Node::Text(t) if depth > 0 => para.push_str(t),
+        Node::End(b"text:p" | b"text:h") => {
+            depth = depth.saturating_sub(1);
+            let text = para.trim();
+            if !text.is_empty() {
+                let target = if in_notes { &mut notes } else { &mut lines };
+                target.push(text.to_string());
+            }
+            para.clear();
+        }
+        Node::End(b"presentation:notes") => in_notes = false,
+        Node::End(b"draw:page") => {
+            push_slide(&mut out, slide, &lines, &notes);
+            lines.clear();
+            notes.clear();
+        }
+        _ => {}
+    })?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    #[test]
+    fn test_docx_paragraphs_and_headings() {
+        let xml = br#"<w:document><w:body>
+            <w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Design</w:t></w:r></w:p>
+            <w:p><w:r><w:t xml:space="preserve">Cache &amp; </w:t></w:r><w:r><w:t>index</w:t></w:r></w:p>
+            <w:p></w:p>
+            <w:p><w:r><w:t>Second</w:t><w:tab/><w:t>para</w:t></w:r></w:p>
+        </w:body></w:document>"#;
+        let text = docx_paragraphs(xml).unwrap();
+        assert_eq!(text, "# Design\n\nCache & index\n\nSecond\tpara\n\n");
+    }
+
+    #[test]
+    fn test_pptx_slides_in_presentation_order_with_notes() {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let files: &[(&str, &str)] = &[
+            (
+                "ppt/presentation.xml",
+                r#"<p:presentation><p:sldIdLst><p:sldId id="256" r:id="rId3"/><p:sldId id="257" r:id="rId2"/></p:sldIdLst></p:presentation>"#,
+            ),
+            (
+                "ppt/_rels/presentation.xml.rels",
+                r#"<Relationships><Relationship Id="rId2" Type="http://schemas/slide" Target="slides/slide1.xml"/><Relationship Id="rId3" Type="http://schemas/slide" Target="slides/slide2.xml"/></Relationships>"#,
+            ),
+            (
+                "ppt/slides/slide1.xml",
+                r#"<p:sld><p:sp><p:txBody><a:p><a:r><a:t>Roadmap</a:t></a:r></a:p></p:txBody></p:sp></p:sld>"#,
+            ),
+            (
+                "ppt/slides/slide2.xml",
+                r#"<p:sld><p:sp><p:txBody><a:p><a:r><a:t>Intro</a:t></a:r></a:p></p:txBody></p:sp></p:sld>"#,
+            ),
+            (
+                "ppt/slides/_rels/slide1.xml.rels",
+                r#"<Relationships><Relationship Id="rId1" Type="http://schemas/notesSlide" Target="../notesSlides/notesSlide7.xml"/></Relationships>"#,
+            ),
+            (
+                "ppt/notesSlides/notesSlide7.xml",
+                r#"<p:notes><p:sp><p:nvSpPr><p:nvPr><p:ph type="sldNum"/></p:nvPr></p:nvSpPr><p:txBody><a:p><a:r><a:t>2</a:t></a:r></a:p></p:txBody></p:sp><p:sp><p:nvSpPr><p:nvPr><p:ph type="body"/></p:nvPr></p:nvSpPr><p:txBody><a:p><a:r><a:t>Mention Q3</a:t></a:r></a:p></p:txBody></p:sp></p:notes>"#,
+            ),
+        ];
+        for (name, body) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(body.as_bytes()).unwrap();
+        }
+        let mut archive = ZipArchive::new(zip.finish().unwrap()).unwrap();
+
+        let text = pptx_text(&mut archive).unwrap();
+        assert_eq!(
+            text,
+            "## Slide 1\nIntro\n\n## Slide 2\nRoadmap\n\nNotes:\nMention Q3\n\n"
+        );
+    }
+
+    #[test]
+    fn test_odf_text_and_slides() {
+        let odt = br#"<office:text><text:h text:outline-level="2">Scope</text:h><text:p>a<text:s text:c="2"/>b<text:span> c</text:span></text:p></office:text>"#;
+        assert_eq!(odt_text(odt).unwrap(), "## Scope\n\na  b c\n\n");
+
+        let odp = br#"<office:presentation><draw:page draw:name="p1"><draw:frame><draw:text-box><text:p>Budget</text:p></draw:text-box></draw:frame><presentation:notes><draw:frame><draw:text-box><text:p>Ask finance</text:p></draw:text-box></draw:frame></presentation:notes></draw:page><draw:page draw:name="p2"/></office:presentation>"#;
+        assert_eq!(odp_text(odp).unwrap(), "## Slide 1\nBudget\n\nNotes:\nAsk finance\n\n");
+    }
+}
@@ -1,15 +1,20 @@
 use log::debug;
 use serde::Serialize;
 
+use super::db::ChunkMeta;
+use super::search::SearchHit;
+
 #[derive(Serialize, Clone)]
 pub struct ScoredResult {
     pub path: String,
     pub snippet: String,
     pub score: f32,
+    #[serde(flatten)]
+    pub meta: Option<ChunkMeta>,
 }
 
 pub fn score_results(
-    final_results: Vec<(String, String, f32)>,
+    final_results: Vec<SearchHit>,
     used_reranker: bool,
     used_hybrid: bool,
     max_results: usize,
@@ -25,7 +30,7 @@ pub fn score_results(
     let mut scored: Vec<ScoredResult> = if used_reranker {
         final_results
             .into_iter()
-            .map(|(path, snippet, raw_score)| {
+            .map(|(path, snippet, raw_score, meta)| {
                 let sigmoid = 1.0 / (1.0 + (-raw_score).exp());
                 let score = sigmoid * 100.0;
                 debug!(
@@ -36,14 +41,15 @@ pub fn score_results(
                     path,
                     snippet,
                     score,
+                    meta,
                 }
             })
             .collect()
     } else if used_hybrid {
-        let max_rrf = final_results.first().map(|(_, _, s)| *s).unwrap_or(1.0);
+        let max_rrf = final_results.first().map(|(_, _, s, _)| *s).unwrap_or(1.0);
         final_results
             .into_iter()
-            .map(|(path, snippet, rrf_score)| {
+            .map(|(path, snippet, rrf_score, meta)| {
                 let pct = if max_rrf > 0.0 {
                     (rrf_score / max_rrf) * 100.0
                 } else {
@@ -53,18 +59,20 @@ pub fn score_results(
                     path,
                     snippet,
                     score: pct,
+                    meta,
                 }
             })
             .collect()
     } else {
         final_results
             .into_iter()
-            .map(|(path, snippet, cosine_dist)| {
+            .map(|(path, snippet, cosine_dist, meta)| {
                 let similarity = (1.0 - cosine_dist).clamp(0.0, 1.0);
                 ScoredResult {
                     path,
                     snippet,
                     score: similarity * 100.0,
+                    meta,
                 }
             })
             .collect()
@@ -92,6 +100,62 @@ pub fn score_results(
     scored
 }
 
+pub fn expand_parents(hits: Vec<SearchHit>) -> Vec<SearchHit> {
+    let mut seen = std::collections::HashSet::new();
+    hits.into_iter()
+        .filter_map(|(path, snippet, score, meta)| {
+            let Some(mut meta) = meta else {
+                return Some((path, snippet, score, None));
+            };
+            let Some(parent) = meta.parent.take().filter(|p| !p.text.is_empty()) else {
+                return Some((path, snippet, score, Some(meta)));
+            };
+            if !seen.insert((path.clone(), parent.start_byte)) {
+                return None;
+            }
+            meta.match_start_line = Some(meta.start_line);
+            meta.match_end_line = Some(meta.end_line);
+            meta.start_line = parent.start_line;
+            meta.end_line = parent.end_line;
+            meta.start_byte = parent.start_byte;
+            meta.end_byte = parent.end_byte;
+            Some((path, parent.text, score, Some(meta)))
+        })
+        .collect()
+}
+
+pub fn clip_snippet(result: &mut ScoredResult, max_bytes: usize) {
+    let len = result.snippet.len();
+    if len <= max_bytes {
+        return;
+    }
+    let match_offset = result
+        .meta
+        .as_ref()
+        .and_then(|m| m.match_start_line.map(|line| line.saturating_sub(m.start_line)))
+        .and_then(|skip| match skip {
+            0 => Some(0),
+            n => result.snippet.match_indices('\n').nth(n - 1).map(|(i, _)| i + 1),
+        })
+        .unwrap_or(0);
+
+    let mut start = match_offset.min(len - max_bytes);
+    if start > 0 {
+        start = result.snippet[start - 1..]
+            .find('\n')
+            .map_or(match_offset, |i| start + i);
+    }
+    let mut end = (start + max_bytes).min(len);
+    while !result.snippet.is_char_boundary(end) {
+        end -= 1;
+    }
+    if let Some(meta) = result.meta.as_mut().filter(|_| start > 0) {
+        meta.start_line += result.snippet[..start].matches('\n').count();
+        meta.start_byte += start;
+    }
+    result.snippet = result.snippet[start..end].to_string();
+}
+
 fn snippet_similarity(a: &str, b: &str) -> f32 {
     let set_a: std::collections::HashSet<&str> = a.split_whitespace().collect();
     let set_b: std::collections::HashSet<&str> = b.split_whitespace().collect();
@@ -162,6 +226,7 @@ pub fn mmr_select(candidates: Vec<ScoredResult>, k: usize, lambda: f32) -> Vec<S
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::indexer::db::ParentSection;
 
     #[test]
     fn test_mmr_preserves_order_with_lambda_one() {
@@ -170,16 +235,19 @@ mod tests {
                 path: "a".into(),
                 snippet: "hello world foo".into(),
                 score: 90.0,
+                meta: None,
             },
             ScoredResult {
                 path: "b".into(),
                 snippet: "hello world bar".into(),
                 score: 80.0,
+                meta: None,
             },
             ScoredResult {
                 path: "c".into(),
                 snippet: "completely different text".into(),
                 score: 70.0,
+                meta: None,
             },
         ];
         let result = mmr_select(candidates, 3, 1.0);
@@ -195,16 +263,19 @@ mod tests {
                 path: "a".into(),
                 snippet: "hello world foo bar baz".into(),
                 score: 90.0,
+                meta: None,
             },
             ScoredResult {
                 path: "b".into(),
                 snippet: "hello world foo bar qux".into(),
                 score: 85.0,
+                meta: None,
             },
             ScoredResult {
                 path: "c".into(),
                 snippet: "completely different unique text here".into(),
                 score: 70.0,
+                meta: None,
             },
         ];
         let result = mmr_select(candidates, 3, 0.5);
@@ -241,11 +312,13 @@ mod tests {
                 path: "a".into(),
                 snippet: "one".into(),
                 score: 90.0,
+                meta: None,
             },
             ScoredResult {
                 path: "b".into(),
                 snippet: "two".into(),
                 score: 80.0,
+                meta: None,
             },
         ];
         let result = mmr_select(candidates, 10, 0.7);
@@ -258,6 +331,7 @@ mod tests {
             path: "only".into(),
             snippet: "solo".into(),
             score: 50.0,
+            meta: None,
         }];
         let result = mmr_select(candidates, 5, 0.7);
         assert_eq!(result.len(), 1);
@@ -271,16 +345,19 @@ mod tests {
                 path: "a".into(),
                 snippet: "hello world foo bar baz".into(),
                 score: 95.0,
+                meta: None,
             },
             ScoredResult {
                 path: "b".into(),
                 snippet: "hello world foo bar qux".into(),
                 score: 90.0,
+                meta: None,
             },
             ScoredResult {
                 path: "c".into(),
                 snippet: "completely unique different text here".into(),
                 score: 60.0,
+                meta: None,
             },
         ];
         let result = mmr_select(candidates, 2, 0.0);
@@ -291,8 +368,8 @@ mod tests {
     #[test]
     fn test_score_results_hybrid_normalization() {
         let input = vec![
-            ("top.rs".into(), "best".into(), 0.032f32),
-            ("mid.rs".into(), "medium".into(), 0.016f32),
+            ("top.rs".into(), "best".into(), 0.032f32, None),
+            ("mid.rs".into(), "medium".into(), 0.016f32, None),
         ];
         let scored = score_results(input, false, true, 10);
         assert_eq!(scored.len(), 2);
@@ -311,8 +388,8 @@ mod tests {
     #[test]
     fn test_score_results_reranker_sigmoid() {
         let input = vec![
-            ("good.rs".into(), "good".into(), 5.0f32),
-            ("bad.rs".into(), "bad".into(), -5.0f32),
+            ("good.rs".into(), "good".into(), 5.0f32, None),
+            ("bad.rs".into(), "bad".into(), -5.0f32, None),
         ];
         let scored = score_results(input, true, false, 10);
         assert!(
@@ -329,8 +406,8 @@ mod tests {
     #[test]
     fn test_score_results_vector_only() {
         let input = vec![
-            ("close.rs".into(), "close".into(), 0.1f32),
-            ("far.rs".into(), "far".into(), 0.8f32),
+            ("close.rs".into(), "close".into(), 0.1f32, None),
+            ("far.rs".into(), "far".into(), 0.8f32, None),
         ];
         let scored = score_results(input, false, false, 10);
         assert_eq!(scored[0].path, "close.rs");
@@ -343,4 +420,69 @@ mod tests {
         let scored = score_results(vec![], false, true, 10);
         assert!(scored.is_empty());
     }
+
+    #[test]
+    fn test_score_results_flattens_chunk_meta() {
+        let meta = ChunkMeta {
+            start_line: 12,
+            end_line: 30,
+            language: "rust".into(),
+            page: Some(37),
+            ..Default::default()
+        };
+        let input = vec![
+            ("lib.rs".into(), "fn main()".into(), 0.1f32, Some(meta)),
+            ("[annotation]".into(), "note".into(), 0.2f32, None),
+        ];
+        let scored = score_results(input, false, false, 10);
+        let json = serde_json::to_value(&scored).unwrap();
+        assert_eq!(json[0]["start_line"], 12);
+        assert_eq!(json[0]["language"], "rust");
+        assert_eq!(json[0]["page"], 37);
+        assert!(json[1].get("start_line").is_none());
+    }
+
+    #[test]
+    fn test_parents_replace_child_snippets_and_clip_around_the_match() {
+        let section: String = (1..=40).map(|i| format!("line {:02} of the section\n", i)).collect();
+        let child = |start_line: usize, start_byte: usize| ChunkMeta {
+            start_line,
+            end_line: start_line + 2,
+            start_byte,
+            end_byte: start_byte + 69,
+            parent: Some(ParentSection {
+                text: section.clone(),
+                start_line: 11,
+                end_line: 50,
+                start_byte: 230,
+                end_byte: 230 + section.len(),
+            }),
+            ..Default::default()
+        };
+        let hits = vec![
+            ("a.md".into(), "line 31".into(), 0.1f32, Some(child(41, 920))),
+            ("a.md".into(), "line 02".into(), 0.2f32, Some(child(12, 253))),
+            ("b.md".into(), "plain".into(), 0.3f32, Some(ChunkMeta::default())),
+            ("c.md".into(), "orphan".into(), 0.4f32, Some(ChunkMeta {
+                parent: Some(ParentSection { end_byte: 90, ..Default::default() }),
+                ..Default::default()
+            })),
+        ];
+        let expanded = expand_parents(hits);
+        assert_eq!(expanded.len(), 3);
+        assert_eq!(expanded[2].1, "orphan");
+        assert_eq!(expanded[0].1, section);
+        let meta = expanded[0].3.clone().unwrap();
+        assert_eq!((meta.start_line, meta.end_line), (11, 50));
+        assert_eq!((meta.match_start_line, meta.match_end_line), (Some(41), Some(43)));
+        assert_eq!(expanded[1].1, "plain");
+
+        let mut scored = score_results(expanded, false, false, 10);
+        clip_snippet(&mut scored[0], 100);
+        assert!(scored[0].snippet.starts_with("line 31 of the section\n"));
+        assert!(scored[0].snippet.len() <= 100);
+        let meta = scored[0].meta.as_ref().unwrap();
+        assert_eq!(meta.start_line, 41);
+        assert_eq!(meta.start_byte, 230 + 30 * 23);
+    }
 }
@@ -0,0 +1,400 @@
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use arrow_array::{Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::table::NewColumnTransform;
+use lancedb::Table;
+use log::{debug, info};
+use serde::Serialize;
+
+const VERSIONS_TABLE: &str = "schema_versions";
+
+struct Migration {
+    version: i64,
+    columns: &'static [(&'static str, &'static str)],
+    backfill: Option<Backfill>,
+}
+
+struct Backfill {
+    filter: &'static str,
+    column: &'static str,
+    value: &'static str,
+}
+
+const CHUNK_MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        columns: &[("mtime", "CAST(0 AS BIGINT)")],
+        backfill: None,
+    },
+    Migration {
+        version: 2,
+        columns: &[("file_hash", "''"), ("chunk_hash", "''")],
+        backfill: None,
+    },
+    Migration {
+        version: 3,
+        columns: &[
+            ("start_line", "CAST(0 AS BIGINT)"),
+            ("end_line", "CAST(0 AS BIGINT)"),
+            ("start_byte", "CAST(0 AS BIGINT)"),
+            ("end_byte", "CAST(0 AS BIGINT)"),
+            ("chunk_index", "CAST(0 AS BIGINT)"),
+            ("language", "''"),
+            ("file_size", "CAST(0 AS BIGINT)"),
+        ],
+        backfill: None,
+    },
+    Migration {
+        version: 4,
+        columns: &[],
+        backfill: Some(Backfill {
+            filter: "end_byte = 0",
+            column: "chunk_index",
+            value: "CAST(-1 AS BIGINT)",
+        }),
+    },
+    Migration {
+        version: 5,
+        columns: &[("root_id", "''"), ("rel_path", "''")],
+        backfill: None,
+    },
+    Migration {
+        version: 6,
+        columns: &[("page", "CAST(0 AS BIGINT)")],
+        backfill: None,
+    },
+    Migration {
+        version: 7,
+        columns: &[("cell", "CAST(0 AS BIGINT)")],
+        backfill: None,
+    },
+    Migration {
+        version: 8,
+        columns: &[("encoding", "''")],
+        backfill: None,
+    },
+    Migration {
+        version: 9,
+        columns: &[("symbol", "''"), ("symbol_kind", "''")],
+        backfill: None,
+    },
+    Migration {
+        version: 10,
+        columns: &[("breadcrumb", "''")],
+        backfill: None,
+    },
+    Migration {
+        version: 11,
+        columns: &[
+            ("parent", "''"),
+            ("parent_start_line", "CAST(0 AS BIGINT)"),
+            ("parent_end_line", "CAST(0 AS BIGINT)"),
+            ("parent_start_byte", "CAST(0 AS BIGINT)"),
+            ("parent_end_byte", "CAST(0 AS BIGINT)"),
+        ],
+        backfill: None,
+    },
+];
+
+const ANNOTATION_MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    columns: &[("source", "'user'")],
+    backfill: None,
+}];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableKind {
+    Chunks,
+    Annotations,
+}
+
+impl TableKind {
+    fn migrations(self) -> &'static [Migration] {
+        match self {
+            TableKind::Chunks => CHUNK_MIGRATIONS,
+            TableKind::Annotations => ANNOTATION_MIGRATIONS,
+        }
+    }
+
+    pub fn current_version(self) -> i64 {
+        self.migrations().last().map(|m| m.version).unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RebuildRequired {
+    pub table: String,
+    pub reason: String,
+}
+
+impl fmt::Display for RebuildRequired {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Index '{}' needs a rebuild: {}. Confirm the rebuild to re-embed it.",
+            self.table, self.reason
+        )
+    }
+}
+
+impl std::error::Error for RebuildRequired {}
+
+pub fn check_dimension(schema: &Schema, table_name: &str, dim: usize) -> Result<()> {
+    let size = match schema.field_with_name("vector").map(|f| f.data_type()) {
+        Ok(DataType::FixedSizeList(_, size)) => *size,
+        _ => {
+            return Err(anyhow!(RebuildRequired {
+                table: table_name.to_string(),
+                reason: "the vector column is missing".to_string(),
+            }))
+        }
+    };
+    if size != dim as i32 {
+        return Err(anyhow!(RebuildRequired {
+            table: table_name.to_string(),
+            reason: format!("embedding dimension changed from {} to {}", size, dim),
+        }));
+    }
+    Ok(())
+}
+
+fn infer_version(schema: &Schema, kind: TableKind) -> i64 {
+    kind.migrations()
+        .iter()
+        .filter(|m| !m.columns.is_empty())
+        .take_while(|m| m.columns.iter().all(|(c, _)| schema.field_with_name(c).is_ok()))
+        .last()
+        .map(|m| m.version)
+        .unwrap_or(0)
+}
+
+pub async fn migrate(db: &Connection, table: &Table, table_name: &str, kind: TableKind) -> Result<i64> {
+    let current = kind.current_version();
+    let stored = get_version(db, table_name).await?;
+    if stored == Some(current) {
+        return Ok(current);
+    }
+
+    let schema = table.schema().await?;
+    let from = stored.unwrap_or_else(|| infer_version(&schema, kind));
+    if from > current {
+        return Err(anyhow!(
+            "Table '{}' has schema version {} but this build supports up to {}",
+            table_name,
+            from,
+            current
+        ));
+    }
+
+    for migration in kind.migrations().iter().filter(|m| m.version > from) {
+        let missing: Vec<(String, String)> = migration
+            .columns
+            .iter()
+            .filter(|(c, _)| schema.field_with_name(c).is_err())
+            .map(|(c, expr)| (c.to_string(), expr.to_string()))
+            .collect();
+        if !missing.is_empty() {
+            table
+                .add_columns(NewColumnTransform::SqlExpressions(missing), None)
+                .await?;
+        }
+        if let Some(backfill) = &migration.backfill {
+            table
+                .update()
+                .only_if(backfill.filter)
+                .column(backfill.column, backfill.value)
+                .execute()
+                .await?;
+        }
+        info!("Migrated '{}' to schema version {}", table_name, migration.version);
+    }
+
+    set_version(db, table_name, current).await?;
+    Ok(current)
+}
+
+fn versions_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("table_name", DataType::Utf8, false),
+        Field::new("version", DataType::Int64, false),
+    ]))
+}
+
+async fn open_versions_table(db: &Connection) -> Result<Table> {
+    if let Ok(table) = db.open_table(VERSIONS_TABLE).execute().await {
+        return Ok(table);
+    }
+    let table = db
+        .create_table(VERSIONS_TABLE, RecordBatchIterator::new(vec![], versions_schema()))
+        .execute()
+        .await?;
+    Ok(table)
+}
+
+pub async fn get_version(db: &Connection, table_name: &str) -> Result<Option<i64>> {
+    let table = match db.open_table(VERSIONS_TABLE).execute().await {
+        Ok(t) => t,
+        Err(_) => return Ok(None),
+    };
+    let safe_name = table_name.replace('\'', "''");
+    let results = table
+        .query()
+        .only_if(format!("table_name = '{}'", safe_name))
+        .select(lancedb::query::Select::Columns(vec!["version".to_string()]))
+        .limit(1)
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    for batch in results {
+        if let Some(versions) = batch
+            .column_by_name("version")
+            .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+        {
+            if batch.num_rows() > 0 {
+                return Ok(Some(versions.value(0)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+pub async fn set_version(db: &Connection, table_name: &str, version: i64) -> Result<()> {
+    let table = open_versions_table(db).await?;
+    let safe_name = table_name.replace('\'', "''");
+    table.delete(&format!("table_name = '{}'", safe_name)).await?;
+
+    let schema = versions_schema();
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(StringArray::from(vec![table_name])),
+            Arc::new(Int64Array::from(vec![version])),
+        ],
+    )?;
+    table
+        .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
+        .execute()
+        .await?;
+    debug!("Schema version of '{}' set to {}", table_name, version);
+    Ok(())
+}
+
+pub async fn drop_versioned_table(db: &Connection, table_name: &str) -> Result<()> {
+    let _ = db.drop_table(table_name, &[]).await;
+    if let Ok(table) = db.open_table(VERSIONS_TABLE).execute().await {
+        let safe_name = table_name.replace('\'', "''");
+        table.delete(&format!("table_name = '{}'", safe_name)).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::db;
+    use arrow_array::{FixedSizeListArray, Float32Array};
+
+    fn schema_with(columns: &[&str]) -> Schema {
+        let mut fields: Vec<Field> = columns
+            .iter()
+            .map(|c| Field::new(*c, DataType::Utf8, false))
+            .collect();
+        fields.push(Field::new(
+            "vector",
+            DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), 384),
+            false,
+        ));
+        Schema::new(fields)
+    }
+
+    #[test]
+    fn test_infer_version_from_columns() {
+        let v0 = schema_with(&["path", "content"]);
+        let v1 = schema_with(&["path", "content", "mtime"]);
+        let v2 = schema_with(&["path", "content", "mtime", "file_hash", "chunk_hash"]);
+        assert_eq!(infer_version(&v0, TableKind::Chunks), 0);
+        assert_eq!(infer_version(&v1, TableKind::Chunks), 1);
+        assert_eq!(infer_version(&v2, TableKind::Chunks), 2);
+        assert_eq!(infer_version(&v2, TableKind::Annotations), 0);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_rows_survive_migration() {
+        let dir = std::env::temp_dir().join(format!("rememex_schema_legacy_{}", std::process::id()));
+        let db = lancedb::connect(dir.to_str().unwrap()).execute().await.unwrap();
+        let item = Arc::new(Field::new("item", DataType::Float32, true));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("path", DataType::Utf8, false),
+            Field::new("content", DataType::Utf8, false),
+            Field::new("vector", DataType::FixedSizeList(item.clone(), 2), false),
+            Field::new("mtime", DataType::Int64, false),
+            Field::new("file_hash", DataType::Utf8, false),
+            Field::new("chunk_hash", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["/docs/a.txt"; 3])),
+                Arc::new(StringArray::from(vec!["one", "two", "three"])),
+                Arc::new(
+                    FixedSizeListArray::try_new(item, 2, Arc::new(Float32Array::from(vec![0.5; 6])), None)
+                        .unwrap(),
+                ),
+                Arc::new(Int64Array::from(vec![7; 3])),
+                Arc::new(StringArray::from(vec!["h"; 3])),
+                Arc::new(StringArray::from(vec!["", "", ""])),
+            ],
+        )
+        .unwrap();
+        let table = db
+            .create_table("c_Legacy", RecordBatchIterator::new(vec![Ok(batch)], schema))
+            .execute()
+            .await
+            .unwrap();
+        set_version(&db, "c_Legacy", 2).await.unwrap();
+
+        assert_eq!(migrate(&db, &table, "c_Legacy", TableKind::Chunks).await.unwrap(), 11);
+        assert_eq!(table.count_rows(None).await.unwrap(), 3);
+        assert_eq!(table.count_rows(Some("chunk_index = -1".to_string())).await.unwrap(), 3);
+
+        let record = db::Record {
+            path: "/docs/a.txt".to_string(),
+            root_id: "r1".to_string(),
+            rel_path: "a.txt".to_string(),
+            content: "one two three".to_string(),
+            vector: vec![0.5, 0.5],
+            mtime: 8,
+            file_hash: "h2".to_string(),
+            chunk_hash: "c".to_string(),
+            meta: db::ChunkMeta { end_byte: 13, ..Default::default() },
+        };
+        db::upsert_records(&table, vec![record]).await.unwrap();
+        assert_eq!(table.count_rows(None).await.unwrap(), 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_current_version_is_last_migration() {
+        assert_eq!(TableKind::Chunks.current_version(), 11);
+        assert_eq!(TableKind::Annotations.current_version(), 1);
+    }
+
+    #[test]
+    fn test_dimension_mismatch_requires_rebuild() {
+        let schema = schema_with(&["path"]);
+        assert!(check_dimension(&schema, "c_Default", 384).is_ok());
+        let err = check_dimension(&schema, "c_Default", 768).unwrap_err();
+        let rebuild = err.downcast_ref::<RebuildRequired>().unwrap();
+        assert_eq!(rebuild.table, "c_Default");
+        assert!(rebuild.reason.contains("384"));
+        assert!(err.to_string().contains("rebuild"));
+    }
+}
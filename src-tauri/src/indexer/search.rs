@@ -9,6 +9,10 @@ use lancedb::query::{ExecutableQuery, QueryBase};
 use lancedb::DistanceType;
 use log::{debug, warn};
 
+use super::db::{self, ChunkMeta};
+
+pub type SearchHit = (String, String, f32, Option<ChunkMeta>);
+
 pub fn build_filter_expr(
     path_prefix: Option<&str>,
     file_extensions: Option<&[String]>,
@@ -57,7 +61,7 @@ pub async fn search_files(
     path_prefix: Option<&str>,
     file_extensions: Option<&[String]>,
     multi_chunk: bool,
-) -> Result<Vec<(String, String, f32)>> {
+) -> Result<Vec<SearchHit>> {
     let table = match db.open_table(table_name).execute().await {
         Ok(t) => t,
         Err(_) => return Err(anyhow!("No index found for '{}'. Index some folders first.", table_name)),
@@ -78,10 +82,18 @@ pub async fn search_files(
 
     let search_limit = if multi_chunk { limit * 3 } else { limit * 2 };
 
+    let mut columns = vec!["path".to_string(), "content".to_string()];
+    columns.extend(
+        db::CHUNK_META_COLUMNS
+            .iter()
+            .filter(|c| schema.field_with_name(c).is_ok())
+            .map(|c| c.to_string()),
+    );
+
     let mut query = table
         .vector_search(query_vector)?
         .distance_type(DistanceType::Cosine)
-        .select(lancedb::query::Select::Columns(vec!["path".to_string(), "content".to_string()]))
+        .select(lancedb::query::Select::Columns(columns))
         .limit(search_limit);
 
     if let Some(filter) = build_filter_expr(path_prefix, file_extensions) {
@@ -113,11 +125,14 @@ pub async fn search_files(
                 .and_then(|c| c.as_any().downcast_ref::<Float32Array>())
                 .ok_or_else(|| anyhow!("Missing or invalid '_distance' column"))?;
 
-            for i in 0..batch.num_rows() {
+            let metas = db::read_chunk_meta(&batch);
+
+            for (i, meta) in metas.into_iter().enumerate() {
                 matches.push((
                     path_array.value(i).to_string(),
                     content_array.value(i).to_string(),
                     dist_array.value(i),
+                    meta,
                 ));
             }
         }
@@ -126,7 +141,7 @@ pub async fn search_files(
         matches.truncate(limit);
         Ok(matches)
     } else {
-        let mut best_per_file: HashMap<String, (String, f32)> = HashMap::new();
+        let mut best_per_file: HashMap<String, (String, f32, Option<ChunkMeta>)> = HashMap::new();
 
         for batch in results {
             let path_array = batch
@@ -144,23 +159,25 @@ pub async fn search_files(
                 .and_then(|c| c.as_any().downcast_ref::<Float32Array>())
                 .ok_or_else(|| anyhow!("Missing or invalid '_distance' column"))?;
 
-            for i in 0..batch.num_rows() {
+            let metas = db::read_chunk_meta(&batch);
+
+            for (i, meta) in metas.into_iter().enumerate() {
                 let path = path_array.value(i).to_string();
                 let content = content_array.value(i).to_string();
                 let dist = dist_array.value(i);
 
                 match best_per_file.get(&path) {
-                    Some((_, existing_dist)) if *existing_dist <= dist => {}
+                    Some((_, existing_dist, _)) if *existing_dist <= dist => {}
                     _ => {
-                        best_per_file.insert(path, (content, dist));
+                        best_per_file.insert(path, (content, dist, meta));
                     }
                 }
             }
         }
 
-        let mut matches: Vec<(String, String, f32)> = best_per_file
+        let mut matches: Vec<SearchHit> = best_per_file
             .into_iter()
-            .map(|(path, (content, dist))| (path, content, dist))
+            .map(|(path, (content, dist, meta))| (path, content, dist, meta))
             .collect();
 
         matches.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
@@ -178,7 +195,7 @@ pub async fn search_fts(
     path_prefix: Option<&str>,
     file_extensions: Option<&[String]>,
     multi_chunk: bool,
-) -> Result<Vec<(String, String)>> {
+) -> Result<Vec<(String, String, Option<ChunkMeta>)>> {
     let table = match db.open_table(table_name).execute().await {
         Ok(t) => t,
         Err(_) => return Err(anyhow!("No index found for '{}'. Index some folders first.", table_name)),
@@ -213,8 +230,9 @@ pub async fn search_fts(
                 .and_then(|c| c.as_any().downcast_ref::<StringArray>());
 
             if let (Some(paths), Some(contents)) = (path_array, content_array) {
-                for i in 0..batch.num_rows() {
-                    matches.push((paths.value(i).to_string(), contents.value(i).to_string()));
+                let metas = db::read_chunk_meta(&batch);
+                for (i, meta) in metas.into_iter().enumerate() {
+                    matches.push((paths.value(i).to_string(), contents.value(i).to_string(), meta));
                     if matches.len() >= limit {
                         return Ok(matches);
                     }
@@ -233,10 +251,11 @@ pub async fn search_fts(
                 .and_then(|c| c.as_any().downcast_ref::<StringArray>());
 
             if let (Some(paths), Some(contents)) = (path_array, content_array) {
-                for i in 0..batch.num_rows() {
+                let metas = db::read_chunk_meta(&batch);
+                for (i, meta) in metas.into_iter().enumerate() {
                     let path = paths.value(i).to_string();
                     if seen_paths.insert(path.clone()) {
-                        matches.push((path, contents.value(i).to_string()));
+                        matches.push((path, contents.value(i).to_string(), meta));
                     }
                     if matches.len() >= limit {
                         return Ok(matches);
@@ -250,32 +269,32 @@ pub async fn search_fts(
 }
 
 pub fn hybrid_merge(
-    vector_results: &[(String, String, f32)],
-    fts_results: &[(String, String)],
+    vector_results: &[SearchHit],
+    fts_results: &[(String, String, Option<ChunkMeta>)],
     limit: usize,
     vector_weight: f32,
     fts_weight: f32,
-) -> Vec<(String, String, f32)> {
+) -> Vec<SearchHit> {
     let k = 60.0_f32;
 
-    let mut rrf_scores: HashMap<String, (String, f32)> = HashMap::new();
+    let mut rrf_scores: HashMap<String, (String, f32, Option<ChunkMeta>)> = HashMap::new();
 
-    for (rank, (path, snippet, _)) in vector_results.iter().enumerate() {
+    for (rank, (path, snippet, _, meta)) in vector_results.iter().enumerate() {
         let score = vector_weight * (1.0 / (k + rank as f32 + 1.0));
-        rrf_scores.insert(path.clone(), (snippet.clone(), score));
+        rrf_scores.insert(path.clone(), (snippet.clone(), score, meta.clone()));
     }
 
-    for (rank, (path, snippet)) in fts_results.iter().enumerate() {
+    for (rank, (path, snippet, meta)) in fts_results.iter().enumerate() {
         let score = fts_weight * (1.0 / (k + rank as f32 + 1.0));
         rrf_scores
             .entry(path.clone())
-            .and_modify(|(_, s)| *s += score)
-            .or_insert_with(|| (snippet.clone(), score));
+            .and_modify(|(_, s, _)| *s += score)
+            .or_insert_with(|| (snippet.clone(), score, meta.clone()));
     }
 
-    let mut merged: Vec<(String, String, f32)> = rrf_scores
+    let mut merged: Vec<SearchHit> = rrf_scores
         .into_iter()
-        .map(|(path, (snippet, score))| (path, snippet, score))
+        .map(|(path, (snippet, score, meta))| (path, snippet, score, meta))
         .collect();
 
     merged.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
@@ -294,7 +313,7 @@ pub async fn search_pipeline(
     file_extensions: Option<&[String]>,
     vector_weight: f32,
     fts_weight: f32,
-) -> Result<(Vec<(String, String, f32)>, bool)> {
+) -> Result<(Vec<SearchHit>, bool)> {
     let query_variants = super::chunking::expand_query(query);
 
     let vector_fut = search_files(db, table_name, query_vector, search_limit, path_prefix, file_extensions, false);
@@ -311,7 +330,7 @@ pub async fn search_pipeline(
             .map(|v| search_fts(&fts_db, &fts_table, v, 30, pp_ref, fe_ref, false))
             .collect();
         let results = futures::future::join_all(futs).await;
-        let mut all: Vec<(String, String)> = Vec::new();
+        let mut all: Vec<(String, String, Option<ChunkMeta>)> = Vec::new();
         let mut seen = std::collections::HashSet::new();
         for result in results.into_iter().flatten() {
             for item in result {
@@ -330,13 +349,41 @@ pub async fn search_pipeline(
         vector_results.len(), fts_results.len(), vector_weight, fts_weight);
 
     let used_hybrid = !fts_results.is_empty();
-    let merged = if fts_results.is_empty() {
+    let mut merged = if fts_results.is_empty() {
         vector_results
     } else {
         hybrid_merge(&vector_results, &fts_results, search_limit, vector_weight, fts_weight)
     };
 
-    Ok((merged, used_hybrid))
+    if let Err(e) = load_parents(db, table_name, &mut merged).await {
+        warn!("Failed to load parent sections: {}", e);
+    }
+
+    Ok((super::pipeline::expand_parents(merged), used_hybrid))
+}
+
+async fn load_parents(db: &Connection, table_name: &str, hits: &mut [SearchHit]) -> Result<()> {
+    let missing: Vec<(String, usize)> = hits
+        .iter()
+        .filter_map(|(path, _, _, meta)| {
+            let parent = meta.as_ref()?.parent.as_ref()?;
+            parent.text.is_empty().then(|| (path.clone(), parent.start_byte))
+        })
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let table = db.open_table(table_name).execute().await?;
+    let texts = db::parent_texts(&table, &missing).await?;
+    for (path, _, _, meta) in hits.iter_mut() {
+        if let Some(parent) = meta.as_mut().and_then(|m| m.parent.as_mut()) {
+            if let Some(text) = texts.get(&(path.clone(), parent.start_byte)) {
+                parent.text.clone_from(text);
+            }
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -346,18 +393,35 @@ mod tests {
     #[test]
     fn test_hybrid_merge() {
         let vector = vec![
-            ("a.txt".to_string(), "hello".to_string(), 0.1),
-            ("b.txt".to_string(), "world".to_string(), 0.2),
+            ("a.txt".to_string(), "hello".to_string(), 0.1, None),
+            ("b.txt".to_string(), "world".to_string(), 0.2, None),
         ];
         let fts = vec![
-            ("b.txt".to_string(), "world".to_string()),
-            ("c.txt".to_string(), "new".to_string()),
+            ("b.txt".to_string(), "world".to_string(), None),
+            ("c.txt".to_string(), "new".to_string(), None),
         ];
         let merged = hybrid_merge(&vector, &fts, 10, 1.0, 1.0);
         assert_eq!(merged.len(), 3);
         assert_eq!(merged[0].0, "b.txt");
     }
 
+    #[test]
+    fn test_hybrid_merge_keeps_chunk_meta() {
+        let meta = ChunkMeta {
+            start_line: 10,
+            end_line: 24,
+            chunk_index: 2,
+            language: "rust".to_string(),
+            ..Default::default()
+        };
+        let vector = vec![("a.rs".to_string(), "fn a()".to_string(), 0.1, Some(meta.clone()))];
+        let fts = vec![("b.rs".to_string(), "fn b()".to_string(), None)];
+        let merged = hybrid_merge(&vector, &fts, 10, 1.0, 1.0);
+        let a = merged.iter().find(|m| m.0 == "a.rs").unwrap();
+        assert_eq!(a.3.as_ref(), Some(&meta));
+        assert!(merged.iter().find(|m| m.0 == "b.rs").unwrap().3.is_none());
+    }
+
     #[test]
     fn test_build_filter_expr_none() {
         assert_eq!(build_filter_expr(None, None), None);
@@ -411,10 +475,10 @@ mod tests {
     #[test]
     fn test_hybrid_merge_vector_heavy() {
         let vector = vec![
-            ("v.rs".into(), "vectorhit".into(), 0.1),
+            ("v.rs".into(), "vectorhit".into(), 0.1, None),
         ];
         let fts = vec![
-            ("f.rs".into(), "ftshit".into()),
+            ("f.rs".into(), "ftshit".into(), None),
         ];
         let merged = hybrid_merge(&vector, &fts, 10, 1.5, 0.5);
         assert_eq!(merged[0].0, "v.rs", "vector-heavy weights should rank vector first");
@@ -423,10 +487,10 @@ mod tests {
     #[test]
     fn test_hybrid_merge_fts_heavy() {
         let vector = vec![
-            ("v.rs".into(), "vectorhit".into(), 0.1),
+            ("v.rs".into(), "vectorhit".into(), 0.1, None),
         ];
         let fts = vec![
-            ("f.rs".into(), "ftshit".into()),
+            ("f.rs".into(), "ftshit".into(), None),
         ];
         let merged = hybrid_merge(&vector, &fts, 10, 0.3, 1.7);
         assert_eq!(merged[0].0, "f.rs", "fts-heavy weights should rank fts first");
@@ -434,8 +498,8 @@ mod tests {
 
     #[test]
     fn test_hybrid_merge_empty_inputs() {
-        let empty_vec: Vec<(String, String, f32)> = vec![];
-        let empty_fts: Vec<(String, String)> = vec![];
+        let empty_vec: Vec<SearchHit> = vec![];
+        let empty_fts: Vec<(String, String, Option<ChunkMeta>)> = vec![];
         let merged = hybrid_merge(&empty_vec, &empty_fts, 10, 1.0, 1.0);
         assert!(merged.is_empty());
     }
@@ -443,11 +507,11 @@ mod tests {
     #[test]
     fn test_hybrid_merge_overlap_score_boosted() {
         let vector = vec![
-            ("shared.rs".into(), "content".into(), 0.1),
+            ("shared.rs".into(), "content".into(), 0.1, None),
         ];
         let fts = vec![
-            ("shared.rs".into(), "content".into()),
-            ("fts_only.rs".into(), "other".into()),
+            ("shared.rs".into(), "content".into(), None),
+            ("fts_only.rs".into(), "other".into(), None),
         ];
         let merged = hybrid_merge(&vector, &fts, 10, 1.0, 1.0);
         assert_eq!(merged[0].0, "shared.rs", "item in both sources should rank highest");
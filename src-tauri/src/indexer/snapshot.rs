@@ -0,0 +1,533 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use arrow_array::{RecordBatch, RecordBatchIterator, StringArray};
+use arrow_ipc::reader::StreamReader;
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::DataType;
+use fastembed::TextEmbedding;
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::ExecutableQuery;
+use log::info;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::config::{get_embedding_model, get_table_name, ContainerInfo, EmbeddingProviderConfig};
+
+use super::annotations::annotations_table_name;
+use super::db;
+use super::jobs::now_unix;
+use super::schema::{self, TableKind};
+
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const CHUNKS_ENTRY: &str = "chunks.arrow";
+const ANNOTATIONS_ENTRY: &str = "annotations.arrow";
+const SNAPSHOT_QUEUE_SIZE: usize = 4;
+const IMPORT_BATCH_ROWS: usize = 8192;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProviderIdentity {
+    pub kind: String,
+    pub model: String,
+    pub dimension: usize,
+}
+
+impl ProviderIdentity {
+    pub fn from_config(config: &EmbeddingProviderConfig) -> Self {
+        match config {
+            EmbeddingProviderConfig::Local { model } => Self {
+                kind: "local".to_string(),
+                model: model.clone(),
+                dimension: TextEmbedding::get_model_info(&get_embedding_model(model))
+                    .map(|info| info.dim)
+                    .unwrap_or(0),
+            },
+            EmbeddingProviderConfig::Remote(rc) => Self {
+                kind: "remote".to_string(),
+                model: rc.model.clone(),
+                dimension: rc.dimensions,
+            },
+        }
+    }
+
+    pub fn check_compatible(&self, expected: &ProviderIdentity) -> Result<()> {
+        if self.kind != expected.kind || self.model != expected.model {
+            return Err(anyhow!(
+                "Archive was built with {} model '{}' but the container uses {} model '{}'",
+                self.kind,
+                self.model,
+                expected.kind,
+                expected.model
+            ));
+        }
+        if self.dimension != expected.dimension {
+            return Err(anyhow!(
+                "Archive has embedding dimension {} but the container expects {}",
+                self.dimension,
+                expected.dimension
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SnapshotManifest {
+    pub format_version: u32,
+    pub container: String,
+    pub info: ContainerInfo,
+    pub provider: ProviderIdentity,
+    pub roots: Vec<String>,
+    pub chunk_rows: usize,
+    pub annotation_rows: usize,
+    pub chunk_schema_version: i64,
+    pub annotation_schema_version: i64,
+    pub created_at: i64,
+}
+
+type ArchiveWriter = ZipWriter<BufWriter<File>>;
+
+enum Sink {
+    Idle(ArchiveWriter),
+    Writing(StreamWriter<ArchiveWriter>),
+}
+
+fn relativize_path(path: &str, roots: &[String]) -> String {
+    let full = Path::new(path);
+    roots
+        .iter()
+        .enumerate()
+        .filter_map(|(i, root)| full.strip_prefix(root).ok().map(|rel| (i, root.len(), rel)))
+        .max_by_key(|(_, len, _)| *len)
+        .map(|(i, _, rel)| {
+            let parts: Vec<String> = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            format!("{}/{}", i, parts.join("/"))
+        })
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn reroot_path(path: &str, roots: &[String]) -> String {
+    let Some((id, rel)) = path.split_once('/') else {
+        return path.to_string();
+    };
+    let Some(root) = id.parse::<usize>().ok().and_then(|i| roots.get(i)) else {
+        return path.to_string();
+    };
+    let mut full = PathBuf::from(root);
+    full.extend(rel.split('/').filter(|part| !part.is_empty()));
+    full.to_string_lossy().to_string()
+}
+
+fn map_paths(batch: RecordBatch, f: impl Fn(&str) -> String) -> Result<RecordBatch> {
+    let schema = batch.schema();
+    let idx = schema.index_of("path")?;
+    let paths = batch
+        .column(idx)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .ok_or_else(|| anyhow!("Invalid 'path' column"))?;
+    let mapped: StringArray = paths.iter().map(|p| p.map(&f)).collect();
+    let mut columns = batch.columns().to_vec();
+    columns[idx] = Arc::new(mapped);
+    Ok(RecordBatch::try_new(schema, columns)?)
+}
+
+fn vector_dimension(schema: &arrow_schema::Schema) -> Option<usize> {
+    match schema.field_with_name("vector").map(|f| f.data_type()) {
+        Ok(DataType::FixedSizeList(_, size)) => Some(*size as usize),
+        _ => None,
+    }
+}
+
+fn strip_secrets(info: &ContainerInfo) -> ContainerInfo {
+    let mut info = info.clone();
+    if let Some(EmbeddingProviderConfig::Remote(rc)) = info.embedding_provider.as_mut() {
+        rc.api_key = None;
+    }
+    info
+}
+
+fn write_archive(
+    dest: PathBuf,
+    mut rx: mpsc::Receiver<(&'static str, RecordBatch)>,
+) -> Result<(ArchiveWriter, HashMap<&'static str, usize>)> {
+    let mut sink = Sink::Idle(ZipWriter::new(BufWriter::new(File::create(&dest)?)));
+    let mut current: Option<&'static str> = None;
+    let mut rows: HashMap<&'static str, usize> = HashMap::new();
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+
+    while let Some((entry, batch)) = rx.blocking_recv() {
+        if current != Some(entry) {
+            let mut zip = match sink {
+                Sink::Idle(zip) => zip,
+                Sink::Writing(mut writer) => {
+                    writer.finish()?;
+                    writer.into_inner()?
+                }
+            };
+            zip.start_file(entry, options)?;
+            sink = Sink::Writing(StreamWriter::try_new(zip, &batch.schema())?);
+            current = Some(entry);
+        }
+        if let Sink::Writing(writer) = &mut sink {
+            writer.write(&batch)?;
+        }
+        *rows.entry(entry).or_default() += batch.num_rows();
+    }
+
+    let zip = match sink {
+        Sink::Idle(zip) => zip,
+        Sink::Writing(mut writer) => {
+            writer.finish()?;
+            writer.into_inner()?
+        }
+    };
+    Ok((zip, rows))
+}
+
+pub async fn export_snapshot(
+    db: &Connection,
+    container: &str,
+    info: &ContainerInfo,
+    provider: &EmbeddingProviderConfig,
+    dest: &Path,
+) -> Result<SnapshotManifest> {
+    let table_name = get_table_name(container);
+    let ann_name = annotations_table_name(&table_name);
+    let table = db
+        .open_table(&table_name)
+        .execute()
+        .await
+        .map_err(|_| anyhow!("Container '{}' has no index to export", container))?;
+    let mut identity = ProviderIdentity::from_config(provider);
+    let table_schema = table.schema().await?;
+    if let Some(dim) = vector_dimension(&table_schema) {
+        identity.dimension = dim;
+    }
+
+    schema::migrate(db, &table, &table_name, TableKind::Chunks).await?;
+    let annotations = match db.open_table(&ann_name).execute().await {
+        Ok(t) => {
+            schema::migrate(db, &t, &ann_name, TableKind::Annotations).await?;
+            Some(t)
+        }
+        Err(_) => None,
+    };
+
+    let roots = info.indexed_paths.clone();
+    let tmp = dest.with_extension("partial");
+    let (tx, rx) = mpsc::channel(SNAPSHOT_QUEUE_SIZE);
+    let writer = tokio::task::spawn_blocking({
+        let tmp = tmp.clone();
+        move || write_archive(tmp, rx)
+    });
+
+    let sources = std::iter::once((CHUNKS_ENTRY, table)).chain(annotations.map(|t| (ANNOTATIONS_ENTRY, t)));
+    for (entry, source) in sources {
+        let mut stream = source.query().execute().await?;
+        while let Some(batch) = stream.try_next().await? {
+            let batch = map_paths(batch, |p| relativize_path(p, &roots))?;
+            if tx.send((entry, batch)).await.is_err() {
+                break;
+            }
+        }
+    }
+    drop(tx);
+
+    let (mut zip, rows) = match writer.await? {
+        Ok(written) => written,
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e);
+        }
+    };
+
+    let manifest = SnapshotManifest {
+        format_version: SNAPSHOT_FORMAT_VERSION,
+        container: container.to_string(),
+        info: strip_secrets(info),
+        provider: identity,
+        roots,
+        chunk_rows: rows.get(CHUNKS_ENTRY).copied().unwrap_or(0),
+        annotation_rows: rows.get(ANNOTATIONS_ENTRY).copied().unwrap_or(0),
+        chunk_schema_version: TableKind::Chunks.current_version(),
+        annotation_schema_version: TableKind::Annotations.current_version(),
+        created_at: now_unix(),
+    };
+
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    let finished = tokio::task::spawn_blocking(move || -> Result<()> {
+        zip.start_file(MANIFEST_ENTRY, SimpleFileOptions::default())?;
+        zip.write_all(&manifest_json)?;
+        zip.finish()?.flush()?;
+        Ok(())
+    })
+    .await?;
+    if let Err(e) = finished.and_then(|_| Ok(std::fs::rename(&tmp, dest)?)) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e);
+    }
+
+    info!(
+        "Exported '{}' to {:?} ({} chunks, {} annotations)",
+        container, dest, manifest.chunk_rows, manifest.annotation_rows
+    );
+    Ok(manifest)
+}
+
+pub fn read_manifest(archive: &Path) -> Result<SnapshotManifest> {
+    let mut zip = ZipArchive::new(BufReader::new(File::open(archive)?))?;
+    let entry = zip
+        .by_name(MANIFEST_ENTRY)
+        .map_err(|_| anyhow!("{:?} is not a container snapshot", archive))?;
+    let manifest: SnapshotManifest = serde_json::from_reader(entry)?;
+    if manifest.format_version > SNAPSHOT_FORMAT_VERSION {
+        return Err(anyhow!(
+            "Snapshot format version {} is newer than this build supports ({})",
+            manifest.format_version,
+            SNAPSHOT_FORMAT_VERSION
+        ));
+    }
+    Ok(manifest)
+}
+
+fn read_archive(archive: PathBuf, tx: mpsc::Sender<(&'static str, RecordBatch)>) -> Result<()> {
+    let mut zip = ZipArchive::new(BufReader::new(File::open(&archive)?))?;
+    for entry in [CHUNKS_ENTRY, ANNOTATIONS_ENTRY] {
+        let file = match zip.by_name(entry) {
+            Ok(f) => f,
+            Err(_) => continue,
+        };
+        for batch in StreamReader::try_new(BufReader::new(file), None)? {
+            if tx.blocking_send((entry, batch?)).is_err() {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn append_batches(db: &Connection, table_name: &str, batches: Vec<RecordBatch>) -> Result<()> {
+    let Some(schema) = batches.first().map(|b| b.schema()) else {
+        return Ok(());
+    };
+    let reader = RecordBatchIterator::new(batches.into_iter().map(Ok), schema);
+    match db.open_table(table_name).execute().await {
+        Ok(table) => {
+            table.add(reader).execute().await?;
+        }
+        Err(_) => {
+            db.create_table(table_name, reader).execute().await?;
+        }
+    }
+    Ok(())
+}
+
+async fn load_archive(
+    db: &Connection,
+    archive: &Path,
+    targets: &HashMap<&'static str, String>,
+    roots: &[String],
+) -> Result<()> {
+    let (tx, mut rx) = mpsc::channel(SNAPSHOT_QUEUE_SIZE);
+    let reader = tokio::task::spawn_blocking({
+        let archive = archive.to_path_buf();
+        move || read_archive(archive, tx)
+    });
+
+    let mut buffers: HashMap<&'static str, Vec<RecordBatch>> = HashMap::new();
+    while let Some((entry, batch)) = rx.recv().await {
+        let batch = map_paths(batch, |p| reroot_path(p, roots))?;
+        let buffer = buffers.entry(entry).or_default();
+        buffer.push(batch);
+        if buffer.iter().map(|b| b.num_rows()).sum::<usize>() >= IMPORT_BATCH_ROWS {
+            append_batches(db, &targets[entry], std::mem::take(buffer)).await?;
+        }
+    }
+    reader.await??;
+
+    for (entry, buffer) in buffers {
+        append_batches(db, &targets[entry], buffer).await?;
+    }
+    Ok(())
+}
+
+fn staging_table_name(table_name: &str) -> String {
+    format!("{}__import", table_name)
+}
+
+async fn row_count(db: &Connection, table_name: &str) -> Result<usize> {
+    match db.open_table(table_name).execute().await {
+        Ok(table) => Ok(table.count_rows(None).await?),
+        Err(_) => Ok(0),
+    }
+}
+
+async fn verify_staging(db: &Connection, manifest: &SnapshotManifest, chunks: &str, annotations: &str) -> Result<()> {
+    let chunk_rows = row_count(db, chunks).await?;
+    if chunk_rows != manifest.chunk_rows {
+        return Err(anyhow!(
+            "Snapshot manifest lists {} chunks but the archive holds {}",
+            manifest.chunk_rows,
+            chunk_rows
+        ));
+    }
+    let annotation_rows = row_count(db, annotations).await?;
+    if annotation_rows != manifest.annotation_rows {
+        return Err(anyhow!(
+            "Snapshot manifest lists {} annotations but the archive holds {}",
+            manifest.annotation_rows,
+            annotation_rows
+        ));
+    }
+    if let Ok(table) = db.open_table(chunks).execute().await {
+        let table_schema = table.schema().await?;
+        let dimension = vector_dimension(&table_schema);
+        if dimension != Some(manifest.provider.dimension) {
+            return Err(anyhow!(
+                "Snapshot manifest lists embedding dimension {} but the archive holds {:?}",
+                manifest.provider.dimension,
+                dimension
+            ));
+        }
+    }
+    Ok(())
+}
+
+async fn replace_from_staging(db: &Connection, staging: &str, table_name: &str, version: i64) -> Result<()> {
+    schema::drop_versioned_table(db, table_name).await?;
+    let Ok(source) = db.open_table(staging).execute().await else {
+        return Ok(());
+    };
+    let mut stream = source.query().execute().await?;
+    let mut buffer = Vec::new();
+    while let Some(batch) = stream.try_next().await? {
+        buffer.push(batch);
+        if buffer.iter().map(|b| b.num_rows()).sum::<usize>() >= IMPORT_BATCH_ROWS {
+            append_batches(db, table_name, std::mem::take(&mut buffer)).await?;
+        }
+    }
+    append_batches(db, table_name, buffer).await?;
+    schema::set_version(db, table_name, version).await?;
+    schema::drop_versioned_table(db, staging).await
+}
+
+pub async fn import_snapshot(
+    db: &Connection,
+    archive: &Path,
+    container: &str,
+    roots: &[String],
+    expected: &ProviderIdentity,
+    overwrite: bool,
+) -> Result<SnapshotManifest> {
+    let manifest = read_manifest(archive)?;
+    manifest.provider.check_compatible(expected)?;
+    if roots.len() != manifest.roots.len() {
+        return Err(anyhow!(
+            "Snapshot has {} indexed roots but {} were given",
+            manifest.roots.len(),
+            roots.len()
+        ));
+    }
+
+    let table_name = get_table_name(container);
+    let ann_name = annotations_table_name(&table_name);
+    if !overwrite && row_count(db, &table_name).await? + row_count(db, &ann_name).await? > 0 {
+        return Err(anyhow!(
+            "Container '{}' already has an index; import with overwrite to replace it",
+            container
+        ));
+    }
+
+    let staging = staging_table_name(&table_name);
+    let ann_staging = staging_table_name(&ann_name);
+    schema::drop_versioned_table(db, &staging).await?;
+    schema::drop_versioned_table(db, &ann_staging).await?;
+
+    let targets = HashMap::from([
+        (CHUNKS_ENTRY, staging.clone()),
+        (ANNOTATIONS_ENTRY, ann_staging.clone()),
+    ]);
+    let loaded = match load_archive(db, archive, &targets, roots).await {
+        Ok(()) => verify_staging(db, &manifest, &staging, &ann_staging).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = loaded {
+        let _ = schema::drop_versioned_table(db, &staging).await;
+        let _ = schema::drop_versioned_table(db, &ann_staging).await;
+        return Err(e);
+    }
+
+    replace_from_staging(db, &staging, &table_name, manifest.chunk_schema_version).await?;
+    replace_from_staging(db, &ann_staging, &ann_name, manifest.annotation_schema_version).await?;
+
+    if let Ok(table) = db.open_table(&table_name).execute().await {
+        schema::migrate(db, &table, &table_name, TableKind::Chunks).await?;
+        db::build_fts_index(&table).await?;
+        if manifest.chunk_rows >= super::ANN_INDEX_THRESHOLD {
+            db::build_ann_index(&table).await?;
+        }
+    }
+    if let Ok(table) = db.open_table(&ann_name).execute().await {
+        schema::migrate(db, &table, &ann_name, TableKind::Annotations).await?;
+    }
+
+    info!(
+        "Imported {:?} into '{}' ({} chunks, {} annotations)",
+        archive, container, manifest.chunk_rows, manifest.annotation_rows
+    );
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_reroot_onto_new_roots() {
+        let roots = vec!["/home/a/docs".to_string(), "/home/a/docs/work".to_string()];
+        let rel = relativize_path("/home/a/docs/work/notes/todo.md", &roots);
+        assert_eq!(rel, "1/notes/todo.md");
+        assert_eq!(relativize_path("/home/a/docs/readme.md", &roots), "0/readme.md");
+        assert_eq!(relativize_path("/tmp/other.txt", &roots), "/tmp/other.txt");
+
+        let new_roots = vec!["/mnt/docs".to_string(), "/mnt/work".to_string()];
+        assert_eq!(reroot_path(&rel, &new_roots), "/mnt/work/notes/todo.md");
+        assert_eq!(reroot_path("/tmp/other.txt", &new_roots), "/tmp/other.txt");
+    }
+
+    #[test]
+    fn test_provider_mismatch_is_refused() {
+        let archived = ProviderIdentity {
+            kind: "remote".to_string(),
+            model: "text-embedding-3-small".to_string(),
+            dimension: 1536,
+        };
+        assert!(archived.check_compatible(&archived.clone()).is_ok());
+
+        let mut other_dim = archived.clone();
+        other_dim.dimension = 512;
+        assert!(archived.check_compatible(&other_dim).unwrap_err().to_string().contains("dimension"));
+
+        let local = ProviderIdentity {
+            kind: "local".to_string(),
+            model: "MultilingualE5Base".to_string(),
+            dimension: 768,
+        };
+        assert!(archived.check_compatible(&local).is_err());
+    }
+}
@@ -0,0 +1,465 @@
+use std::ops::Range;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+const MAX_DEPTH: usize = 64;
+
+static YAML_KEY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^("[^"]*"|'[^']*'|[^\s#'"\[\]{}&*!|>%@`-][^:#]*?|-[^\s:#][^:#]*?)\s*:(?:\s|$)"#).unwrap()
+});
+static TOML_KEY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^([A-Za-z0-9_\-."' ]+?)\s*=(.*)$"#).unwrap());
+
+#[derive(Debug)]
+struct Node {
+    path: String,
+    range: Range<usize>,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn new(path: String, start: usize) -> Self {
+        Self {
+            path,
+            range: start..start,
+            children: Vec::new(),
+        }
+    }
+}
+
+pub fn is_structured_extension(ext: &str) -> bool {
+    matches!(ext, "json" | "yaml" | "yml" | "toml")
+}
+
+fn join(parent: &str, key: &str) -> String {
+    let key = key.trim();
+    let key = key
+        .strip_prefix('"')
+        .and_then(|k| k.strip_suffix('"'))
+        .or_else(|| key.strip_prefix('\'').and_then(|k| k.strip_suffix('\'')))
+        .unwrap_or(key);
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
+
+struct JsonScanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl JsonScanner<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.bytes.get(self.pos).is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek()? {
+                b'\\' => self.pos += 2,
+                b'"' => break,
+                _ => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        serde_json::from_slice(&self.bytes[start..self.pos]).ok()
+    }
+
+    fn value(&mut self, path: String, depth: usize) -> Option<Node> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.skip_whitespace();
+        let mut node = Node::new(path, self.pos);
+        match self.peek()? {
+            b'{' => {
+                self.pos += 1;
+                loop {
+                    self.skip_whitespace();
+                    if self.peek()? == b'}' {
+                        break;
+                    }
+                    let key_start = self.pos;
+                    if self.peek()? != b'"' {
+                        return None;
+                    }
+                    let key = self.string()?;
+                    self.skip_whitespace();
+                    if self.peek()? != b':' {
+                        return None;
+                    }
+                    self.pos += 1;
+                    let mut child = self.value(join(&node.path, &key), depth + 1)?;
+                    child.range.start = key_start;
+                    node.children.push(child);
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        b',' => self.pos += 1,
+                        b'}' => break,
+                        _ => return None,
+                    }
+                }
+                self.pos += 1;
+            }
+            b'[' => {
+                self.pos += 1;
+                loop {
+                    self.skip_whitespace();
+                    if self.peek()? == b']' {
+                        break;
+                    }
+                    let path = format!("{}[{}]", node.path, node.children.len());
+                    node.children.push(self.value(path, depth + 1)?);
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        b',' => self.pos += 1,
+                        b']' => break,
+                        _ => return None,
+                    }
+                }
+                self.pos += 1;
+            }
+            b'"' => {
+                self.string()?;
+            }
+            _ => {
+                while self
+                    .peek()
+                    .is_some_and(|b| !b.is_ascii_whitespace() && !matches!(b, b',' | b'}' | b']'))
+                {
+                    self.pos += 1;
+                }
+                if self.pos == node.range.start {
+                    return None;
+                }
+            }
+        }
+        node.range.end = self.pos;
+        Some(node)
+    }
+}
+
+fn json_tree(text: &str) -> Option<Node> {
+    let mut scanner = JsonScanner {
+        bytes: text.as_bytes(),
+        pos: 0,
+    };
+    let mut root = scanner.value(String::new(), 0)?;
+    scanner.skip_whitespace();
+    if scanner.pos != text.len() {
+        return None;
+    }
+    root.range = 0..text.len();
+    Some(root)
+}
+
+struct Open {
+    indent: usize,
+    item: bool,
+    node: Node,
+}
+
+fn attach(stack: &mut [Open], root: &mut Node, node: Node) {
+    match stack.last_mut() {
+        Some(parent) => parent.node.children.push(node),
+        None => root.children.push(node),
+    }
+}
+
+fn close_while(stack: &mut Vec<Open>, root: &mut Node, end: usize, done: impl Fn(&Open) -> bool) {
+    while stack.last().is_some_and(&done) {
+        let mut open = stack.pop().unwrap();
+        open.node.range.end = end;
+        attach(stack, root, open.node);
+    }
+}
+
+fn yaml_tree(text: &str) -> Option<Node> {
+    let mut root = Node::new(String::new(), 0);
+    let mut stack: Vec<Open> = Vec::new();
+    let mut block_indent: Option<usize> = None;
+    let mut start = 0;
+
+    for line in text.split_inclusive('\n') {
+        let line_start = start;
+        start += line.len();
+        let content = line.trim_end();
+        let trimmed = content.trim_start();
+        let indent = content.len() - trimmed.len();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(block) = block_indent {
+            if indent > block {
+                continue;
+            }
+            block_indent = None;
+        }
+        if trimmed == "---" || trimmed.starts_with("--- ") || trimmed == "..." {
+            close_while(&mut stack, &mut root, line_start, |_| true);
+            continue;
+        }
+
+        let is_item = trimmed == "-" || trimmed.starts_with("- ");
+        close_while(&mut stack, &mut root, line_start, |open| {
+            open.indent > indent || (open.indent == indent && (!is_item || open.item))
+        });
+
+        let (mut column, mut rest) = (indent, trimmed);
+        loop {
+            let parent_path = stack.last().map_or(&root.path, |o| &o.node.path).clone();
+            if let Some(item) = rest.strip_prefix('-').filter(|r| r.is_empty() || r.starts_with(' ')) {
+                let index = stack.last().map_or(&root, |o| &o.node).children.len();
+                let path = format!("{}[{}]", parent_path, index);
+                stack.push(Open {
+                    indent: column,
+                    item: true,
+                    node: Node::new(path, line_start),
+                });
+                let value = item.trim_start();
+                column += rest.len() - value.len();
+                rest = value;
+                continue;
+            }
+            if let Some(caps) = YAML_KEY.captures(rest) {
+                let key = caps.get(1).map_or("", |m| m.as_str());
+                let value = rest[caps.get(0).map_or(0, |m| m.end())..].trim_start();
+                stack.push(Open {
+                    indent: column,
+                    item: false,
+                    node: Node::new(join(&parent_path, key), line_start),
+                });
+                if value.starts_with('|') || value.starts_with('>') {
+                    block_indent = Some(column);
+                }
+            }
+            break;
+        }
+    }
+
+    close_while(&mut stack, &mut root, text.len(), |_| true);
+    root.range = 0..text.len();
+    (!root.children.is_empty()).then_some(root)
+}
+
+fn toml_tree(text: &str) -> Option<Node> {
+    let mut root = Node::new(String::new(), 0);
+    let mut table: Option<Node> = None;
+    let mut key: Option<Node> = None;
+    let mut array_tables: std::collections::HashMap<String, usize> = Default::default();
+    let mut multiline: Option<&str> = None;
+    let mut start = 0;
+
+    for line in text.split_inclusive('\n') {
+        let line_start = start;
+        start += line.len();
+        if let Some(delimiter) = multiline {
+            if line.contains(delimiter) {
+                multiline = None;
+            }
+            continue;
+        }
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if trimmed.starts_with('[') {
+            let header = trimmed.split('#').next().unwrap_or("").trim_end();
+            let path = if let Some(name) = header.strip_prefix("[[").and_then(|h| h.strip_suffix("]]")) {
+                let name = join("", name);
+                let count = array_tables.entry(name.clone()).or_insert(0);
+                *count += 1;
+                format!("{}[{}]", name, *count - 1)
+            } else if let Some(name) = header.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+                join("", name)
+            } else {
+                return None;
+            };
+            let parent = table.as_mut().unwrap_or(&mut root);
+            if let Some(mut done) = key.take() {
+                done.range.end = line_start;
+                parent.children.push(done);
+            }
+            if let Some(mut done) = table.take() {
+                done.range.end = line_start;
+                root.children.push(done);
+            }
+            table = Some(Node::new(path, line_start));
+            continue;
+        }
+
+        if let Some(caps) = TOML_KEY.captures(trimmed) {
+            let parent = table.as_mut().unwrap_or(&mut root);
+            if let Some(mut done) = key.take() {
+                done.range.end = line_start;
+                parent.children.push(done);
+            }
+            key = Some(Node::new(join(&parent.path, &caps[1]), line_start));
+            let value = caps[2].trim_start();
+            for delimiter in ["\"\"\"", "'''"] {
+                if value.starts_with(delimiter) && value.matches(delimiter).count() == 1 {
+                    multiline = Some(delimiter);
+                }
+            }
+        }
+    }
+
+    let parent = table.as_mut().unwrap_or(&mut root);
+    if let Some(mut done) = key.take() {
+        done.range.end = text.len();
+        parent.children.push(done);
+    }
+    if let Some(mut done) = table.take() {
+        done.range.end = text.len();
+        root.children.push(done);
+    }
+    root.range = 0..text.len();
+    (!root.children.is_empty()).then_some(root)
+}
+
+fn collect(node: &Node, max_bytes: usize, units: &mut Vec<(String, Range<usize>)>) {
+    if node.range.len() <= max_bytes || node.children.is_empty() {
+        units.push((node.path.clone(), node.range.clone()));
+        return;
+    }
+
+    let mut group: Option<(Range<usize>, &Node, usize)> = None;
+    let flush = |group: &mut Option<(Range<usize>, &Node, usize)>, units: &mut Vec<_>| {
+        if let Some((range, first, count)) = group.take() {
+            let path = if count == 1 { &first.path } else { &node.path };
+            units.push((path.clone(), range));
+        }
+    };
+    for child in &node.children {
+        if child.range.len() > max_bytes {
+            flush(&mut group, units);
+            collect(child, max_bytes, units);
+            continue;
+        }
+        match &mut group {
+            Some((range, _, count)) if child.range.end - range.start <= max_bytes => {
+                range.end = child.range.end;
+                *count += 1;
+            }
+            _ => {
+                flush(&mut group, units);
+                group = Some((child.range.clone(), child, 1));
+            }
+        }
+    }
+    flush(&mut group, units);
+}
+
+pub fn key_path_units(text: &str, ext: &str, max_bytes: usize) -> Option<Vec<(String, Range<usize>)>> {
+    let root = match ext {
+        "json" => json_tree(text)?,
+        "yaml" | "yml" => yaml_tree(text)?,
+        "toml" => toml_tree(text)?,
+        _ => return None,
+    };
+    let mut units = Vec::new();
+    collect(&root, max_bytes, &mut units);
+    for (_, range) in units.iter_mut() {
+        let line_start = text[..range.start].rfind('\n').map_or(0, |i| i + 1);
+        if text[line_start..range.start].trim().is_empty() {
+            range.start = line_start;
+        }
+    }
+    Some(units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(text: &str, ext: &str, max_bytes: usize) -> Vec<String> {
+        key_path_units(text, ext, max_bytes)
+            .unwrap()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    #[test]
+    fn test_yaml_key_paths() {
+        let yaml = "\
+services:
+  api:
+    image: registry.local/api:2.4
+    env:
+      DATABASE_URL: postgres://db:5432/app
+      LOG_LEVEL: debug
+    ports:
+      - 8080:8080
+  worker:
+    command: |
+      run: --queue default
+    replicas: 3
+volumes:
+  - name: data
+    size: 10Gi
+";
+        assert_eq!(paths(yaml, "yaml", 400), vec![""]);
+        assert_eq!(paths(yaml, "yaml", 90), vec![
+            "services.api.image",
+            "services.api.env",
+            "services.api.ports",
+            "services.worker",
+            "volumes",
+        ]);
+
+        let units = key_path_units(yaml, "yaml", 90).unwrap();
+        let (_, env) = units.iter().find(|(p, _)| p == "services.api.env").unwrap();
+        assert_eq!(&yaml[env.clone()], "    env:\n      DATABASE_URL: postgres://db:5432/app\n      LOG_LEVEL: debug\n");
+        assert!(!paths(yaml, "yaml", 20).iter().any(|p| p.contains("run")));
+    }
+
+    #[test]
+    fn test_json_and_toml_key_paths() {
+        let json = r#"{"name": "app", "services": {"api": {"env": {"DATABASE_URL": "postgres://db/app", "TOKEN": "x"}}, "jobs": [{"cron": "0 * * * *"}, {"cron": "@daily"}]}}"#;
+        assert_eq!(
+            paths(json, "json", 60),
+            vec!["name", "services.api.env", "services.jobs"]
+        );
+        assert_eq!(paths(json, "json", 25), vec![
+            "name",
+            "services.api.env.DATABASE_URL",
+            "services.api.env.TOKEN",
+            "services.jobs[0]",
+            "services.jobs[1]",
+        ]);
+        assert!(key_path_units("{\"a\": [1, 2", "json", 60).is_none());
+
+        let toml = "\
+title = \"rememex\"
+
+[server.http]
+port = 8080
+banner = \"\"\"
+[not.a.table]
+\"\"\"
+
+[[plugins]]
+name = \"ocr\"
+
+[[plugins]]
+name = \"git\"
+";
+        assert_eq!(
+            paths(toml, "toml", 40),
+            vec!["title", "server.http.port", "server.http.banner", "plugins[0]", "plugins[1]"]
+        );
+    }
+}
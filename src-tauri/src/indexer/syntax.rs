@@ -0,0 +1,347 @@
+use std::ops::Range;
+
+use tree_sitter::{Language, Node, Parser};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolUnit {
+    pub range: Range<usize>,
+    pub symbol: Option<Symbol>,
+}
+
+struct Item<'t> {
+    name: String,
+    kind: &'static str,
+    body: Option<Node<'t>>,
+}
+
+fn language(ext: &str) -> Option<Language> {
+    let language = match ext {
+        "rs" => tree_sitter_rust::LANGUAGE,
+        "py" | "pyi" | "pyw" => tree_sitter_python::LANGUAGE,
+        "js" | "mjs" | "cjs" | "jsx" => tree_sitter_javascript::LANGUAGE,
+        "ts" | "mts" | "cts" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT,
+        "tsx" => tree_sitter_typescript::LANGUAGE_TSX,
+        "go" => tree_sitter_go::LANGUAGE,
+        "java" => tree_sitter_java::LANGUAGE,
+        "c" | "h" => tree_sitter_c::LANGUAGE,
+        "cpp" | "cc" | "cxx" | "hpp" | "hxx" | "hh" => tree_sitter_cpp::LANGUAGE,
+        _ => return None,
+    };
+    Some(language.into())
+}
+
+pub fn is_syntax_extension(ext: &str) -> bool {
+    language(ext).is_some()
+}
+
+fn separator(ext: &str) -> &'static str {
+    match ext {
+        "rs" | "c" | "h" | "cpp" | "cc" | "cxx" | "hpp" | "hxx" | "hh" => "::",
+        _ => ".",
+    }
+}
+
+fn text<'s>(node: Node, source: &'s str) -> &'s str {
+    &source[node.byte_range()]
+}
+
+fn declarator_name(node: Node, source: &str) -> Option<String> {
+    let mut current = node;
+    while let Some(inner) = current.child_by_field_name("declarator") {
+        current = inner;
+    }
+    (current.id() != node.id()).then(|| text(current, source).to_string())
+}
+
+fn named<'t>(node: Node<'t>, source: &str, kind: &'static str, field: &str) -> Option<Item<'t>> {
+    Some(Item {
+        name: text(node.child_by_field_name(field)?, source).to_string(),
+        kind,
+        body: node.child_by_field_name("body"),
+    })
+}
+
+fn describe<'t>(node: Node<'t>, source: &str) -> Option<Item<'t>> {
+    match node.kind() {
+        "function_item" | "function_declaration" | "generator_function_declaration" => {
+            named(node, source, "function", "name")
+        }
+        "function_definition" => match node.child_by_field_name("name") {
+            Some(name) => Some(Item {
+                name: text(name, source).to_string(),
+                kind: "function",
+                body: node.child_by_field_name("body"),
+            }),
+            None => Some(Item {
+                name: declarator_name(node, source)?,
+                kind: "function",
+                body: None,
+            }),
+        },
+        "method_definition" | "method_declaration" => named(node, source, "method", "name"),
+        "constructor_declaration" => named(node, source, "constructor", "name"),
+        "impl_item" => named(node, source, "impl", "type"),
+        "trait_item" => named(node, source, "trait", "name"),
+        "mod_item" => named(node, source, "module", "name"),
+        "struct_item" => named(node, source, "struct", "name"),
+        "enum_item" | "enum_declaration" => named(node, source, "enum", "name"),
+        "union_item" => named(node, source, "union", "name"),
+        "type_item" | "type_alias_declaration" => named(node, source, "type", "name"),
+        "macro_definition" => named(node, source, "macro", "name"),
+        "class_definition" | "class_declaration" | "abstract_class_declaration" => {
+            named(node, source, "class", "name")
+        }
+        "interface_declaration" => named(node, source, "interface", "name"),
+        "record_declaration" => named(node, source, "record", "name"),
+        "namespace_definition" | "internal_module" => named(node, source, "namespace", "name"),
+        "struct_specifier" | "class_specifier" | "union_specifier" | "enum_specifier" => {
+            node.child_by_field_name("body")?;
+            let kind = match node.kind() {
+                "class_specifier" => "class",
+                "union_specifier" => "union",
+                "enum_specifier" => "enum",
+                _ => "struct",
+            };
+            named(node, source, kind, "name")
+        }
+        "type_definition" => Some(Item {
+            name: text(node.child_by_field_name("declarator")?, source).to_string(),
+            kind: "type",
+            body: None,
+        }),
+        "type_declaration" => {
+            let spec = node.named_child(0).filter(|n| n.kind() == "type_spec")?;
+            let kind = match spec.child_by_field_name("type").map(|t| t.kind()) {
+                Some("struct_type") => "struct",
+                Some("interface_type") => "interface",
+                _ => "type",
+            };
+            named(spec, source, kind, "name")
+        }
+        "lexical_declaration" | "variable_declaration" => {
+            let declarator = node.named_child(0).filter(|_| node.named_child_count() == 1)?;
+            let value = declarator.child_by_field_name("value")?;
+            matches!(value.kind(), "arrow_function" | "function_expression" | "function")
+                .then_some(())?;
+            named(declarator, source, "function", "name")
+        }
+        "decorated_definition" => describe(node.child_by_field_name("definition")?, source),
+        "export_statement" => describe(node.child_by_field_name("declaration")?, source),
+        "template_declaration" => {
+            let mut cursor = node.walk();
+            let inner = node.named_children(&mut cursor).last()?;
+            describe(inner, source)
+        }
+        "declaration" | "field_declaration" => {
+            let mut cursor = node.walk();
+            let specifier = node
+                .named_children(&mut cursor)
+                .find(|n| n.kind().ends_with("_specifier") && n.child_by_field_name("body").is_some())?;
+            describe(specifier, source)
+        }
+        _ => None,
+    }
+}
+
+fn is_attached(node: Node) -> bool {
+    node.kind().contains("comment") || node.kind() == "attribute_item"
+}
+
+fn line_span(source: &str, range: Range<usize>) -> Range<usize> {
+    let line_start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let start = if source[line_start..range.start].trim().is_empty() {
+        line_start
+    } else {
+        range.start
+    };
+    let rest = &source[range.end..];
+    let line_end = rest.find('\n').map_or(source.len(), |i| range.end + i + 1);
+    let end = if source[range.end..line_end].trim_matches(|c: char| c.is_whitespace() || c == ';').is_empty() {
+        line_end
+    } else {
+        range.end
+    };
+    start..end
+}
+
+struct Walker<'s> {
+    source: &'s str,
+    separator: &'static str,
+    max_bytes: usize,
+    units: Vec<SymbolUnit>,
+}
+
+impl Walker<'_> {
+    fn push(&mut self, range: Range<usize>, symbol: Option<Symbol>, merge: bool) {
+        if let Some(last) = self.units.last_mut() {
+            if merge && last.symbol == symbol && range.end - last.range.start <= self.max_bytes {
+                last.range.end = range.end;
+                return;
+            }
+        }
+        self.units.push(SymbolUnit { range, symbol });
+    }
+
+    fn scope(&mut self, parent: Node, range: Range<usize>, container: Option<&Symbol>) {
+        let mut cursor = parent.walk();
+        let children: Vec<Node> = parent.named_children(&mut cursor).collect();
+        let first_unit = self.units.len();
+        let mut attached: Option<Node> = None;
+
+        for (i, child) in children.iter().enumerate() {
+            if is_attached(*child) {
+                let next = children.get(i + 1);
+                let adjacent = next.is_some_and(|n| n.start_position().row <= child.end_position().row + 1);
+                if adjacent && attached.is_none() {
+                    attached = Some(*child);
+                }
+                if adjacent {
+                    continue;
+                }
+            }
+            let start = attached.take().map_or(child.start_byte(), |a| a.start_byte());
+            let span = line_span(self.source, start..child.end_byte());
+            match describe(*child, self.source) {
+                Some(item) => self.item(span, item, container),
+                None => self.push(span, container.cloned(), true),
+            }
+        }
+
+        if self.units.len() > first_unit {
+            self.units[first_unit].range.start = range.start;
+            self.units.last_mut().unwrap().range.end = range.end;
+        } else {
+            self.push(range, container.cloned(), false);
+        }
+    }
+
+    fn item(&mut self, span: Range<usize>, item: Item, container: Option<&Symbol>) {
+        let symbol = match container {
+            Some(outer) => Symbol {
+                name: format!("{}{}{}", outer.name, self.separator, item.name),
+                kind: match (item.kind, outer.kind.as_str()) {
+                    ("function", "namespace" | "module") => "function",
+                    ("function", _) => "method",
+                    (kind, _) => kind,
+                }
+                .to_string(),
+            },
+            None => Symbol {
+                name: item.name,
+                kind: item.kind.to_string(),
+            },
+        };
+        let nested = item.body.is_some_and(|body| {
+            let mut cursor = body.walk();
+            let found = body.named_children(&mut cursor).any(|n| describe(n, self.source).is_some());
+            found
+        });
+        match item.body {
+            Some(body) if span.len() > self.max_bytes && nested => self.scope(body, span, Some(&symbol)),
+            _ => self.push(span, Some(symbol), false),
+        }
+    }
+}
+
+pub fn symbol_units(text: &str, ext: &str, max_bytes: usize) -> Option<Vec<SymbolUnit>> {
+    let mut parser = Parser::new();
+    parser.set_language(&language(ext)?).ok()?;
+    let tree = parser.parse(text, None)?;
+    let root = tree.root_node();
+
+    let mut walker = Walker {
+        source: text,
+        separator: separator(ext),
+        max_bytes,
+        units: Vec::new(),
+    };
+    walker.scope(root, 0..text.len(), None);
+    if walker.units.iter().all(|u| u.symbol.is_none()) {
+        return None;
+    }
+    if text.len() <= max_bytes {
+        let mut symbols = walker.units.iter().filter_map(|u| u.symbol.clone());
+        let symbol = symbols.next().filter(|_| symbols.next().is_none());
+        return Some(vec![SymbolUnit { range: 0..text.len(), symbol }]);
+    }
+    Some(walker.units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(text: &str, ext: &str, max_bytes: usize) -> Vec<(String, String)> {
+        symbol_units(text, ext, max_bytes)
+            .unwrap()
+            .into_iter()
+            .filter_map(|u| u.symbol.map(|s| (s.name, s.kind)))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_items_and_nested_impls() {
+        let code = format!(
+            "use std::io;\n\n/// Reads the config.\n#[derive(Debug)]\npub struct Config {{\n    path: String,\n}}\n\nimpl Config {{\n    /// Opens it.\n    pub fn open() -> Self {{\n{}    }}\n\n    fn close(&self) {{}}\n}}\n",
+            "        let x = 1;\n".repeat(20)
+        );
+        let units = symbol_units(&code, "rs", 200).unwrap();
+        let config = units.iter().find(|u| u.symbol.as_ref().is_some_and(|s| s.name == "Config")).unwrap();
+        assert!(code[config.range.clone()].starts_with("/// Reads the config.\n#[derive(Debug)]\npub struct"));
+        assert_eq!(symbols(&code, "rs", 200), vec![
+            ("Config".to_string(), "struct".to_string()),
+            ("Config::open".to_string(), "method".to_string()),
+            ("Config::close".to_string(), "method".to_string()),
+        ]);
+        let open = units.iter().find(|u| u.symbol.as_ref().is_some_and(|s| s.name == "Config::open")).unwrap();
+        assert!(code[open.range.clone()].starts_with("impl Config {\n    /// Opens it.\n"));
+        assert!(code[units.last().unwrap().range.clone()].ends_with("fn close(&self) {}\n}\n"));
+    }
+
+    #[test]
+    fn test_other_languages() {
+        let python = "import os\n\n@cache\ndef load(path):\n    return open(path).read()\n\nclass Store:\n    def get(self, key):\n        return self.data[key]\n\n    def put(self, key, value):\n        self.data[key] = value\n";
+        assert_eq!(symbols(python, "py", 60), vec![
+            ("load".to_string(), "function".to_string()),
+            ("Store.get".to_string(), "method".to_string()),
+            ("Store.put".to_string(), "method".to_string()),
+        ]);
+
+        let ts = "export interface User { id: number }\nexport const greet = (u: User) => `hi ${u.id}`;\nclass Repo<T> {\n  find(id: number): T | undefined { return undefined; }\n}\n";
+        assert_eq!(symbols(ts, "ts", 40), vec![
+            ("User".to_string(), "interface".to_string()),
+            ("greet".to_string(), "function".to_string()),
+            ("Repo.find".to_string(), "method".to_string()),
+        ]);
+
+        let go = "package main\n\ntype Server struct {\n\tAddr string\n}\n\nfunc (s *Server) Start() error {\n\treturn nil\n}\n";
+        assert_eq!(symbols(go, "go", 40), vec![
+            ("Server".to_string(), "struct".to_string()),
+            ("Start".to_string(), "method".to_string()),
+        ]);
+
+        let java = "package app;\n\npublic class Cache<K, V> {\n    private final Map<K, V> items = new HashMap<>();\n\n    public V get(K key) {\n        return items.get(key);\n    }\n}\n";
+        assert_eq!(symbols(java, "java", 60), vec![
+            ("Cache".to_string(), "class".to_string()),
+            ("Cache.get".to_string(), "method".to_string()),
+        ]);
+
+        let cpp = "namespace net {\nint parse(const char* s) {\n  if (s) {\n    return 1;\n  }\n  return 0;\n}\n}\nstruct Point { int x; int y; };\n";
+        assert_eq!(symbols(cpp, "cpp", 40), vec![
+            ("net::parse".to_string(), "function".to_string()),
+            ("Point".to_string(), "struct".to_string()),
+        ]);
+
+        assert_eq!(symbol_units("int x = 1;\n", "c", 400), None);
+        assert_eq!(
+            symbol_units("fn main() {}\n", "rs", 400).unwrap()[0].symbol,
+            Some(Symbol { name: "main".to_string(), kind: "function".to_string() })
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use std::ops::Range;
+use std::path::Path;
+
+const MAX_COLUMNS: usize = 40;
+const MAX_ROWS: usize = 20_000;
+const MAX_VALUE_BYTES: usize = 300;
+
+struct Record {
+    fields: Vec<String>,
+    range: Range<usize>,
+}
+
+pub struct RowChunk {
+    pub text: String,
+    pub header_len: usize,
+    pub range: Range<usize>,
+    pub rows: Vec<RowSpan>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowSpan {
+    pub rendered: Range<usize>,
+    pub source: Range<usize>,
+}
+
+pub fn source_offset(rows: &[RowSpan], pos: usize, end: usize) -> usize {
+    rows.iter().find(|row| row.rendered.end > pos).map_or(end, |row| {
+        let offset = pos.saturating_sub(row.rendered.start);
+        row.source.start + offset * row.source.len() / row.rendered.len().max(1)
+    })
+}
+
+pub fn is_tabular_extension(ext: &str) -> bool {
+    matches!(ext, "csv" | "tsv")
+}
+
+pub fn is_tabular_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| is_tabular_extension(&e.to_lowercase()))
+}
+
+fn delimiter(ext: &str) -> char {
+    if ext == "tsv" {
+        '\t'
+    } else {
+        ','
+    }
+}
+
+fn parse_records(text: &str, delimiter: char, limit: usize) -> Vec<Record> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if quoted {
+            if c != '"' {
+                field.push(c);
+            } else if chars.next_if(|&(_, next)| next == '"').is_some() {
+                field.push('"');
+            } else {
+                quoted = false;
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            '\r' if chars.peek().is_some_and(|&(_, next)| next == '\n') => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                let row = std::mem::take(&mut fields);
+                if row.iter().any(|f| !f.trim().is_empty()) {
+                    records.push(Record { fields: row, range: start..i + 1 });
+                    if records.len() >= limit {
+                        return records;
+                    }
+                }
+                start = i + 1;
+            }
+            c if c == delimiter => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+
+    fields.push(field);
+    if fields.iter().any(|f| !f.trim().is_empty()) {
+        records.push(Record { fields, range: start..text.len() });
+    }
+    records
+}
+
+fn clip(value: &str) -> &str {
+    if value.len() <= MAX_VALUE_BYTES {
+        return value;
+    }
+    let mut end = MAX_VALUE_BYTES;
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    &value[..end]
+}
+
+fn render_row(columns: &[String], fields: &[String]) -> String {
+    fields
+        .iter()
+        .take(MAX_COLUMNS)
+        .enumerate()
+        .map(|(i, value)| (i, value.trim()))
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(i, value)| match columns.get(i).filter(|c| !c.is_empty()) {
+            Some(column) => format!("{}: {}", column, clip(value)),
+            None => format!("column {}: {}", i + 1, clip(value)),
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+pub fn row_chunks(text: &str, ext: &str, max_bytes: usize) -> Vec<RowChunk> {
+    let delimiter = delimiter(ext);
+    let records = parse_records(text, delimiter, MAX_ROWS + 1);
+    let Some((header, rows)) = records.split_first() else {
+        return Vec::new();
+    };
+
+    let columns: Vec<String> = header
+        .fields
+        .iter()
+        .take(MAX_COLUMNS)
+        .map(|c| clip(c.trim()).to_string())
+        .collect();
+    let header_line = if header.fields.len() > MAX_COLUMNS {
+        columns.join(&delimiter.to_string())
+    } else {
+        text[header.range.clone()].trim_end().to_string()
+    };
+    if rows.is_empty() {
+        return vec![RowChunk {
+            text: header_line,
+            header_len: 0,
+            range: header.range.clone(),
+            rows: Vec::new(),
+        }];
+    }
+    let chunk = |body: &str, range: Range<usize>, rows: Vec<RowSpan>| RowChunk {
+        text: format!("{}\n{}", header_line, body),
+        header_len: header_line.len() + 1,
+        range,
+        rows,
+    };
+
+    let mut chunks = Vec::new();
+    let mut body = String::new();
+    let mut spans = Vec::new();
+    let mut range = 0..0;
+    for row in rows {
+        let rendered = render_row(&columns, &row.fields);
+        if !body.is_empty() && header_line.len() + body.len() + rendered.len() + 2 > max_bytes {
+            chunks.push(chunk(&body, range.clone(), std::mem::take(&mut spans)));
+            body.clear();
+        }
+        if body.is_empty() {
+            range = row.range.clone();
+        } else {
+            body.push('\n');
+        }
+        let start = body.len();
+        body.push_str(&rendered);
+        spans.push(RowSpan { rendered: start..body.len(), source: row.range.clone() });
+        range.end = row.range.end;
+    }
+    chunks.push(chunk(&body, range, spans));
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows_repeat_header_and_respect_quotes() {
+        let csv = "id,name,notes\r\n1,Ada,\"likes \"\"math\"\", \nand engines\"\n2,Grace,\n\n3,Linus,kernel\n";
+        let chunks = row_chunks(csv, "csv", 70);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(
+            chunks[0].text,
+            "id,name,notes\nid: 1 | name: Ada | notes: likes \"math\", \nand engines"
+        );
+        assert_eq!(
+            chunks[1].text,
+            "id,name,notes\nid: 2 | name: Grace\nid: 3 | name: Linus | notes: kernel"
+        );
+        assert_eq!(&csv[chunks[1].range.clone()], "2,Grace,\n\n3,Linus,kernel\n");
+        let body = &chunks[1].text[chunks[1].header_len..];
+        let rows: Vec<(&str, &str)> = chunks[1]
+            .rows
+            .iter()
+            .map(|row| (&body[row.rendered.clone()], &csv[row.source.clone()]))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("id: 2 | name: Grace", "2,Grace,\n"),
+                ("id: 3 | name: Linus | notes: kernel", "3,Linus,kernel\n"),
+            ]
+        );
+        assert_eq!(source_offset(&chunks[1].rows, 0, csv.len()), chunks[1].range.start);
+        assert_eq!(source_offset(&chunks[1].rows, body.len(), csv.len()), csv.len());
+        assert_eq!(&chunks[1].text[..chunks[1].header_len], "id,name,notes\n");
+
+        let tsv = "city\tpopulation\nOslo\t709037\tNorway\n";
+        let chunks = row_chunks(tsv, "tsv", 800);
+        assert_eq!(chunks[0].text, "city\tpopulation\ncity: Oslo | population: 709037 | column 3: Norway");
+    }
+
+    #[test]
+    fn test_wide_and_long_files_are_capped() {
+        let header: Vec<String> = (0..60).map(|i| format!("c{}", i)).collect();
+        let row: Vec<String> = (0..60).map(|i| i.to_string()).collect();
+        let mut csv = format!("{}\n", header.join(","));
+        for _ in 0..MAX_ROWS + 50 {
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        let chunks = row_chunks(&csv, "csv", 4000);
+        assert!(chunks[0].text.starts_with("c0,c1,"));
+        assert!(chunks[0].text.lines().next().unwrap().ends_with(",c39"));
+        assert!(!chunks[0].text.contains("c40"));
+        let rows: usize = chunks.iter().map(|chunk| chunk.text.lines().count() - 1).sum();
+        assert_eq!(rows, MAX_ROWS);
+    }
+}
@@ -0,0 +1,122 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use log::warn;
+
+const HEAD_BYTES: u64 = 1024;
+const STATE_PREFIX: &str = "tail:";
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TailState {
+    pub offset: u64,
+    pub lines: usize,
+    pub chunks: usize,
+    pub head: String,
+}
+
+impl TailState {
+    pub fn parse(file_hash: &str) -> Option<Self> {
+        let mut parts = file_hash.strip_prefix(STATE_PREFIX)?.splitn(4, ':');
+        Some(Self {
+            offset: parts.next()?.parse().ok()?,
+            lines: parts.next()?.parse().ok()?,
+            chunks: parts.next()?.parse().ok()?,
+            head: parts.next()?.to_string(),
+        })
+    }
+
+    pub fn to_file_hash(&self) -> String {
+        format!(
+            "{}{}:{}:{}:{}",
+            STATE_PREFIX, self.offset, self.lines, self.chunks, self.head
+        )
+    }
+}
+
+pub fn build_matcher(patterns: &[String]) -> Option<GlobSet> {
+    if patterns.is_empty() {
+        return None;
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        match Glob::new(pattern) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(e) => warn!("Ignoring invalid tail pattern {}: {}", pattern, e),
+        }
+    }
+    builder.build().ok()
+}
+
+pub fn is_match(matcher: &GlobSet, path: &Path) -> bool {
+    matcher.is_match(path) || path.file_name().is_some_and(|name| matcher.is_match(name))
+}
+
+fn hash_head(file: &mut File, offset: u64) -> io::Result<String> {
+    let mut head = Vec::new();
+    file.seek(SeekFrom::Start(0))?;
+    file.take(offset.min(HEAD_BYTES)).read_to_end(&mut head)?;
+    Ok(super::file_io::hash_bytes(&head))
+}
+
+pub fn head_hash(path: &Path, offset: u64) -> io::Result<String> {
+    hash_head(&mut File::open(path)?, offset)
+}
+
+pub fn read_appended(path: &Path, previous: &TailState, max_bytes: u64) -> io::Result<Option<Vec<u8>>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    if len < previous.offset {
+        return Ok(None);
+    }
+    if previous.offset > 0 && hash_head(&mut file, previous.offset)? != previous.head {
+        return Ok(None);
+    }
+
+    let mut appended = Vec::new();
+    file.seek(SeekFrom::Start(previous.offset))?;
+    file.take((len - previous.offset).min(max_bytes)).read_to_end(&mut appended)?;
+    let complete = appended.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+    appended.truncate(complete);
+    Ok(Some(appended))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_appended_lines_and_rotation() {
+        let path = std::env::temp_dir().join("rememex_tail_test.log");
+        std::fs::write(&path, "boot\nready\n").unwrap();
+        let state = TailState {
+            offset: 11,
+            lines: 2,
+            chunks: 1,
+            head: head_hash(&path, 11).unwrap(),
+        };
+        assert_eq!(TailState::parse(&state.to_file_hash()), Some(state.clone()));
+        assert_eq!(TailState::parse("0f3a9c"), None);
+
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"GET /health 200\npartial").unwrap();
+        assert_eq!(read_appended(&path, &state, u64::MAX).unwrap(), Some(b"GET /health 200\n".to_vec()));
+        file.write_all(b"\nGET /ready 200\n").unwrap();
+        assert_eq!(read_appended(&path, &state, 20).unwrap(), Some(b"GET /health 200\n".to_vec()));
+
+        std::fs::write(&path, "rotated\n").unwrap();
+        assert_eq!(read_appended(&path, &state, u64::MAX).unwrap(), None);
+        std::fs::write(&path, "Boot\nready\nmore\n").unwrap();
+        assert_eq!(read_appended(&path, &state, u64::MAX).unwrap(), None);
+
+        let matcher = build_matcher(&["*.log".to_string(), "**/services/*.out".to_string()]).unwrap();
+        assert!(is_match(&matcher, Path::new("/var/log/app.log")));
+        assert!(is_match(&matcher, Path::new("/srv/services/api.out")));
+        assert!(!is_match(&matcher, Path::new("/srv/api.out")));
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -16,6 +16,8 @@ use tauri_plugin_global_shortcut::ShortcutState;
 use tokio::sync::Mutex;
 
 use config::{ConfigState, EmbeddingProviderConfig, get_embedding_model, parse_hotkey};
+use indexer::jobs::{JobManager, JobState};
+use indexer::maintenance::{MaintenanceState, MAINTENANCE_INTERVAL};
 use state::{DbState, ModelState, ProviderState, RerankerState};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -158,6 +160,32 @@ pub fn run() {
             let watcher_state = watcher::new_state();
             app.manage(watcher_state.clone());
 
+            let job_state: JobState = Arc::new(JobManager::new(app_data.join("jobs")));
+            app.manage(job_state.clone());
+
+            let control_app = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    for (job_id, action) in job_state.take_control_requests() {
+                        if let Err(e) = commands::apply_job_action(&control_app, &job_id, action).await {
+                            warn!("Job control request for {} failed: {}", job_id, e);
+                        }
+                    }
+                }
+            });
+
+            let maintenance_state: MaintenanceState = Arc::default();
+            app.manage(maintenance_state);
+
+            let maintenance_app = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    tokio::time::sleep(MAINTENANCE_INTERVAL).await;
+                    commands::run_scheduled_maintenance(&maintenance_app).await;
+                }
+            });
+
             let models_path = app_data.join("models");
             std::fs::create_dir_all(&models_path).ok();
 
@@ -228,6 +256,7 @@ pub fn run() {
                                             watcher_provider_state.clone(),
                                             watcher_app.clone(),
                                         ).await;
+                                        commands::resume_interrupted_jobs(&watcher_app).await;
 
                                         break;
                                     }
@@ -265,6 +294,7 @@ pub fn run() {
                                 watcher_provider_state.clone(),
                                 watcher_app.clone(),
                             ).await;
+                            commands::resume_interrupted_jobs(&watcher_app).await;
                         });
                     }
                 }
@@ -316,7 +346,17 @@ pub fn run() {
             commands::update_config,
             commands::add_annotation,
             commands::get_annotations,
-            commands::delete_annotation
+            commands::delete_annotation,
+            commands::confirm_index_rebuild,
+            commands::list_indexing_jobs,
+            commands::cancel_indexing_job,
+            commands::pause_indexing_job,
+            commands::resume_indexing_job,
+            commands::get_maintenance_status,
+            commands::run_index_maintenance,
+            commands::export_container,
+            commands::import_container,
+            commands::relocate_root
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
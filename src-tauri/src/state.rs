@@ -1,5 +1,6 @@
 use serde::Serialize;
 
+use crate::indexer::db::ChunkMeta;
 use crate::indexer::embedding_provider::EmbeddingProvider;
 
 pub struct DbState {
@@ -28,6 +29,8 @@ pub struct SearchResult {
     pub path: String,
     pub snippet: String,
     pub score: f32,
+    #[serde(flatten)]
+    pub meta: Option<ChunkMeta>,
 }
 
 #[derive(Serialize, Clone)]
@@ -35,6 +38,8 @@ pub struct IndexingProgress {
     pub current: usize,
     pub total: usize,
     pub path: String,
+    pub pruned: usize,
+    pub job_id: Option<String>,
 }
 
 #[derive(Serialize, Clone)]
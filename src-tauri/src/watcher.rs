@@ -10,7 +10,7 @@ use notify_debouncer_full::notify::{self, RecursiveMode};
 use tauri::{AppHandle, Emitter};
 use tokio::sync::Mutex;
 
-use crate::config::{get_table_name, ConfigState};
+use crate::config::{get_table_name, ConfigState, ContainerInfo, IndexingConfig};
 use crate::indexer;
 use crate::state::{IndexingProgress, ProviderState};
 
@@ -50,18 +50,10 @@ pub async fn restart(
     let handle = {
         let config = config_state.config.lock().await;
         let table_name = get_table_name(&config.active_container);
-        let paths = config
-            .containers
-            .get(&config.active_container)
-            .map(|info| info.indexed_paths.clone())
-            .unwrap_or_default();
-        let wc = WatcherConfig {
-            use_git_history: config.indexing.use_git_history,
-            chunk_size: config.indexing.chunk_size,
-            chunk_overlap: config.indexing.chunk_overlap,
-        };
+        let container = config.containers.get(&config.active_container).cloned();
+        let indexing = config.indexing.clone();
         drop(config);
-        start_watcher(paths, db, provider_state, table_name, app, wc)
+        container.and_then(|info| start_watcher(info, db, provider_state, table_name, app, indexing))
     };
 
     info!("File watcher restarted");
@@ -69,20 +61,15 @@ pub async fn restart(
     *guard = handle;
 }
 
-struct WatcherConfig {
-    use_git_history: bool,
-    chunk_size: Option<usize>,
-    chunk_overlap: Option<usize>,
-}
-
 fn start_watcher(
-    paths: Vec<String>,
+    container: ContainerInfo,
     db: lancedb::Connection,
     provider_state: Arc<Mutex<ProviderState>>,
     table_name: String,
     app: AppHandle,
-    wc: WatcherConfig,
+    indexing: IndexingConfig,
 ) -> Option<WatcherHandle> {
+    let paths = container.indexed_paths.clone();
     if paths.is_empty() {
         debug!("No paths to watch, skipping watcher");
         return None;
@@ -113,6 +100,8 @@ fn start_watcher(
 
     let rt = tokio::runtime::Handle::current();
     let indexing_lock = Arc::new(Mutex::new(()));
+    let container = Arc::new(container);
+    let indexing = Arc::new(indexing);
     std::thread::spawn(move || {
         while let Ok(events) = rx.recv() {
             let mut changed: HashSet<PathBuf> = HashSet::new();
@@ -154,6 +143,8 @@ fn start_watcher(
             let tn = table_name.clone();
             let app = app.clone();
             let lock = indexing_lock.clone();
+            let container = container.clone();
+            let indexing = indexing.clone();
             let changed: Vec<PathBuf> = changed.into_iter().collect();
             let deleted: Vec<PathBuf> = deleted.into_iter().collect();
             let total = changed.len() + deleted.len();
@@ -167,27 +158,35 @@ fn start_watcher(
                     current: 0,
                     total,
                     path: format!("Auto-reindexing {} files...", total),
+                    pruned: 0,
+                    job_id: None,
                 });
 
                 let mut count = 0usize;
 
                 for path in &deleted {
-                    let path_str = path.to_string_lossy().to_string();
-                    if let Err(e) = indexer::delete_file_from_index(&path_str, &tn, &db).await {
-                        error!("Failed to remove {} from index: {}", path_str, e);
+                    if let Some(root) = container.root_for(path) {
+                        let path_str = path.to_string_lossy().to_string();
+                        if let Err(e) = indexer::delete_file_from_index(&path_str, &root, &tn, &db).await {
+                            error!("Failed to remove {} from index: {}", path_str, e);
+                        }
                     }
                     count += 1;
                 }
 
                 for path in &changed {
-                    if let Err(e) = indexer::index_single_file(path, &tn, &db, &ms, wc.use_git_history, wc.chunk_size, wc.chunk_overlap).await {
-                        error!("Failed to index {}: {}", path.display(), e);
+                    if let Some(root) = container.root_for(path) {
+                        if let Err(e) = indexer::index_single_file(path, &root, &tn, &db, &ms, &indexing).await {
+                            error!("Failed to index {}: {}", path.display(), e);
+                        }
                     }
                     count += 1;
                     let _ = app.emit("indexing-progress", IndexingProgress {
                         current: count,
                         total,
                         path: path.to_string_lossy().to_string(),
+                        pruned: 0,
+                        job_id: None,
                     });
                 }
 
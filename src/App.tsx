@@ -12,7 +12,7 @@ import ResultsList from "./components/ResultsList";
 import StatusBar from "./components/StatusBar";
 import TitleBar from "./components/TitleBar";
 import Settings from "./components/Settings";
-import type { SearchResult, IndexingProgress, ContainerItem } from "./types";
+import type { SearchResult, IndexingProgress, ContainerItem, IndexRebuildRequired } from "./types";
 import logoSrc from "./assets/rememex.png";
 import "./App.css";
 
@@ -118,6 +118,7 @@ function App() {
     let remoteApiKey = "";
     let remoteModel = "";
     let remoteDimensions = 1024;
+    let remoteMaxTokens = 0;
 
     if (providerType === "local") {
       const step2 = await modal.prompt({
@@ -147,6 +148,7 @@ function App() {
           { key: "api_key", label: "API Key", type: "password" as const, placeholder: "sk-..." },
           { key: "model", label: "Model Name", placeholder: "text-embedding-3-small" },
           { key: "dimensions", label: "Dimensions", type: "number" as const, defaultValue: "1024", placeholder: "1024" },
+          { key: "max_tokens", label: "Max Tokens", type: "number" as const, placeholder: "8191" },
         ],
         confirmText: t("dialog_create"),
       });
@@ -157,6 +159,7 @@ function App() {
       remoteApiKey = (step2.values.api_key || "").trim();
       remoteModel = (step2.values.model || "").trim();
       remoteDimensions = Number.parseInt(step2.values.dimensions || "1024", 10) || 1024;
+      remoteMaxTokens = Number.parseInt(step2.values.max_tokens || "0", 10) || 0;
     }
 
     try {
@@ -169,6 +172,7 @@ function App() {
         remoteApiKey: remoteApiKey || null,
         remoteModel: remoteModel || null,
         remoteDimensions: remoteDimensions || null,
+        remoteMaxTokens: remoteMaxTokens || null,
       });
       await fetchContainers();
       await handleSwitchContainer(step1.values.name.trim());
@@ -279,11 +283,39 @@ function App() {
       setIndexProgress(null);
     });
 
+    const unlistenRebuildRequired = listen<IndexRebuildRequired>("index-rebuild-required", async (event) => {
+      setIsIndexing(false);
+      setIndexProgress(null);
+      const result = await modal.confirm({
+        title: t("dialog_destructive_rebuild_title"),
+        message: t("dialog_destructive_rebuild_message", { reason: event.payload.reason }),
+        icon: "warning",
+        confirmText: t("dialog_destructive_rebuild_confirm"),
+        confirmVariant: "danger",
+      });
+      if (!result.confirmed) {
+        setStatus(t("status_rebuild_needed"));
+        return;
+      }
+      try {
+        setStatus(t("status_rebuilding"));
+        setIsIndexing(true);
+        setResults([]);
+        const msg = await invoke<string>("confirm_index_rebuild", { table: event.payload.table });
+        setStatus(msg);
+      } catch (err) {
+        setStatus(String(err));
+      } finally {
+        setIsIndexing(false);
+      }
+    });
+
     return () => {
       unlistenProgress.then((f) => f());
       unlistenComplete.then((f) => f());
       unlistenModelLoaded.then((f) => f());
       unlistenModelError.then((f) => f());
+      unlistenRebuildRequired.then((f) => f());
     };
   }, []);
 
@@ -377,7 +409,7 @@ function App() {
 
   async function handleOpenFile(path: string) {
     try {
-      await openPath(path);
+      await openPath(path.split("!/")[0]);
     } catch (e) {
       console.error("Failed to open file:", path, e);
       setStatus(`Failed to open: ${String(e)}`);
@@ -495,7 +527,7 @@ function App() {
         setSettingsOpen(false);
         if (isFirstRunRef.current) {
           isFirstRunRef.current = false;
-          invoke<{ provider_type: string; remote_endpoint: string; remote_api_key: string; remote_model: string; remote_dimensions: number; embedding_model: string }>("get_config").then((c) => {
+          invoke<{ provider_type: string; remote_endpoint: string; remote_api_key: string; remote_model: string; remote_dimensions: number; remote_max_tokens: number | null; embedding_model: string }>("get_config").then((c) => {
             invoke("update_config", {
               updates: {
                 provider_type: c.provider_type,
@@ -503,6 +535,7 @@ function App() {
                 remote_api_key: c.remote_api_key,
                 remote_model: c.remote_model,
                 remote_dimensions: c.remote_dimensions,
+                remote_max_tokens: c.remote_max_tokens ?? 0,
                 embedding_model: c.embedding_model,
               }
             }).catch(() => { });
@@ -79,7 +79,8 @@ const Row = ({ index, style, results, selectedIndex, setSelectedIndex, handleOpe
                         {isAnnotation ? result.snippet.replace("[annotation] ", "") : (result.snippet || <span className="italic opacity-50">{noPreviewText}</span>)}
                     </div>
                     <div className="truncate text-[10px] opacity-40 mt-0.5 font-mono">
-                        {result.path}
+                        {result.path}{result.page ? ` (p. ${result.page})` : result.cell ? ` (cell ${result.cell})` : result.start_line ? `:${result.start_line}-${result.end_line}` : ""}
+                        {result.breadcrumb && ` · ${result.breadcrumb}`}
                     </div>
                 </div>
             </button>
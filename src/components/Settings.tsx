@@ -15,6 +15,7 @@ interface AppConfig {
     embedding_model: string;
     chunk_size: number | null;
     chunk_overlap: number | null;
+    chunk_unit: "bytes" | "tokens";
     extra_extensions: string[];
     excluded_extensions: string[];
     provider_type: string;
@@ -22,6 +23,7 @@ interface AppConfig {
     remote_api_key: string;
     remote_model: string;
     remote_dimensions: number;
+    remote_max_tokens: number | null;
     first_run: boolean;
     use_reranker: boolean;
     hyde_enabled: boolean;
@@ -7,6 +7,7 @@ interface IndexingConfig {
     use_git_history: boolean;
     chunk_size: number | null;
     chunk_overlap: number | null;
+    chunk_unit: "bytes" | "tokens";
 }
 
 interface Props {
@@ -79,6 +80,15 @@ export default function IndexingSettings({
                                 updateField({ chunk_overlap: v });
                             }}
                         />
+                        <select
+                            className="settings-select"
+                            value={config.chunk_unit}
+                            aria-label={t("settings_chunk_unit")}
+                            onChange={(e) => updateField({ chunk_unit: e.target.value })}
+                        >
+                            <option value="bytes">{t("settings_chunk_unit_bytes")}</option>
+                            <option value="tokens">{t("settings_chunk_unit_tokens")}</option>
+                        </select>
                     </div>
                 }
             />
@@ -1,4 +1,4 @@
-import { Brain, Cloud, Server, Link, Key, Hash } from "lucide-react";
+import { Brain, Cloud, Server, Link, Key, Hash, Ruler } from "lucide-react";
 import { useLocale } from "../../i18n";
 import { SettingsRow } from "./SettingsRow";
 import "./ProviderSettings.css";
@@ -20,19 +20,21 @@ interface Props {
     remoteApiKeyDraft: string;
     remoteModelDraft: string;
     remoteDimsDraft: string;
+    remoteMaxTokensDraft: string;
     providerChanged: boolean;
     setRemoteEndpointDraft: (v: string) => void;
     setRemoteApiKeyDraft: (v: string) => void;
     setRemoteModelDraft: (v: string) => void;
     setRemoteDimsDraft: (v: string) => void;
+    setRemoteMaxTokensDraft: (v: string) => void;
     setProviderChanged: (v: boolean) => void;
     updateField: (updates: Record<string, unknown>) => Promise<void>;
 }
 
 export default function ProviderSettings({
     config, remoteEndpointDraft, remoteApiKeyDraft, remoteModelDraft, remoteDimsDraft,
-    providerChanged, setRemoteEndpointDraft, setRemoteApiKeyDraft, setRemoteModelDraft,
-    setRemoteDimsDraft, setProviderChanged, updateField,
+    remoteMaxTokensDraft, providerChanged, setRemoteEndpointDraft, setRemoteApiKeyDraft,
+    setRemoteModelDraft, setRemoteDimsDraft, setRemoteMaxTokensDraft, setProviderChanged, updateField,
 }: Readonly<Props>) {
     const { t } = useLocale();
 
@@ -66,6 +68,7 @@ export default function ProviderSettings({
                                     remote_api_key: remoteApiKeyDraft,
                                     remote_model: remoteModelDraft,
                                     remote_dimensions: Number.parseInt(remoteDimsDraft, 10) || 1024,
+                                    remote_max_tokens: Number.parseInt(remoteMaxTokensDraft, 10) || 0,
                                 });
                             }}
                         >
@@ -170,6 +173,24 @@ export default function ProviderSettings({
                             />
                         }
                     />
+                    <SettingsRow
+                        icon={<Ruler size={14} />}
+                        label={t("settings_remote_max_tokens")}
+                        desc={t("settings_remote_max_tokens_desc")}
+                        control={
+                            <input
+                                type="number"
+                                className="settings-number-input"
+                                value={remoteMaxTokensDraft}
+                                placeholder="8191"
+                                min={0}
+                                max={131072}
+                                onChange={(e) => setRemoteMaxTokensDraft(e.target.value)}
+                                onBlur={() => updateField({ remote_max_tokens: Number.parseInt(remoteMaxTokensDraft, 10) || 0 })}
+                                onKeyDown={(e) => { if (e.key === "Enter") updateField({ remote_max_tokens: Number.parseInt(remoteMaxTokensDraft, 10) || 0 }); }}
+                            />
+                        }
+                    />
                 </div>
             )}
 
@@ -2,12 +2,87 @@ export interface SearchResult {
     path: string;
     snippet: string;
     score: number;
+    start_line?: number;
+    end_line?: number;
+    start_byte?: number;
+    end_byte?: number;
+    chunk_index?: number;
+    language?: string;
+    file_size?: number;
+    page?: number;
+    cell?: number;
+    encoding?: string;
+    symbol?: string;
+    symbol_kind?: string;
+    breadcrumb?: string;
+    match_start_line?: number;
+    match_end_line?: number;
 }
 
 export interface IndexingProgress {
     current: number;
     total: number;
     path: string;
+    pruned: number;
+    job_id: string | null;
+}
+
+export interface IndexingJob {
+    id: string;
+    container: string;
+    root_dir: string;
+    status: "running" | "paused" | "completed" | "cancelled" | "failed" | "interrupted";
+    processed: number;
+    checkpoint: string | null;
+    files_indexed: number;
+    skipped: { path: string; reason: string }[];
+    error: string | null;
+    started_at: number;
+    updated_at: number;
+}
+
+export interface MaintenanceStatus {
+    table: string;
+    rows: number;
+    version: number;
+    has_fts_index: boolean;
+    has_vector_index: boolean;
+    unindexed_fts_rows: number;
+    unindexed_vector_rows: number;
+    ann_trained_rows: number;
+    compacted_version: number;
+    last_run: number | null;
+    last_index_refresh: number | null;
+    last_ann_rebuild: number | null;
+    last_compaction: number | null;
+    error: string | null;
+}
+
+export interface ProviderIdentity {
+    kind: string;
+    model: string;
+    dimension: number;
+}
+
+export interface SnapshotManifest {
+    format_version: number;
+    container: string;
+    info: {
+        description: string;
+        indexed_paths: string[];
+    };
+    provider: ProviderIdentity;
+    roots: string[];
+    chunk_rows: number;
+    annotation_rows: number;
+    chunk_schema_version: number;
+    annotation_schema_version: number;
+    created_at: number;
+}
+
+export interface IndexRebuildRequired {
+    table: string;
+    reason: string;
 }
 
 export interface ContainerItem {